@@ -31,7 +31,47 @@ fn main() {
     println!("cargo:rustc-env=GIT_VERSION={}", git_describe);
     println!("cargo:rustc-env=BUILD_DATE={}", build_date);
     println!("cargo:rustc-env=BUILT_BY={}", built_by);
+    println!(
+        "cargo:rustc-env=TARGET={}",
+        std::env::var("TARGET").unwrap_or_else(|_| "unknown".to_string())
+    );
 
     // Rerun if git changes
     println!("cargo:rerun-if-changed=.git/HEAD");
+
+    // Mark feature-gated items in the docs.rs build, which runs on nightly
+    println!("cargo::rustc-check-cfg=cfg(docsrs)");
+    println!("cargo:rerun-if-env-changed=DOCS_RS");
+    if std::env::var_os("DOCS_RS").is_some() {
+        println!("cargo:rustc-cfg=docsrs");
+    }
+
+    #[cfg(feature = "ffi")]
+    generate_ffi_header();
+}
+
+/// Write the C header for `src/ffi.rs` to `target/include/wow_patcher.h`.
+#[cfg(feature = "ffi")]
+fn generate_ffi_header() {
+    let manifest_dir = std::path::PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let target_dir = std::env::var_os("CARGO_TARGET_DIR")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| manifest_dir.join("target"));
+
+    let config = cbindgen::Config {
+        language: cbindgen::Language::C,
+        include_guard: Some("WOW_PATCHER_H".to_string()),
+        sys_includes: vec!["stddef.h".to_string(), "stdint.h".to_string()],
+        no_includes: true,
+        usize_is_size_t: true,
+        ..Default::default()
+    };
+
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(manifest_dir.join("src/ffi.rs"))
+        .generate()
+        .expect("Unable to generate C header for the ffi feature")
+        .write_to_file(target_dir.join("include/wow_patcher.h"));
 }
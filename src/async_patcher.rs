@@ -0,0 +1,368 @@
+//! Asynchronous patching API built on tokio.
+//!
+//! [`AsyncPatcher`] wraps a configured [`Patcher`] and performs all file I/O with
+//! `tokio::fs`, running the CPU-bound pattern search on a blocking thread so GUI
+//! applications stay responsive.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use wow_patcher::{AsyncPatcher, Patcher};
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! AsyncPatcher::new(Patcher::new("Wow.exe").output("Wow-patched.exe"))
+//!     .on_progress(|progress| {
+//!         Box::pin(async move {
+//!             println!("{}/{} {}", progress.step_index, progress.total_steps, progress.step_name);
+//!         })
+//!     })
+//!     .patch()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::cmd::execute::{patch_data, preview_patches, write_patched_output};
+use crate::errors::{ErrorCategory, WowPatcherError};
+use crate::patcher::{PatchProgress, Patcher, backup_error};
+use crate::platform::host_macos_version;
+use crate::report::{ConsoleReporter, PatchReport, Reporter};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+/// A boxed, sendable future as returned by progress callbacks
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+type ProgressCallback = Box<dyn Fn(PatchProgress) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// Asynchronous counterpart of [`Patcher`].
+pub struct AsyncPatcher {
+    patcher: Patcher,
+    on_progress: Option<ProgressCallback>,
+}
+
+impl fmt::Debug for AsyncPatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncPatcher")
+            .field("patcher", &self.patcher)
+            .field("on_progress", &self.on_progress.is_some())
+            .finish()
+    }
+}
+
+impl From<Patcher> for AsyncPatcher {
+    fn from(patcher: Patcher) -> Self {
+        Self::new(patcher)
+    }
+}
+
+impl AsyncPatcher {
+    /// Create an `AsyncPatcher` from a configured [`Patcher`].
+    pub fn new(patcher: Patcher) -> Self {
+        Self {
+            patcher,
+            on_progress: None,
+        }
+    }
+
+    /// Register a callback that is awaited after each patch step.
+    ///
+    /// A final message with `step_index == total_steps` is sent once patching
+    /// has completed.
+    pub fn on_progress(
+        mut self,
+        callback: impl Fn(PatchProgress) -> BoxFuture<'static, ()> + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Patch the input executable and return the patched bytes without writing them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input cannot be read, fails validation, or no
+    /// known pattern could be patched.
+    pub async fn patch_in_memory(self) -> Result<Vec<u8>, WowPatcherError> {
+        let mut reporter = ConsoleReporter::new(self.patcher.report_mode);
+        let (data, _) = self.apply(&mut reporter).await?;
+        self.report(PatchProgress::complete(self.patcher.total_steps()))
+            .await;
+        Ok(data)
+    }
+
+    /// Patch the input executable and write the result to the output path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if patching fails or the output cannot be written.
+    pub async fn patch(self) -> Result<(), WowPatcherError> {
+        let mut reporter = ConsoleReporter::new(self.patcher.report_mode);
+        let result = self.patch_with(&mut reporter).await;
+        if let Err(e) = &result {
+            reporter.error(e);
+        }
+        result
+    }
+
+    async fn patch_with(&self, reporter: &mut ConsoleReporter) -> Result<(), WowPatcherError> {
+        if self.patcher.dry_run {
+            let (header, report) = self.preview(reporter).await?;
+            reporter.status(header.trim_end());
+            reporter.finish(&report);
+            self.report(PatchProgress::complete(self.patcher.total_steps()))
+                .await;
+            return Ok(());
+        }
+
+        let (data, mut report) = self.apply(reporter).await?;
+
+        // The input is still untouched, so it is only backed up once patching succeeded
+        if let Some(backup_path) = self.patcher.backup_path() {
+            tokio::fs::copy(&self.patcher.input, &backup_path)
+                .await
+                .map_err(|e| backup_error(&backup_path, e))?;
+        }
+        let output_path = self.patcher.output_path();
+        self.write_output(data, reporter).await?;
+
+        reporter.detail(&format!("✅ Patched executable saved to {:?}", output_path));
+
+        self.report(PatchProgress::complete(self.patcher.total_steps()))
+            .await;
+        report.platform.macos_version = host_macos_version();
+        reporter.finish(&report);
+        Ok(())
+    }
+
+    /// Read the input, then validate it and apply all patches on a blocking thread.
+    ///
+    /// `reporter` goes to the blocking thread with the patching and comes
+    /// back with it, so the warnings it holds reach [`Reporter::finish`].
+    async fn apply(
+        &self,
+        reporter: &mut ConsoleReporter,
+    ) -> Result<(Vec<u8>, PatchReport), WowPatcherError> {
+        let mut data = self.read_input().await?;
+        let patcher = self.patcher.clone();
+        let mut task_reporter = reporter.clone();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let task = tokio::task::spawn_blocking(move || {
+            let result = patcher.patch_options().and_then(|mut opts| {
+                opts.on_progress = Some(Box::new(move |progress| {
+                    let _ = tx.send(progress);
+                }));
+                patch_data(&patcher.input, &mut data, &mut opts, &mut task_reporter)
+                    .map(|report| (data, report))
+            });
+            (result, task_reporter)
+        });
+
+        while let Some(progress) = rx.recv().await {
+            self.report(progress).await;
+        }
+
+        let (result, task_reporter) = task.await.map_err(|e| {
+            WowPatcherError::wrap(ErrorCategory::PatchingError, "Patching task failed", e)
+        })?;
+        *reporter = task_reporter;
+        result
+    }
+
+    /// Read the input, then find the patches a dry run reports on a blocking thread.
+    ///
+    /// Returns the dry run header and report from [`preview_patches`], with
+    /// `reporter` handed to the blocking thread like in [`AsyncPatcher::apply`].
+    async fn preview(
+        &self,
+        reporter: &mut ConsoleReporter,
+    ) -> Result<(String, PatchReport), WowPatcherError> {
+        let data = self.read_input().await?;
+        let patcher = self.patcher.clone();
+        let mut task_reporter = reporter.clone();
+
+        let task = tokio::task::spawn_blocking(move || {
+            let result = patcher.patch_options().and_then(|opts| {
+                preview_patches(
+                    &patcher.input,
+                    &patcher.output_path(),
+                    &data,
+                    &opts,
+                    &mut task_reporter,
+                )
+            });
+            (result, task_reporter)
+        });
+
+        let (result, task_reporter) = task.await.map_err(|e| {
+            WowPatcherError::wrap(ErrorCategory::PatchingError, "Patching task failed", e)
+        })?;
+        *reporter = task_reporter;
+        result
+    }
+
+    /// Write the patched bytes to the output on a blocking thread.
+    ///
+    /// Goes through the same temporary file, permission and code signing
+    /// steps as [`Patcher::patch`], with `reporter` handed over like in
+    /// [`AsyncPatcher::apply`].
+    async fn write_output(
+        &self,
+        data: Vec<u8>,
+        reporter: &mut ConsoleReporter,
+    ) -> Result<(), WowPatcherError> {
+        let output_path = self.patcher.output_path();
+        let codesign = self
+            .patcher
+            .strip_codesign
+            .then(|| self.patcher.codesign_options.clone());
+        let output_mode = self.patcher.output_mode;
+        let mut task_reporter = reporter.clone();
+
+        let task = tokio::task::spawn_blocking(move || {
+            let result = write_patched_output(
+                &output_path,
+                &data,
+                codesign,
+                output_mode,
+                &mut task_reporter,
+            );
+            (result, task_reporter)
+        });
+
+        let (result, task_reporter) = task.await.map_err(|e| {
+            WowPatcherError::wrap(ErrorCategory::FileOperationError, "Write task failed", e)
+        })?;
+        *reporter = task_reporter;
+        result
+    }
+
+    /// Read the input bytes or the input file.
+    async fn read_input(&self) -> Result<Vec<u8>, WowPatcherError> {
+        if self.patcher.input_bytes.is_some() {
+            return self.patcher.read_input();
+        }
+        tokio::fs::read(&self.patcher.input).await.map_err(|e| {
+            WowPatcherError::wrap(
+                ErrorCategory::FileOperationError,
+                "Failed to read WoW executable file",
+                e,
+            )
+        })
+    }
+
+    async fn report(&self, progress: PatchProgress) {
+        if let Some(sender) = &self.patcher.progress_sender {
+            sender.send(progress.clone());
+        }
+        if let Some(callback) = &self.on_progress {
+            callback(progress).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::test_helpers::create_test_executable;
+    use crate::report::ReportMode;
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_patch_in_memory_reports_progress() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("game.bin");
+        std::fs::write(&input, create_test_executable()).unwrap();
+
+        let steps = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&steps);
+
+        let patched = AsyncPatcher::new(Patcher::new(&input))
+            .on_progress(move |progress| {
+                let recorded = Arc::clone(&recorded);
+                Box::pin(async move {
+                    recorded.lock().unwrap().push(progress.step_index);
+                })
+            })
+            .patch_in_memory()
+            .await
+            .unwrap();
+
+        assert!(patched[0x300..0x312].iter().all(|&b| b == 0));
+        assert_eq!(*steps.lock().unwrap(), vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_writes_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("game.bin");
+        let output = temp_dir.path().join("game-patched.bin");
+        std::fs::write(&input, create_test_executable()).unwrap();
+
+        let steps = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&steps);
+        AsyncPatcher::new(
+            Patcher::new(&input)
+                .output(&output)
+                .dry_run(true)
+                .report_mode(ReportMode::Silent),
+        )
+        .on_progress(move |progress| {
+            let recorded = Arc::clone(&recorded);
+            Box::pin(async move {
+                recorded.lock().unwrap().push(progress.step_index);
+            })
+        })
+        .patch()
+        .await
+        .unwrap();
+
+        assert!(!output.exists());
+        // Only the completion message is sent, as no step is applied
+        assert_eq!(*steps.lock().unwrap(), vec![5]);
+    }
+
+    #[tokio::test]
+    async fn test_patch_replaces_output() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("game.bin");
+        let output = temp_dir.path().join("game-patched.bin");
+        std::fs::write(&input, create_test_executable()).unwrap();
+        std::fs::write(&output, b"old").unwrap();
+
+        AsyncPatcher::new(
+            Patcher::new(&input)
+                .output(&output)
+                .with_output_permissions(0o700)
+                .report_mode(ReportMode::Silent),
+        )
+        .patch()
+        .await
+        .unwrap();
+
+        assert!(
+            std::fs::read(&output).unwrap()[0x300..0x312]
+                .iter()
+                .all(|&b| b == 0)
+        );
+        // The temporary file was renamed over the output
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&output).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_patch_missing_input() {
+        let result = AsyncPatcher::new(Patcher::new("/nonexistent/Wow.exe"))
+            .patch()
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,209 @@
+//! Universal (fat) Mach-O binaries holding one slice per CPU architecture.
+//!
+//! Every slice is a complete thin Mach-O with its own header, so sections are
+//! looked up in the slice that contains an offset, not in the fat container.
+
+use crate::binary::{ParsedBinary, Pattern, SectionInfo, find_pattern_region, patch_region};
+use crate::errors::{ErrorCategory, WowPatcherError, new_pattern_missing_error};
+use goblin::mach::MultiArch;
+use goblin::mach::cputype::{CPU_TYPE_ARM64, CPU_TYPE_X86_64, get_arch_name_from_types};
+use std::fmt;
+use std::ops::Range;
+
+/// Architecture slices of a universal binary to patch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Arch {
+    /// Apple Silicon slice
+    Arm64,
+    /// Intel slice
+    X86_64,
+    /// Every slice
+    #[default]
+    All,
+}
+
+impl Arch {
+    /// Whether a slice with the Mach-O CPU type `cputype` is selected.
+    pub fn matches(self, cputype: u32) -> bool {
+        match self {
+            Arch::Arm64 => cputype == CPU_TYPE_ARM64,
+            Arch::X86_64 => cputype == CPU_TYPE_X86_64,
+            Arch::All => true,
+        }
+    }
+}
+
+impl fmt::Display for Arch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Arch::Arm64 => write!(f, "arm64"),
+            Arch::X86_64 => write!(f, "x86_64"),
+            Arch::All => write!(f, "all"),
+        }
+    }
+}
+
+/// One architecture slice of a universal binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FatSlice {
+    /// Architecture name such as `arm64` or `x86_64`
+    pub name: &'static str,
+    /// Mach-O CPU type of the slice
+    pub cputype: u32,
+    /// File offsets covered by the slice
+    pub range: Range<usize>,
+}
+
+impl FatSlice {
+    /// Find the section containing the file offset `offset`, using this slice's own header.
+    ///
+    /// The returned section has its file offset translated back into the fat
+    /// binary.
+    pub fn section_at(&self, data: &[u8], offset: usize) -> Option<SectionInfo> {
+        let relative = offset.checked_sub(self.range.start)?;
+        let slice = data.get(self.range.clone())?;
+        let mut section = ParsedBinary::new(slice).section_at(relative)?;
+        section.file_offset += self.range.start as u64;
+        Some(section)
+    }
+}
+
+/// List the architecture slices of a universal binary.
+///
+/// # Errors
+///
+/// Returns a validation error if `data` is not a universal binary or a
+/// slice extends past the end of the file.
+pub fn fat_slices(data: &[u8]) -> Result<Vec<FatSlice>, WowPatcherError> {
+    let arches = MultiArch::new(data)
+        .and_then(|multi| multi.arches())
+        .map_err(|e| {
+            WowPatcherError::wrap(
+                ErrorCategory::ValidationError,
+                "Failed to parse universal Mach-O header",
+                e,
+            )
+        })?;
+
+    arches
+        .iter()
+        .map(|arch| {
+            let start = arch.offset as usize;
+            let range = start..start.saturating_add(arch.size as usize);
+            if range.end > data.len() {
+                return Err(WowPatcherError::new(
+                    ErrorCategory::ValidationError,
+                    "Universal binary slice extends past the end of the file",
+                )
+                .with_offset(start));
+            }
+            Ok(FatSlice {
+                name: get_arch_name_from_types(arch.cputype(), arch.cpusubtype())
+                    .unwrap_or("unknown"),
+                cputype: arch.cputype(),
+                range,
+            })
+        })
+        .collect()
+}
+
+/// The slices of a universal binary selected by `arch`.
+///
+/// # Errors
+///
+/// Returns a validation error if `data` is not a valid universal binary or
+/// has no slice for `arch`.
+pub fn select_slices(data: &[u8], arch: Arch) -> Result<Vec<FatSlice>, WowPatcherError> {
+    let slices = fat_slices(data)?;
+    let available: Vec<&str> = slices.iter().map(|slice| slice.name).collect();
+    let selected: Vec<FatSlice> = slices
+        .iter()
+        .filter(|slice| arch.matches(slice.cputype))
+        .cloned()
+        .collect();
+    if selected.is_empty() {
+        return Err(WowPatcherError::new(
+            ErrorCategory::ValidationError,
+            format!("Universal binary has no {} slice", arch),
+        )
+        .with_context("architectures", available.join(", ")));
+    }
+    Ok(selected)
+}
+
+/// Replace the first occurrence of `find` in each slice of a universal binary selected by `arch`.
+///
+/// Each match must lie in a patchable section of its own slice. Every
+/// selected slice is checked before anything is written, so on error `data`
+/// is left unchanged. Returns the file offsets of the replacements.
+///
+/// # Errors
+///
+/// Returns a validation error if `data` is not a universal binary or has no
+/// slice for `arch`, and a patching error if a selected slice does not
+/// contain the pattern or contains it outside a patchable section.
+pub fn patch_macho_fat(
+    data: &mut [u8],
+    arch: Arch,
+    find: &Pattern,
+    replace: &[u8],
+) -> Result<Vec<usize>, WowPatcherError> {
+    let mut offsets = Vec::new();
+    for slice in select_slices(data, arch)? {
+        let offset = find_pattern_region(data, slice.range.start, slice.range.end, find)
+            .ok_or_else(|| {
+                new_pattern_missing_error(format!("Pattern not found in the {} slice", slice.name))
+            })?;
+        match slice.section_at(data, offset) {
+            Some(section) if section.is_patchable => offsets.push(offset),
+            section => {
+                let location = section.map_or_else(
+                    || "outside any section".to_string(),
+                    |section| format!("in non-patchable section '{}'", section.name),
+                );
+                return Err(WowPatcherError::new(
+                    ErrorCategory::PatchingError,
+                    format!("Pattern found {} of the {} slice", location, slice.name),
+                )
+                .with_offset(offset));
+            }
+        }
+    }
+
+    for &offset in &offsets {
+        patch_region(data, offset, offset + find.len(), find, replace)?;
+    }
+    Ok(offsets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arch_matches() {
+        assert!(Arch::Arm64.matches(CPU_TYPE_ARM64));
+        assert!(!Arch::Arm64.matches(CPU_TYPE_X86_64));
+        assert!(Arch::X86_64.matches(CPU_TYPE_X86_64));
+        assert!(Arch::All.matches(CPU_TYPE_ARM64));
+        assert!(Arch::All.matches(CPU_TYPE_X86_64));
+        assert_eq!(Arch::default(), Arch::All);
+        assert_eq!(Arch::X86_64.to_string(), "x86_64");
+    }
+
+    #[test]
+    fn test_fat_slices_rejects_thin_binaries() {
+        let err = fat_slices(b"MZ\x90\x00\x03\x00\x00\x00").unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+
+        // A slice pointing past the end of the file
+        let mut data = vec![0u8; 64];
+        data[0..4].copy_from_slice(&0xCAFE_BABEu32.to_be_bytes());
+        data[4..8].copy_from_slice(&1u32.to_be_bytes());
+        data[8..12].copy_from_slice(&CPU_TYPE_ARM64.to_be_bytes());
+        data[16..20].copy_from_slice(&0x1000u32.to_be_bytes());
+        data[20..24].copy_from_slice(&0x1000u32.to_be_bytes());
+        let err = fat_slices(&data).unwrap_err();
+        assert_eq!(err.offset(), Some(0x1000));
+    }
+}
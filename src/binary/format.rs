@@ -0,0 +1,165 @@
+use crate::binary::SectionInfo;
+use crate::binary::section::{section_in_object, section_named_in_object};
+use crate::errors::{ErrorCategory, WowPatcherError};
+use goblin::Object;
+
+/// Executable container format, determined from the leading magic bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryFormat {
+    /// Windows PE (`MZ`)
+    Pe,
+    /// Thin 32 or 64-bit Mach-O, in either byte order
+    MachO,
+    /// Universal (fat) Mach-O containing several architectures
+    MachOFat,
+    /// ELF (`\x7FELF`)
+    Elf,
+    Unknown,
+}
+
+impl std::fmt::Display for BinaryFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BinaryFormat::Pe => write!(f, "PE"),
+            BinaryFormat::MachO => write!(f, "Mach-O"),
+            BinaryFormat::MachOFat => write!(f, "Mach-O (universal)"),
+            BinaryFormat::Elf => write!(f, "ELF"),
+            BinaryFormat::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+/// Determine the executable format from the magic bytes at the start of `data`.
+pub fn detect_format(data: &[u8]) -> BinaryFormat {
+    if data.starts_with(b"MZ") {
+        return BinaryFormat::Pe;
+    }
+    if data.starts_with(b"\x7FELF") {
+        return BinaryFormat::Elf;
+    }
+
+    let Some(magic) = data.get(..4) else {
+        return BinaryFormat::Unknown;
+    };
+    match u32::from_be_bytes([magic[0], magic[1], magic[2], magic[3]]) {
+        // MH_MAGIC, MH_MAGIC_64 and their byte-swapped forms
+        0xFEED_FACE | 0xFEED_FACF | 0xCEFA_EDFE | 0xCFFA_EDFE => BinaryFormat::MachO,
+        // FAT_MAGIC, FAT_MAGIC_64 and their byte-swapped forms
+        0xCAFE_BABE | 0xCAFE_BABF | 0xBEBA_FECA | 0xBFBA_FECA => BinaryFormat::MachOFat,
+        _ => BinaryFormat::Unknown,
+    }
+}
+
+/// An executable parsed once, for repeated format and section lookups.
+pub struct ParsedBinary<'a> {
+    pub format: BinaryFormat,
+    /// Parsed object, `Object::Unknown` when the format is unknown or parsing failed
+    pub obj: Object<'a>,
+    pub data: &'a [u8],
+}
+
+impl<'a> ParsedBinary<'a> {
+    /// Detect the format of `data` and parse it if it is a known executable format.
+    pub fn new(data: &'a [u8]) -> Self {
+        let format = detect_format(data);
+        let obj = match format {
+            BinaryFormat::Unknown => None,
+            _ => Object::parse(data).ok(),
+        };
+        Self {
+            format,
+            obj: obj.unwrap_or(Object::Unknown(0)),
+            data,
+        }
+    }
+
+    /// Parse `data`, failing if it is not a PE, Mach-O or ELF executable.
+    ///
+    /// Unlike [`ParsedBinary::new`], parse errors are reported instead of
+    /// falling back to an object without sections.
+    pub fn parse(data: &'a [u8]) -> Result<Self, WowPatcherError> {
+        let format = detect_format(data);
+        if format == BinaryFormat::Unknown {
+            return Err(WowPatcherError::new(
+                ErrorCategory::ValidationError,
+                "Unrecognized executable format",
+            ));
+        }
+        let obj = Object::parse(data).map_err(|e| {
+            WowPatcherError::wrap(
+                ErrorCategory::ValidationError,
+                format!("Failed to parse {} executable", format),
+                e,
+            )
+        })?;
+        Ok(Self { format, obj, data })
+    }
+
+    /// Find the section containing a file offset.
+    pub fn section_at(&self, offset: usize) -> Option<SectionInfo> {
+        section_in_object(&self.obj, offset)
+    }
+
+    /// Find a section by name, such as `.rdata` or `__TEXT.__const` for Mach-O.
+    pub fn section_named(&self, name: &str) -> Option<SectionInfo> {
+        section_named_in_object(&self.obj, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::{check_offset_section, check_offset_section_parsed};
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(detect_format(b"MZ\x90\x00"), BinaryFormat::Pe);
+        assert_eq!(detect_format(b"\x7FELF\x02\x01"), BinaryFormat::Elf);
+        assert_eq!(
+            detect_format(&[0xCF, 0xFA, 0xED, 0xFE, 0x07]),
+            BinaryFormat::MachO
+        );
+        assert_eq!(
+            detect_format(&[0xFE, 0xED, 0xFA, 0xCE]),
+            BinaryFormat::MachO
+        );
+        assert_eq!(
+            detect_format(&[0xCA, 0xFE, 0xBA, 0xBE, 0x00]),
+            BinaryFormat::MachOFat
+        );
+        assert_eq!(detect_format(b"\xCA\xFE"), BinaryFormat::Unknown);
+        assert_eq!(detect_format(b"plain text"), BinaryFormat::Unknown);
+    }
+
+    #[test]
+    fn test_parsed_binary_unknown() {
+        let data = [0u8; 64];
+        let binary = ParsedBinary::new(&data);
+        assert_eq!(binary.format, BinaryFormat::Unknown);
+        assert!(matches!(binary.obj, Object::Unknown(_)));
+        assert!(binary.section_at(0).is_none());
+
+        // A truncated PE keeps its format but has no sections
+        let binary = ParsedBinary::new(b"MZ");
+        assert_eq!(binary.format, BinaryFormat::Pe);
+        assert!(binary.section_at(0).is_none());
+    }
+
+    #[test]
+    fn test_parsed_binary_parse() {
+        let err = ParsedBinary::parse(&[0u8; 64]).err().unwrap();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+        assert_eq!(err.message, "Unrecognized executable format");
+
+        let err = ParsedBinary::parse(b"MZ").err().unwrap();
+        assert_eq!(err.message, "Failed to parse PE executable");
+
+        let data = crate::patterns::test_helpers::create_test_executable();
+        let binary = ParsedBinary::parse(&data).unwrap();
+        assert_eq!(binary.format, BinaryFormat::Pe);
+        assert_eq!(
+            check_offset_section_parsed(&binary, 0x200).map(|section| section.name),
+            check_offset_section(&data, 0x200).map(|section| section.name)
+        );
+    }
+}
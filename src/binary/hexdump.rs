@@ -0,0 +1,119 @@
+use std::fmt::Write;
+
+/// Number of bytes rendered per hex dump line.
+const BYTES_PER_LINE: usize = 16;
+
+/// Render `len` bytes of `data` starting at `offset` as an `xxd`-style hex dump.
+///
+/// Each line shows the file offset, 16 bytes in groups of two and the ASCII
+/// representation. The range is clamped to the end of `data`.
+pub fn hex_dump(data: &[u8], offset: usize, len: usize, label: &str) -> String {
+    let start = offset.min(data.len());
+    let end = offset.saturating_add(len).min(data.len());
+
+    let mut output = format!("{}:\n", label);
+    for (index, chunk) in data[start..end].chunks(BYTES_PER_LINE).enumerate() {
+        output.push_str(&format_line(start + index * BYTES_PER_LINE, chunk));
+        output.push('\n');
+    }
+    output
+}
+
+/// Render the differences between two copies of the same region.
+///
+/// `before` and `after` hold the bytes starting at file offset `offset`.
+/// Unchanged lines are shown once; lines containing changed bytes show the
+/// original bytes followed by the new bytes marked with `>>`.
+pub fn hex_diff(before: &[u8], after: &[u8], offset: usize, len: usize) -> String {
+    let len = len.min(before.len()).min(after.len());
+
+    let mut output = String::new();
+    for (index, (old, new)) in before[..len]
+        .chunks(BYTES_PER_LINE)
+        .zip(after[..len].chunks(BYTES_PER_LINE))
+        .enumerate()
+    {
+        let line_offset = offset + index * BYTES_PER_LINE;
+        if old == new {
+            let _ = writeln!(output, "   {}", format_line(line_offset, old));
+        } else {
+            let _ = writeln!(output, "   {}", format_line(line_offset, old));
+            let _ = writeln!(output, ">> {}", format_line(line_offset, new));
+        }
+    }
+    output
+}
+
+/// Format a single line of up to 16 bytes in `xxd` layout.
+fn format_line(offset: usize, bytes: &[u8]) -> String {
+    let mut hex = String::new();
+    for (i, byte) in bytes.iter().enumerate() {
+        if i > 0 && i % 2 == 0 {
+            hex.push(' ');
+        }
+        let _ = write!(hex, "{:02x}", byte);
+    }
+
+    let ascii: String = bytes
+        .iter()
+        .map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        })
+        .collect();
+
+    // Full line: 32 hex digits plus 7 group separators
+    format!("{:08x}: {:<39}  {}", offset, hex, ascii)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_dump_format() {
+        let data = b"Hello, world!\x00\x01\x02ABC";
+        let dump = hex_dump(data, 0, data.len(), "Portal");
+
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "Portal:");
+        assert_eq!(
+            lines[1],
+            "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 2100 0102  Hello, world!..."
+        );
+        assert_eq!(
+            lines[2],
+            "00000010: 4142 43                                  ABC"
+        );
+    }
+
+    #[test]
+    fn test_hex_dump_clamps_range() {
+        let data = [0u8; 8];
+        let dump = hex_dump(&data, 4, 100, "tail");
+        assert_eq!(dump.lines().count(), 2);
+        assert!(dump.contains("00000004: 0000 0000"));
+
+        let dump = hex_dump(&data, 100, 4, "empty");
+        assert_eq!(dump, "empty:\n");
+    }
+
+    #[test]
+    fn test_hex_diff_marks_changed_lines() {
+        let before = [0u8; 32];
+        let mut after = before;
+        after[20] = 0xff;
+
+        let diff = hex_diff(&before, &after, 0x300, 32);
+        let lines: Vec<&str> = diff.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("   00000300:"));
+        assert!(lines[1].starts_with("   00000310:"));
+        assert!(lines[2].starts_with(">> 00000310:"));
+        assert!(lines[2].contains("ff00"));
+    }
+}
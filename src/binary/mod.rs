@@ -1,25 +1,141 @@
-use crate::errors::{ErrorCategory, WowPatcherError};
-
+use crate::errors::{ErrorCategory, WowPatcherError, new_pattern_missing_error};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+pub mod fat;
+pub mod format;
+pub mod hexdump;
+pub mod pe;
+pub mod search;
 pub mod section;
-pub use section::{SectionInfo, check_offset_section, validate_patch_offsets};
+pub use fat::{Arch, FatSlice, fat_slices, patch_macho_fat, select_slices};
+pub use format::{BinaryFormat, ParsedBinary, detect_format};
+pub use hexdump::{hex_diff, hex_dump};
+pub use search::{MultiSearchResult, PatternSearch, find_string_references, multi_pattern_search};
+pub use section::{
+    SectionInfo, check_offset_section, check_offset_section_parsed, find_pattern_in_section,
+    validate_patch_offsets,
+};
 
 pub type Pattern = Vec<i16>;
 
+/// Convert an ASCII string to a pattern matching its bytes.
+///
+/// Non-ASCII input would match its UTF-8 encoding byte by byte, which is
+/// almost never intended; use [`string_to_pattern_checked`] for untrusted input.
 pub fn string_to_pattern(s: &str) -> Pattern {
+    debug_assert!(s.is_ascii(), "pattern string must be ASCII: {:?}", s);
     s.bytes().map(|b| b as i16).collect()
 }
 
+/// Convert a string to a pattern, rejecting non-ASCII input.
+pub fn string_to_pattern_checked(s: &str) -> Result<Pattern, WowPatcherError> {
+    if !s.is_ascii() {
+        return Err(WowPatcherError::new(
+            ErrorCategory::ValidationError,
+            "Pattern string contains non-ASCII characters",
+        )
+        .with_context("pattern", s.to_string()));
+    }
+    Ok(string_to_pattern(s))
+}
+
+/// Convert a pattern without wildcards back to the bytes it matches.
+///
+/// Fails with a validation error if the pattern contains a wildcard, since
+/// a wildcard has no single byte value.
+pub fn pattern_to_bytes(pattern: &Pattern) -> Result<Vec<u8>, WowPatcherError> {
+    if let Some(index) = pattern.iter().position(|&b| b == -1) {
+        return Err(WowPatcherError::new(
+            ErrorCategory::ValidationError,
+            format!("Pattern has a wildcard at index {}", index),
+        )
+        .with_suggestion("Use pattern_to_bytes_or_default to substitute a byte for wildcards"));
+    }
+    Ok(pattern.iter().map(|&b| b as u8).collect())
+}
+
+/// Convert a pattern to bytes, writing `wildcard_byte` for each wildcard.
+pub fn pattern_to_bytes_or_default(pattern: &Pattern, wildcard_byte: u8) -> Vec<u8> {
+    pattern
+        .iter()
+        .map(|&b| if b == -1 { wildcard_byte } else { b as u8 })
+        .collect()
+}
+
 pub trait PatternExt {
     fn empty(&self) -> Vec<u8>;
+
+    /// Start a search for this pattern in `data`, see [`PatternSearch`].
+    fn search_in<'a>(&'a self, data: &'a [u8]) -> PatternSearch<'a>;
 }
 
 impl PatternExt for Pattern {
     fn empty(&self) -> Vec<u8> {
         vec![0; self.len()]
     }
+
+    fn search_in<'a>(&'a self, data: &'a [u8]) -> PatternSearch<'a> {
+        PatternSearch {
+            pattern: self,
+            data,
+        }
+    }
+}
+
+/// Constructors for [`Pattern`] values.
+pub struct PatternBuilder;
+
+impl PatternBuilder {
+    /// Parse hex bytes such as `"91 D5 ?? B7"` or `"91d5??b7"` into a pattern.
+    ///
+    /// Whitespace is ignored and `??` marks a wildcard byte.
+    pub fn from_hex(hex: &str) -> Result<Pattern, WowPatcherError> {
+        let digits: Vec<u8> = hex.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+        if !digits.len().is_multiple_of(2) {
+            return Err(WowPatcherError::new(
+                ErrorCategory::ValidationError,
+                "hex pattern has an odd number of digits",
+            )
+            .with_context("pattern", hex.to_string()));
+        }
+
+        digits
+            .chunks(2)
+            .map(|pair| match pair {
+                b"??" => Ok(-1),
+                _ => std::str::from_utf8(pair)
+                    .ok()
+                    .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                    .map(i16::from)
+                    .ok_or_else(|| {
+                        WowPatcherError::new(
+                            ErrorCategory::ValidationError,
+                            format!("invalid hex byte '{}'", String::from_utf8_lossy(pair)),
+                        )
+                        .with_context("pattern", hex.to_string())
+                    }),
+            })
+            .collect()
+    }
+
+    /// Build a pattern matching the bytes of `s` exactly, see [`string_to_pattern`].
+    // Returns a `Pattern` rather than `Self`, so `FromStr` does not apply
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Pattern {
+        string_to_pattern(s)
+    }
+
+    /// Build a pattern of `count` wildcards, for masking bytes that vary between builds.
+    pub fn wildcard_bytes(count: usize) -> Pattern {
+        vec![-1; count]
+    }
 }
 
 pub trait DataExt {
+    /// Offset of the first occurrence of `pattern`, where `-1` matches any byte.
+    ///
+    /// An empty pattern never matches and returns `None`.
     fn find_pattern(&self, pattern: &Pattern) -> Option<usize>;
 }
 
@@ -35,7 +151,12 @@ impl DataExt for [u8] {
     }
 }
 
-pub fn patch(data: &mut [u8], find: &Pattern, replace: &[u8]) -> Result<(), WowPatcherError> {
+/// Replace the first occurrence of `find` with `replace`.
+///
+/// Returns the offset of the first replaced byte. Fails if `data` or `find`
+/// is empty. With the `tracing` feature a debug event records the offset and
+/// the first 8 bytes before and after the replacement.
+pub fn patch(data: &mut [u8], find: &Pattern, replace: &[u8]) -> Result<usize, WowPatcherError> {
     if data.is_empty() {
         return Err(WowPatcherError::new(
             ErrorCategory::PatchingError,
@@ -43,6 +164,13 @@ pub fn patch(data: &mut [u8], find: &Pattern, replace: &[u8]) -> Result<(), WowP
         ));
     }
 
+    if find.is_empty() {
+        return Err(WowPatcherError::new(
+            ErrorCategory::PatchingError,
+            "cannot patch with an empty pattern",
+        ));
+    }
+
     if find.len() > data.len() {
         return Err(WowPatcherError::new(
             ErrorCategory::PatchingError,
@@ -53,18 +181,204 @@ pub fn patch(data: &mut [u8], find: &Pattern, replace: &[u8]) -> Result<(), WowP
     let position = find_pattern(data, find);
 
     match position {
+        Some(pos) => {
+            let replace_len = replace.len().min(find.len());
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                pattern_offset = pos,
+                before = ?&data[pos..pos + replace_len.min(8)],
+                after = ?&replace[..replace_len.min(8)],
+                "applied patch"
+            );
+            data[pos..(replace_len + pos)].copy_from_slice(&replace[..replace_len]);
+            Ok(pos)
+        }
+        None => Err(new_pattern_missing_error("pattern not found in data")),
+    }
+}
+
+/// Replace the first occurrence of `find` within `data[start..end]`.
+///
+/// Returns the absolute offset of the first replaced byte.
+pub fn patch_region(
+    data: &mut [u8],
+    start: usize,
+    end: usize,
+    find: &Pattern,
+    replace: &[u8],
+) -> Result<usize, WowPatcherError> {
+    if start > end || end > data.len() {
+        return Err(WowPatcherError::new(
+            ErrorCategory::PatchingError,
+            format!(
+                "invalid region 0x{:x}..0x{:x} for {} bytes of data",
+                start,
+                end,
+                data.len()
+            ),
+        )
+        .with_offset(start));
+    }
+
+    if find.len() > end - start {
+        return Err(WowPatcherError::new(
+            ErrorCategory::PatchingError,
+            "pattern longer than region",
+        ));
+    }
+
+    match find_pattern_region(data, start, end, find) {
         Some(pos) => {
             let replace_len = replace.len().min(find.len());
             data[pos..(replace_len + pos)].copy_from_slice(&replace[..replace_len]);
-            Ok(())
+            Ok(pos)
         }
-        None => Err(WowPatcherError::new(
+        None => Err(new_pattern_missing_error("pattern not found in region")),
+    }
+}
+
+/// Find the first occurrence of `pattern` within `data[start..end]`.
+///
+/// Returns the absolute offset, or `None` if the range is invalid or the
+/// pattern does not occur in it.
+pub fn find_pattern_region(
+    data: &[u8],
+    start: usize,
+    end: usize,
+    pattern: &Pattern,
+) -> Option<usize> {
+    let region = data.get(start..end)?;
+    find_pattern(region, pattern).map(|pos| pos + start)
+}
+
+/// Offsets of every non-overlapping occurrence of `pattern`, in ascending order.
+///
+/// [`DataExt::find_pattern`] only reports the first match; some builds embed
+/// the same key more than once. An empty pattern never matches.
+pub fn find_pattern_all(data: &[u8], pattern: &Pattern) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    let mut start = 0;
+    while let Some(pos) = find_pattern_region(data, start, data.len(), pattern) {
+        offsets.push(pos);
+        start = pos + pattern.len();
+    }
+    offsets
+}
+
+/// Replace every non-overlapping occurrence of `find` with `replace`.
+///
+/// Returns the offsets of all replacements, or an error if there were none.
+/// The number of replacements is the length of the result.
+pub fn patch_all(
+    data: &mut [u8],
+    find: &Pattern,
+    replace: &[u8],
+) -> Result<Vec<usize>, WowPatcherError> {
+    let first = patch(data, find, replace)?;
+    let mut offsets = vec![first];
+    let replace_len = replace.len().min(find.len());
+    let mut start = first + find.len();
+
+    while let Some(pos) = data
+        .get(start..)
+        .and_then(|rest| find_pattern(rest, find))
+        .map(|pos| pos + start)
+    {
+        data[pos..(replace_len + pos)].copy_from_slice(&replace[..replace_len]);
+        offsets.push(pos);
+        start = pos + find.len();
+    }
+
+    Ok(offsets)
+}
+
+/// Replace occurrences of `find` with `replace` until none remain.
+///
+/// Unlike [`patch_all`], the search resumes around each replacement, so
+/// matches created by a previous replacement are patched as well. Returns
+/// the number of replacements, which is 0 when `find` does not occur.
+///
+/// Fails if `find` is empty or the replacement would itself match `find`,
+/// since either would never terminate.
+pub fn patch_until_exhausted(
+    data: &mut [u8],
+    find: &Pattern,
+    replace: &[u8],
+) -> Result<usize, WowPatcherError> {
+    if find.is_empty() {
+        return Err(WowPatcherError::new(
+            ErrorCategory::PatchingError,
+            "cannot patch with an empty pattern",
+        ));
+    }
+
+    // Bytes past the replacement are left as they were, so the window still
+    // matches whenever the written prefix matches the pattern prefix
+    let replace_len = replace.len().min(find.len());
+    let rematches = find
+        .iter()
+        .zip(&replace[..replace_len])
+        .all(|(&p, &b)| p == -1 || p == b as i16);
+    if rematches {
+        return Err(WowPatcherError::new(
             ErrorCategory::PatchingError,
-            "pattern not found in data",
-        )),
+            "replacement matches the pattern it replaces",
+        ));
+    }
+
+    let mut count = 0;
+    let mut start = 0;
+    while let Some(pos) = data
+        .get(start..)
+        .and_then(|rest| find_pattern(rest, find))
+        .map(|pos| pos + start)
+    {
+        data[pos..(replace_len + pos)].copy_from_slice(&replace[..replace_len]);
+        count += 1;
+        // Earlier windows that do not overlap the written bytes are unchanged
+        start = pos.saturating_sub(find.len() - 1);
     }
+
+    Ok(count)
+}
+
+/// Round `offset` up to the next multiple of `alignment`.
+///
+/// An `alignment` of 0 leaves the offset unchanged.
+pub fn align_to(offset: usize, alignment: usize) -> usize {
+    if alignment == 0 {
+        return offset;
+    }
+    offset.next_multiple_of(alignment)
+}
+
+/// Compute the SHA256 digest of `data`.
+pub fn compute_sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+/// Compute the SHA256 digest of `data` as a lowercase hex string.
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(compute_sha256(data))
+}
+
+/// Read a file and compute its SHA256 digest.
+pub fn sha256_of_file(path: &Path) -> Result<[u8; 32], WowPatcherError> {
+    let data = std::fs::read(path).map_err(|e| {
+        WowPatcherError::wrap(
+            ErrorCategory::FileOperationError,
+            "Failed to read file for checksum",
+            e,
+        )
+        .with_context("path", path.display().to_string())
+    })?;
+    Ok(compute_sha256(&data))
 }
 
+/// Naive scan for the first match of `pattern`.
+///
+/// An empty pattern would trivially match at offset 0, so it is treated as
+/// matching nowhere instead.
 fn find_pattern(data: &[u8], pattern: &Pattern) -> Option<usize> {
     if pattern.is_empty() || data.len() < pattern.len() {
         return None;
@@ -98,10 +412,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pattern_to_bytes() {
+        assert_eq!(pattern_to_bytes(&string_to_pattern("abc")).unwrap(), b"abc");
+        assert_eq!(
+            pattern_to_bytes(&vec![0x00, 0xFF, 0x7F]).unwrap(),
+            [0x00, 0xFF, 0x7F]
+        );
+
+        let pattern = vec![0x91, -1, 0x9B];
+        let err = pattern_to_bytes(&pattern).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+        assert_eq!(err.message, "Pattern has a wildcard at index 1");
+        assert_eq!(
+            pattern_to_bytes_or_default(&pattern, 0xCC),
+            [0x91, 0xCC, 0x9B]
+        );
+    }
+
+    #[test]
+    fn test_string_to_pattern_checked() {
+        assert_eq!(
+            string_to_pattern_checked("hello").unwrap(),
+            string_to_pattern("hello")
+        );
+
+        // "é" is two bytes in UTF-8 and must not become two pattern entries
+        let err = string_to_pattern_checked("caf\u{e9}.battle.net").unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+        assert!(string_to_pattern_checked("\u{1F600}").is_err());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "pattern string must be ASCII")]
+    fn test_string_to_pattern_non_ascii() {
+        string_to_pattern("caf\u{e9}");
+    }
+
+    #[test]
+    fn test_pattern_builder() {
+        assert_eq!(
+            PatternBuilder::from_hex("91 D5 ?? b7").unwrap(),
+            vec![0x91, 0xD5, -1, 0xB7]
+        );
+        assert_eq!(
+            PatternBuilder::from_hex("91d5??b7").unwrap(),
+            vec![0x91, 0xD5, -1, 0xB7]
+        );
+        assert_eq!(PatternBuilder::from_hex("").unwrap(), Pattern::new());
+
+        let err = PatternBuilder::from_hex("91 D").unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+        assert!(PatternBuilder::from_hex("91 ZZ").is_err());
+        assert!(PatternBuilder::from_hex("9?").is_err());
+
+        assert_eq!(PatternBuilder::from_str("ab"), vec![97, 98]);
+        assert_eq!(PatternBuilder::wildcard_bytes(3), vec![-1, -1, -1]);
+    }
+
     #[test]
     fn test_pattern_empty() {
         let pattern = Pattern::new();
-        assert_eq!(pattern.empty(), vec![]);
+        assert_eq!(pattern.empty(), Vec::<u8>::new());
 
         let pattern = vec![1, 2, 3, 4, 5];
         assert_eq!(pattern.empty(), vec![0, 0, 0, 0, 0]);
@@ -116,10 +489,121 @@ mod tests {
         let find = vec![104, 101, 108, 108, 111]; // "hello"
         let replace = b"HELLO";
 
-        assert!(patch(&mut data, &find, replace).is_ok());
+        assert_eq!(patch(&mut data, &find, replace).unwrap(), 0);
         assert_eq!(&data, b"HELLO world");
     }
 
+    #[test]
+    fn test_patch_returns_offset() {
+        let mut data = b"say hello world".to_vec();
+        let find = string_to_pattern("hello");
+
+        assert_eq!(patch(&mut data, &find, b"HELLO").unwrap(), 4);
+        assert_eq!(&data, b"say HELLO world");
+    }
+
+    #[test]
+    fn test_patch_all() {
+        let mut data = b"abc abc xabcx".to_vec();
+        let find = string_to_pattern("abc");
+
+        let offsets = patch_all(&mut data, &find, b"XYZ").unwrap();
+        assert_eq!(offsets, vec![0, 4, 9]);
+        assert_eq!(&data, b"XYZ XYZ xXYZx");
+
+        let result = patch_all(&mut data, &find, b"XYZ");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_find_pattern_all() {
+        // Three back-to-back copies of the RSA pattern, as in builds with a debug copy
+        let pattern = crate::patterns::connect_to_modulus_pattern();
+        let copy: Vec<u8> = pattern.iter().map(|&b| b as u8).collect();
+        let mut data = vec![0u8; 4];
+        for _ in 0..3 {
+            data.extend_from_slice(&copy);
+        }
+        assert_eq!(find_pattern_all(&data, pattern), vec![4, 12, 20]);
+
+        let offsets = patch_all(&mut data, pattern, &[0xAA; 8]).unwrap();
+        assert_eq!(offsets.len(), 3);
+        assert!(find_pattern_all(&data, pattern).is_empty());
+        assert_eq!(&data[4..], &[0xAA; 24][..]);
+
+        // Matches do not overlap
+        assert_eq!(
+            find_pattern_all(b"aaaa", &string_to_pattern("aa")),
+            vec![0, 2]
+        );
+        assert!(find_pattern_all(b"aaaa", &Pattern::new()).is_empty());
+    }
+
+    #[test]
+    fn test_patch_until_exhausted() {
+        // Collapsing "aab" to "ab" creates a new match on the left each time
+        let mut data = b"aaaab".to_vec();
+        let find = string_to_pattern("ab");
+        assert_eq!(patch_until_exhausted(&mut data, &find, b"bb").unwrap(), 4);
+        assert_eq!(&data, b"bbbbb");
+
+        // patch_all scans once and leaves the newly created match behind
+        let mut data = b"aaaab".to_vec();
+        assert_eq!(patch_all(&mut data, &find, b"bb").unwrap(), vec![3]);
+        assert_eq!(&data, b"aaabb");
+
+        let mut data = b"no match here".to_vec();
+        assert_eq!(patch_until_exhausted(&mut data, &find, b"xx").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_patch_until_exhausted_rejects_endless_loops() {
+        let mut data = b"abab".to_vec();
+        let err = patch_until_exhausted(&mut data, &Pattern::new(), b"x").unwrap_err();
+        assert_eq!(err.category, ErrorCategory::PatchingError);
+
+        let find = vec![b'a' as i16, -1];
+        assert!(patch_until_exhausted(&mut data, &find, b"az").is_err());
+        assert!(patch_until_exhausted(&mut data, &find, b"a").is_err());
+        assert_eq!(&data, b"abab");
+    }
+
+    #[test]
+    fn test_align_to() {
+        assert_eq!(align_to(0, 512), 0);
+        assert_eq!(align_to(1, 512), 512);
+        assert_eq!(align_to(511, 512), 512);
+        assert_eq!(align_to(512, 512), 512);
+        assert_eq!(align_to(513, 512), 1024);
+
+        assert_eq!(align_to(1, 4096), 4096);
+        assert_eq!(align_to(4095, 4096), 4096);
+        assert_eq!(align_to(4096, 4096), 4096);
+        assert_eq!(align_to(4097, 4096), 8192);
+
+        assert_eq!(align_to(123, 0), 123);
+    }
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Wow.exe");
+        std::fs::write(&path, b"abc").unwrap();
+        assert_eq!(sha256_of_file(&path).unwrap(), compute_sha256(b"abc"));
+
+        let err = sha256_of_file(&dir.path().join("missing.exe")).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::FileOperationError);
+    }
+
     #[test]
     fn test_patch_no_match() {
         let mut data = b"hello world".to_vec();
@@ -131,6 +615,18 @@ mod tests {
         assert_eq!(&data, b"hello world");
     }
 
+    #[test]
+    fn test_patch_empty_pattern() {
+        let mut data = b"hello world".to_vec();
+        assert_eq!(data.find_pattern(&Pattern::new()), None);
+
+        let err = patch(&mut data, &Pattern::new(), b"HELLO").unwrap_err();
+        assert_eq!(err.category, ErrorCategory::PatchingError);
+        assert_eq!(err.message, "cannot patch with an empty pattern");
+        assert!(patch_all(&mut data, &Pattern::new(), b"").is_err());
+        assert_eq!(&data, b"hello world");
+    }
+
     #[test]
     fn test_patch_wildcard() {
         let mut data = vec![0x01, 0x02, 0x03, 0x04, 0x05];
@@ -226,4 +722,30 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(data, vec![1, 2, 3]);
     }
+
+    #[test]
+    fn test_patch_region() {
+        let mut data = b"hello world hello".to_vec();
+        let find = string_to_pattern("hello");
+
+        assert_eq!(patch_region(&mut data, 6, 17, &find, b"HELLO").unwrap(), 12);
+        assert_eq!(&data, b"hello world HELLO");
+
+        assert!(patch_region(&mut data, 6, 17, &find, b"HELLO").is_err());
+        assert!(patch_region(&mut data, 10, 5, &find, b"HELLO").is_err());
+        let err = patch_region(&mut data, 0, 100, &find, b"HELLO").unwrap_err();
+        assert_eq!(err.offset(), Some(0));
+        assert!(patch_region(&mut data, 0, 3, &find, b"HELLO").is_err());
+    }
+
+    #[test]
+    fn test_find_pattern_region() {
+        let data = b"abc abc abc";
+        let find = string_to_pattern("abc");
+
+        assert_eq!(find_pattern_region(data, 0, data.len(), &find), Some(0));
+        assert_eq!(find_pattern_region(data, 1, data.len(), &find), Some(4));
+        assert_eq!(find_pattern_region(data, 1, 6, &find), None);
+        assert_eq!(find_pattern_region(data, 5, 50, &find), None);
+    }
 }
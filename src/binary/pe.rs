@@ -0,0 +1,188 @@
+use crate::binary::ParsedBinary;
+use crate::errors::{ErrorCategory, WowPatcherError};
+use goblin::Object;
+
+/// Offset of the `CheckSum` field within the optional header, the same for PE32 and PE32+
+const CHECKSUM_OPTIONAL_HEADER_OFFSET: usize = 64;
+
+/// Calculate the import hash (imphash) of a PE executable.
+///
+/// Every import is normalized to `<dll>.<function>` in lowercase, with the
+/// DLL extension removed and ordinal-only imports written as `ord<N>`. The
+/// pairs are sorted alphabetically, joined with commas and hashed with MD5.
+///
+/// Returns `None` for non-PE binaries and PEs without an import table.
+pub fn import_hash(data: &[u8]) -> Option<String> {
+    let binary = ParsedBinary::new(data);
+    let Object::PE(pe) = &binary.obj else {
+        return None;
+    };
+
+    let imports: Vec<(&str, &str)> = pe
+        .imports
+        .iter()
+        .map(|import| (import.dll, import.name.as_ref()))
+        .collect();
+
+    imphash_from_imports(&imports)
+}
+
+/// Hash a list of `(dll, function)` import pairs using the imphash rules.
+fn imphash_from_imports(imports: &[(&str, &str)]) -> Option<String> {
+    if imports.is_empty() {
+        return None;
+    }
+
+    let mut entries: Vec<String> = imports
+        .iter()
+        .map(|(dll, function)| {
+            let dll = dll.to_lowercase();
+            let dll = ["dll", "ocx", "sys"]
+                .iter()
+                .find_map(|ext| dll.strip_suffix(&format!(".{}", ext)))
+                .unwrap_or(&dll)
+                .to_string();
+
+            // goblin names ordinal-only imports "ORDINAL <n>"
+            let function = match function.strip_prefix("ORDINAL ") {
+                Some(ordinal) => format!("ord{}", ordinal),
+                None => function.to_lowercase(),
+            };
+
+            format!("{}.{}", dll, function)
+        })
+        .collect();
+    entries.sort();
+
+    Some(format!("{:x}", md5::compute(entries.join(","))))
+}
+
+/// Recompute the checksum stored in the PE optional header and write it back.
+///
+/// Uses the `CheckSumMappedFile` algorithm: the file is summed as 16-bit
+/// little-endian words with carries folded back in, and the file length is
+/// added. The checksum field counts as zero, so calling this again on its own
+/// output gives the same checksum and leaves the file unchanged.
+///
+/// Returns the new checksum.
+///
+/// # Errors
+///
+/// Returns a validation error if `data` is not a PE executable with an
+/// optional header.
+pub fn update_checksum(data: &mut [u8]) -> Result<u32, WowPatcherError> {
+    let field = checksum_offset(data)?;
+    data[field..field + 4].fill(0);
+    let checksum = pe_checksum(data);
+    data[field..field + 4].copy_from_slice(&checksum.to_le_bytes());
+    Ok(checksum)
+}
+
+/// File offset of the optional header's `CheckSum` field.
+fn checksum_offset(data: &[u8]) -> Result<usize, WowPatcherError> {
+    let not_pe = || {
+        WowPatcherError::new(
+            ErrorCategory::ValidationError,
+            "Cannot update checksum: not a PE executable",
+        )
+    };
+    if !data.starts_with(b"MZ") {
+        return Err(not_pe());
+    }
+    let pe_offset = data
+        .get(0x3C..0x40)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+        .ok_or_else(not_pe)?;
+    if data.get(pe_offset..pe_offset + 4) != Some(b"PE\0\0".as_slice()) {
+        return Err(not_pe());
+    }
+    // PE signature, then the 20 byte COFF header
+    let optional_header = pe_offset + 4 + 20;
+    let field = optional_header + CHECKSUM_OPTIONAL_HEADER_OFFSET;
+    if field + 4 > data.len() {
+        return Err(not_pe().with_offset(field));
+    }
+    Ok(field)
+}
+
+/// PE checksum of `data` as stored, including whatever its checksum field holds.
+fn pe_checksum(data: &[u8]) -> u32 {
+    let mut sum: u64 = 0;
+    for word in data.chunks(2) {
+        let word = u16::from_le_bytes([word[0], word.get(1).copied().unwrap_or(0)]);
+        sum += word as u64;
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    sum = (sum & 0xFFFF) + (sum >> 16);
+    (sum as u32).wrapping_add(data.len() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_imphash_from_imports() {
+        let imports = [
+            ("USER32.dll", "MessageBoxA"),
+            ("KERNEL32.dll", "CreateFileA"),
+            ("WS2_32.dll", "ORDINAL 23"),
+        ];
+        let expected = format!(
+            "{:x}",
+            md5::compute("kernel32.createfilea,user32.messageboxa,ws2_32.ord23")
+        );
+
+        let hash = imphash_from_imports(&imports).unwrap();
+        assert_eq!(hash, expected);
+        assert_eq!(hash.len(), 32);
+    }
+
+    #[test]
+    fn test_imphash_is_order_independent() {
+        let a = imphash_from_imports(&[("a.dll", "f"), ("b.dll", "g")]);
+        let b = imphash_from_imports(&[("b.dll", "g"), ("a.dll", "f")]);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_update_checksum_is_idempotent() {
+        let mut data = crate::patterns::test_helpers::create_test_executable();
+        let field = checksum_offset(&data).unwrap();
+        assert_eq!(field, 0x98 + CHECKSUM_OPTIONAL_HEADER_OFFSET);
+
+        data[field..field + 4].copy_from_slice(&0xDEAD_BEEFu32.to_le_bytes());
+        let first = update_checksum(&mut data).unwrap();
+        let once = data.clone();
+        let second = update_checksum(&mut data).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(data, once);
+        assert_eq!(&data[field..field + 4], &first.to_le_bytes());
+        assert_ne!(first, 0xDEAD_BEEF);
+
+        // Only the checksum field changes
+        let mut zeroed = data.clone();
+        zeroed[field..field + 4].fill(0);
+        assert_eq!(pe_checksum(&zeroed), first);
+    }
+
+    #[test]
+    fn test_pe_checksum() {
+        // Words 0x0201 and 0x0003 (odd trailing byte padded), plus the length
+        assert_eq!(pe_checksum(&[1, 2, 3]), 0x0201 + 0x0003 + 3);
+        // Carries out of the low 16 bits are folded back in
+        assert_eq!(pe_checksum(&[0xFF, 0xFF, 0x02, 0x00]), 0x0002 + 4);
+
+        let mut data = b"not an executable".to_vec();
+        let err = update_checksum(&mut data).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+        assert_eq!(data, b"not an executable");
+    }
+
+    #[test]
+    fn test_import_hash_without_imports() {
+        assert_eq!(imphash_from_imports(&[]), None);
+        assert_eq!(import_hash(b"not an executable"), None);
+    }
+}
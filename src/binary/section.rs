@@ -1,4 +1,6 @@
+use crate::binary::{DataExt, ParsedBinary, Pattern, align_to};
 use goblin::Object;
+use std::ops::Range;
 
 /// Information about a binary section
 #[derive(Debug, Clone)]
@@ -7,45 +9,155 @@ pub struct SectionInfo {
     pub virtual_address: u64,
     pub virtual_size: u64,
     pub file_offset: u64,
+    /// Number of bytes the section occupies in the file, which may differ from `virtual_size`
+    pub raw_size: u64,
     pub is_patchable: bool,
 }
 
+impl SectionInfo {
+    /// File offsets covered by the section.
+    pub fn raw_offset_range(&self) -> Range<usize> {
+        let start = self.file_offset as usize;
+        start..start.saturating_add(self.raw_size as usize)
+    }
+
+    /// Whether the file offset lies within the section's raw data.
+    pub fn contains_offset(&self, offset: usize) -> bool {
+        self.raw_offset_range().contains(&offset)
+    }
+
+    /// Offset of `abs_offset` from the start of the section, or `None` if it lies outside.
+    pub fn offset_relative(&self, abs_offset: usize) -> Option<usize> {
+        self.contains_offset(abs_offset)
+            .then(|| abs_offset - self.file_offset as usize)
+    }
+
+    /// The section's raw bytes within `data`, or `None` if they extend past its end.
+    pub fn as_slice<'a>(&self, data: &'a [u8]) -> Option<&'a [u8]> {
+        data.get(self.checked_raw_range()?)
+    }
+
+    /// Mutable access to the section's raw bytes, see [`SectionInfo::as_slice`].
+    pub fn as_slice_mut<'a>(&self, data: &'a mut [u8]) -> Option<&'a mut [u8]> {
+        data.get_mut(self.checked_raw_range()?)
+    }
+
+    /// Raw offset range, `None` if it does not fit in `usize`.
+    fn checked_raw_range(&self) -> Option<Range<usize>> {
+        let start = usize::try_from(self.file_offset).ok()?;
+        let end = start.checked_add(usize::try_from(self.raw_size).ok()?)?;
+        Some(start..end)
+    }
+}
+
+/// File offset of the first occurrence of `pattern` within `section`.
+///
+/// Returns `None` if the pattern is not in the section or the section
+/// extends past the end of `data`.
+pub fn find_pattern_in_section(
+    data: &[u8],
+    section: &SectionInfo,
+    pattern: &Pattern,
+) -> Option<usize> {
+    let offset = section.as_slice(data)?.find_pattern(pattern)?;
+    Some(section.file_offset as usize + offset)
+}
+
 /// Check if a given file offset falls within a patchable section
 /// Returns the section name if found and whether it's safe to patch
+///
+/// Parses `data` on every call; use [`check_offset_section_parsed`] for repeated lookups.
 pub fn check_offset_section(data: &[u8], offset: usize) -> Option<SectionInfo> {
-    let obj = Object::parse(data).ok()?;
+    check_offset_section_parsed(&ParsedBinary::new(data), offset)
+}
+
+/// Find the section containing a file offset in an executable parsed once up front.
+pub fn check_offset_section_parsed(parsed: &ParsedBinary, offset: usize) -> Option<SectionInfo> {
+    parsed.section_at(offset)
+}
 
+/// Find the section containing a file offset in an already parsed object.
+pub(crate) fn section_in_object(obj: &Object, offset: usize) -> Option<SectionInfo> {
     match obj {
-        Object::PE(pe) => check_pe_offset(&pe, offset),
-        Object::Mach(mach) => check_macho_offset(&mach, offset),
+        Object::PE(pe) => check_pe_offset(pe, offset),
+        Object::Mach(mach) => check_macho_offset(mach, offset),
         _ => None,
     }
 }
 
-/// Check PE section for a given offset
-fn check_pe_offset(pe: &goblin::pe::PE, offset: usize) -> Option<SectionInfo> {
-    for section in &pe.sections {
+/// Find the section named `name` in an already parsed object.
+///
+/// Mach-O sections are named `<segment>.<section>`, e.g. `__TEXT.__const`.
+pub(crate) fn section_named_in_object(obj: &Object, name: &str) -> Option<SectionInfo> {
+    match obj {
+        Object::PE(pe) => pe_sections(pe).find(|section| section.name == name),
+        Object::Mach(goblin::mach::Mach::Binary(macho)) => {
+            macho.segments.iter().find_map(|segment| {
+                let seg_name = segment.name().ok()?;
+                let sections = segment.sections().ok()?;
+                sections
+                    .iter()
+                    .filter_map(|(sect, _)| macho_section_info(seg_name, sect))
+                    .find(|section| section.name == name)
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Sections of a PE image with their file ranges.
+fn pe_sections<'a>(pe: &'a goblin::pe::PE) -> impl Iterator<Item = SectionInfo> + 'a {
+    let file_alignment = pe
+        .header
+        .optional_header
+        .map(|header| header.windows_fields.file_alignment as usize)
+        .unwrap_or(0);
+
+    pe.sections.iter().map(move |section| {
         let name = String::from_utf8_lossy(&section.name)
             .trim_end_matches('\0')
             .to_string();
-        let start = section.pointer_to_raw_data as usize;
-        let end = start + section.size_of_raw_data as usize;
-
-        if offset >= start && offset < end {
-            // Only .rdata and .data sections are safely patchable in binary files
-            // .text section modifications will be overwritten at runtime
-            let is_patchable = name == ".rdata" || name == ".data";
-
-            return Some(SectionInfo {
-                name: name.clone(),
-                virtual_address: section.virtual_address as u64,
-                virtual_size: section.virtual_size as u64,
-                file_offset: section.pointer_to_raw_data as u64,
-                is_patchable,
-            });
+        // Only .rdata and .data sections are safely patchable in binary files
+        // .text section modifications will be overwritten at runtime
+        let is_patchable = name == ".rdata" || name == ".data";
+        SectionInfo {
+            name,
+            virtual_address: section.virtual_address as u64,
+            virtual_size: section.virtual_size as u64,
+            // Raw data starts on the FileAlignment boundary after any padding
+            file_offset: align_to(section.pointer_to_raw_data as usize, file_alignment) as u64,
+            raw_size: section.size_of_raw_data as u64,
+            is_patchable,
         }
-    }
-    None
+    })
+}
+
+/// Check PE section for a given offset
+fn check_pe_offset(pe: &goblin::pe::PE, offset: usize) -> Option<SectionInfo> {
+    pe_sections(pe).find(|info| info.contains_offset(offset))
+}
+
+/// Describe a Mach-O section, named `<segment>.<section>`.
+fn macho_section_info(
+    seg_name: &str,
+    sect: &goblin::mach::segment::Section,
+) -> Option<SectionInfo> {
+    let section_name = sect.name().ok()?;
+    // In Mach-O, patchable sections are typically in __DATA segment
+    // __TEXT segment sections will be protected at runtime
+    let is_patchable = seg_name == "__DATA" ||
+                      seg_name == "__DATA_CONST" ||
+                      // __TEXT.__const is read-only data, sometimes patchable
+                      (seg_name == "__TEXT" && section_name == "__const");
+
+    Some(SectionInfo {
+        name: format!("{}.{}", seg_name, section_name),
+        virtual_address: sect.addr,
+        virtual_size: sect.size,
+        file_offset: sect.offset as u64,
+        raw_size: sect.size,
+        is_patchable,
+    })
 }
 
 /// Check Mach-O section for a given offset
@@ -59,53 +171,51 @@ fn check_macho_offset(mach: &goblin::mach::Mach, offset: usize) -> Option<Sectio
             // Iterate through segments to find which contains our offset
             for segment in &macho.segments {
                 let seg_name = segment.name().ok()?;
-                let file_start = segment.fileoff as usize;
-                let file_end = file_start + segment.filesize as usize;
+                let segment_info = SectionInfo {
+                    name: seg_name.to_string(),
+                    virtual_address: segment.vmaddr,
+                    virtual_size: segment.vmsize,
+                    file_offset: segment.fileoff,
+                    raw_size: segment.filesize,
+                    is_patchable: seg_name == "__DATA" || seg_name == "__DATA_CONST",
+                };
 
-                if offset >= file_start && offset < file_end {
+                if segment_info.contains_offset(offset) {
                     // Now find the specific section within this segment
                     if let Ok(sections) = segment.sections() {
                         for (sect, _) in sections.iter() {
-                            let sect_start = sect.offset as usize;
-                            let sect_end = sect_start + sect.size as usize;
-
-                            if offset >= sect_start && offset < sect_end {
-                                let section_name = sect.name().ok()?;
-
-                                // In Mach-O, patchable sections are typically in __DATA segment
-                                // __TEXT segment sections will be protected at runtime
-                                let is_patchable = seg_name == "__DATA" ||
-                                                  seg_name == "__DATA_CONST" ||
-                                                  // __TEXT.__const is read-only data, sometimes patchable
-                                                  (seg_name == "__TEXT" && section_name == "__const");
-
-                                return Some(SectionInfo {
-                                    name: format!("{}.{}", seg_name, section_name),
-                                    virtual_address: sect.addr,
-                                    virtual_size: sect.size,
-                                    file_offset: sect.offset as u64,
-                                    is_patchable,
-                                });
+                            if let Some(info) = macho_section_info(seg_name, sect)
+                                && info.contains_offset(offset)
+                            {
+                                return Some(info);
                             }
                         }
                     }
 
                     // Found segment but no specific section, return segment info
-                    return Some(SectionInfo {
-                        name: seg_name.to_string(),
-                        virtual_address: segment.vmaddr,
-                        virtual_size: segment.vmsize,
-                        file_offset: segment.fileoff,
-                        is_patchable: seg_name == "__DATA" || seg_name == "__DATA_CONST",
-                    });
+                    return Some(segment_info);
                 }
             }
         }
-        goblin::mach::Mach::Fat(_fat) => {
-            // Fat binaries contain multiple architectures
-            // Proper implementation would require knowing which architecture slice we're patching
-            // For now, return None and handle fat binaries separately if needed
-            return None;
+        goblin::mach::Mach::Fat(multi) => {
+            // Every slice has its own header, so the offset is looked up
+            // relative to the slice holding it and translated back
+            for (index, arch) in multi.iter_arches().enumerate() {
+                let arch = arch.ok()?;
+                let start = arch.offset as usize;
+                let Some(relative) = offset
+                    .checked_sub(start)
+                    .filter(|&relative| relative < arch.size as usize)
+                else {
+                    continue;
+                };
+                let Ok(goblin::mach::SingleArch::MachO(macho)) = multi.get(index) else {
+                    return None;
+                };
+                let mut info = check_macho_offset(&goblin::mach::Mach::Binary(macho), relative)?;
+                info.file_offset += start as u64;
+                return Some(info);
+            }
         }
     }
 
@@ -114,10 +224,18 @@ fn check_macho_offset(mach: &goblin::mach::Mach, offset: usize) -> Option<Sectio
 
 /// Validate that all patterns are found in patchable sections
 pub fn validate_patch_offsets(data: &[u8], offsets: &[(usize, &str)]) -> Result<(), String> {
+    validate_offsets_in(&ParsedBinary::new(data), offsets)
+}
+
+/// Validate offsets against an already parsed binary, see [`validate_patch_offsets`].
+pub(crate) fn validate_offsets_in(
+    binary: &ParsedBinary,
+    offsets: &[(usize, &str)],
+) -> Result<(), String> {
     let mut errors = Vec::new();
 
     for (offset, pattern_name) in offsets {
-        if let Some(section) = check_offset_section(data, *offset) {
+        if let Some(section) = binary.section_at(*offset) {
             if !section.is_patchable {
                 errors.push(format!(
                     "Pattern '{}' found at offset 0x{:x} in non-patchable section '{}'. \
@@ -151,11 +269,75 @@ mod tests {
             virtual_address: 0x1000,
             virtual_size: 0x2000,
             file_offset: 0x400,
+            raw_size: 0x1800,
             is_patchable: true,
         };
 
         assert_eq!(info.name, ".rdata");
         assert!(info.is_patchable);
+        // The file range follows the raw size, not the larger virtual size
+        assert_eq!(info.raw_offset_range(), 0x400..0x1c00);
+
+        assert!(info.contains_offset(0x400));
+        assert!(info.contains_offset(0x1bff));
+        assert!(!info.contains_offset(0x3ff));
+        assert!(!info.contains_offset(0x1c00));
+        assert_eq!(info.offset_relative(0x400), Some(0));
+        assert_eq!(info.offset_relative(0x500), Some(0x100));
+        assert_eq!(info.offset_relative(0x1c00), None);
+    }
+
+    #[test]
+    fn test_section_as_slice() {
+        let mut info = SectionInfo {
+            name: ".rdata".to_string(),
+            virtual_address: 0x1000,
+            virtual_size: 0x10,
+            file_offset: 4,
+            raw_size: 8,
+            is_patchable: true,
+        };
+        let mut data: Vec<u8> = (0..16).collect();
+
+        assert_eq!(info.as_slice(&data), Some(&data[4..12]));
+        info.as_slice_mut(&mut data).unwrap()[0] = 0xAA;
+        assert_eq!(data[4], 0xAA);
+
+        // Matches before the section are not reported
+        assert_eq!(find_pattern_in_section(&data, &info, &vec![2, 3]), None);
+        assert_eq!(
+            find_pattern_in_section(&data, &info, &vec![6, -1, 8]),
+            Some(6)
+        );
+        assert_eq!(find_pattern_in_section(&data, &info, &vec![11, 12]), None);
+
+        info.raw_size = 13;
+        assert_eq!(info.as_slice(&data), None);
+        assert!(info.as_slice_mut(&mut data).is_none());
+        assert_eq!(find_pattern_in_section(&data, &info, &vec![6]), None);
+
+        info.raw_size = u64::MAX;
+        assert_eq!(info.as_slice(&data), None);
+    }
+
+    #[test]
+    fn test_pe_section_start_follows_file_alignment() {
+        use crate::patterns::test_helpers::create_test_executable;
+
+        // .rdata raw data is stored at 0x200, which is already 512-byte aligned
+        let mut data = create_test_executable();
+        let section = check_offset_section(&data, 0x200).unwrap();
+        assert_eq!(section.name, ".rdata");
+        assert_eq!(section.file_offset, 0x200);
+        assert!(check_offset_section(&data, 0x1ff).is_none());
+
+        // With a 4096-byte FileAlignment the raw data starts at the next boundary
+        let file_alignment = 0x98 + 36;
+        data[file_alignment..file_alignment + 4].copy_from_slice(&0x1000u32.to_le_bytes());
+        assert!(check_offset_section(&data, 0x200).is_none());
+        let section = check_offset_section(&data, 0x1000).unwrap();
+        assert_eq!(section.file_offset, 0x1000);
+        assert_eq!(section.raw_offset_range(), 0x1000..0x2000);
     }
 
     #[test]
@@ -166,6 +348,7 @@ mod tests {
             virtual_address: 0,
             virtual_size: 0,
             file_offset: 0,
+            raw_size: 0,
             is_patchable: true,
         };
         assert!(rdata_info.is_patchable, ".rdata should be patchable");
@@ -175,6 +358,7 @@ mod tests {
             virtual_address: 0,
             virtual_size: 0,
             file_offset: 0,
+            raw_size: 0,
             is_patchable: false,
         };
         assert!(!text_info.is_patchable, ".text should NOT be patchable");
@@ -188,6 +372,7 @@ mod tests {
             virtual_address: 0,
             virtual_size: 0,
             file_offset: 0,
+            raw_size: 0,
             is_patchable: true,
         };
         assert!(
@@ -200,6 +385,7 @@ mod tests {
             virtual_address: 0,
             virtual_size: 0,
             file_offset: 0,
+            raw_size: 0,
             is_patchable: false,
         };
         assert!(
@@ -212,6 +398,7 @@ mod tests {
             virtual_address: 0,
             virtual_size: 0,
             file_offset: 0,
+            raw_size: 0,
             is_patchable: true,
         };
         assert!(
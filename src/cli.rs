@@ -1,6 +1,12 @@
+use crate::cmd::test_binary::BinaryTestStatus;
+use crate::errors::{ErrorCategory, ErrorChainIter, WowPatcherError};
 use crate::keys::KeyConfig;
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use crate::patcher::Patcher;
+use crate::platform::{CodesignAction, CodesignOptions};
+use crate::report::ReportMode;
+use clap::{Args, Parser, Subcommand};
+use std::error::Error;
+use std::path::Path;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -16,25 +22,51 @@ This tool modifies your WoW executable by:
 
 The patched client will only work with TrinityCore servers that use valid TLS
 certificates and hostname-based connections (not IP addresses).",
+    after_help = "Exit codes:
+  0  Success
+  1  Patching failed
+  2  Required pattern not found (unsupported build)
+  3  Executable is already patched
+  4  Invalid input (arguments or executable)
+  5  Permission denied",
     version
 )]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
 
+    /// Options for patching, accepted with or without the `patch` subcommand
+    #[command(flatten)]
+    pub patch: PatchArgs,
+
+    /// List every known pattern with its purpose and exit
+    #[arg(long = "list-patterns", default_value_t = false)]
+    pub list_patterns: bool,
+}
+
+/// Options of the default `patch` command.
+///
+/// Every option is global, so it can be given before or after a subcommand.
+/// [`PatchArgs::default`] matches a command line without any flags.
+#[derive(Args, Debug, Clone)]
+pub struct PatchArgs {
     /// Path to your WoW executable (auto-detected on macOS)
     #[arg(short = 'l', long = "warcraft-exe", value_name = "FILE", global = true)]
     pub location: Option<String>,
 
-    /// Output filename for the patched WoW executable
+    /// Output filename for the patched WoW executable [default: Arctium]
+    #[arg(short = 'o', long = "output-file", value_name = "FILE", global = true)]
+    pub output: Option<String>,
+
+    /// Write to the executable's name with this suffix added, e.g. -trinity for Wow-trinity.exe
     #[arg(
-        short = 'o',
-        long = "output-file",
-        value_name = "FILE",
-        default_value = "Arctium",
+        long = "output-suffix",
+        value_name = "SUFFIX",
+        conflicts_with = "output",
+        allow_hyphen_values = true,
         global = true
     )]
-    pub output: Option<String>,
+    pub output_suffix: Option<String>,
 
     /// Preview changes without modifying any files
     #[arg(short = 'n', long = "dry-run", default_value_t = false, global = true)]
@@ -49,10 +81,42 @@ pub struct Cli {
     )]
     pub sign: bool,
 
+    /// Re-sign the patched macOS executable with this keychain identity ("-" for ad hoc) instead of stripping the signature
+    #[arg(
+        long = "sign-identity",
+        value_name = "IDENTITY",
+        conflicts_with = "sign",
+        global = true
+    )]
+    pub sign_identity: Option<String>,
+
+    /// Unix permissions of the output file as octal, e.g. 755 or 644 (644 is not executable)
+    #[arg(
+        long = "output-mode",
+        value_name = "OCTAL",
+        value_parser = parse_octal_mode,
+        global = true
+    )]
+    pub output_mode: Option<u32>,
+
+    /// Prompt for the executable, output file and key preset when they are missing
+    #[cfg(feature = "cli-interactive")]
+    #[arg(
+        short = 'i',
+        long = "interactive",
+        default_value_t = false,
+        global = true
+    )]
+    pub interactive: bool,
+
     /// Enable verbose output
     #[arg(short = 'v', long, default_value_t = false, global = true)]
     pub verbose: bool,
 
+    /// Named server key preset (trinitycore, azerothcore), individual key flags override it
+    #[arg(long = "preset", value_name = "NAME", global = true)]
+    pub preset: Option<String>,
+
     /// Custom RSA modulus file (256 bytes binary)
     #[arg(long = "rsa-file", value_name = "FILE", global = true)]
     pub rsa_file: Option<String>,
@@ -61,6 +125,10 @@ pub struct Cli {
     #[arg(long = "rsa-hex", value_name = "HEX", global = true)]
     pub rsa_hex: Option<String>,
 
+    /// Custom RSA modulus as standard base64 string (256 bytes)
+    #[arg(long = "rsa-base64", value_name = "BASE64", global = true)]
+    pub rsa_base64: Option<String>,
+
     /// Custom Ed25519 public key file (32 bytes binary)
     #[arg(long = "ed25519-file", value_name = "FILE", global = true)]
     pub ed25519_file: Option<String>,
@@ -69,6 +137,10 @@ pub struct Cli {
     #[arg(long = "ed25519-hex", value_name = "HEX", global = true)]
     pub ed25519_hex: Option<String>,
 
+    /// Custom Ed25519 public key as standard base64 string (32 bytes)
+    #[arg(long = "ed25519-base64", value_name = "BASE64", global = true)]
+    pub ed25519_base64: Option<String>,
+
     /// Custom version URL for CDN redirection
     #[arg(long = "version-url", value_name = "URL", global = true)]
     pub version_url: Option<String>,
@@ -76,10 +148,38 @@ pub struct Cli {
     /// Custom CDNs URL for CDN redirection
     #[arg(long = "cdns-url", value_name = "URL", global = true)]
     pub cdns_url: Option<String>,
+
+    /// Custom unified API URL for the v3 pattern (WoW Classic 1.15.8+), with up to two %s placeholders
+    #[arg(long = "cdns-url-v3", value_name = "URL", global = true)]
+    pub cdns_url_v3: Option<String>,
+
+    /// Point the version, CDNs and v3 URLs at this CDN, e.g. http://cdn.lan (the URL flags override it)
+    #[arg(long = "cdn-base", value_name = "URL", global = true)]
+    pub cdn_base: Option<String>,
+
+    /// Copy the original executable into this directory before patching
+    #[arg(long = "backup-dir", value_name = "DIR", global = true)]
+    pub backup_dir: Option<String>,
+
+    /// Never create a backup, even with --backup-dir (the patch cannot be undone)
+    #[arg(long = "no-backup", default_value_t = false, global = true)]
+    pub no_backup: bool,
+
+    /// Refuse to patch unless the client has this build number
+    #[arg(long = "expected-build", value_name = "BUILD", global = true)]
+    pub expected_build: Option<u16>,
+}
+
+impl Default for PatchArgs {
+    fn default() -> Self {
+        Cli::parse_from(["wow-patcher"]).patch
+    }
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
+    /// Patch a WoW executable (the default when no subcommand is given)
+    Patch,
     /// Print version information
     Version {
         /// Show detailed version information
@@ -101,129 +201,634 @@ pub enum Commands {
         #[arg(short = 'w', long, default_value_t = 0)]
         wait: u64,
     },
+    /// Show client type, version and fingerprint of a WoW executable
+    Inspect {
+        /// Print the import hash (imphash) of the executable
+        #[arg(long)]
+        imphash: bool,
+    },
+    /// Check whether a WoW executable contains the patterns needed for patching
+    ///
+    /// Exits with 0 if every required pattern is in a patchable section, 1 if a
+    /// required pattern is missing and 2 if one is in a non-patchable section.
+    TestBinary {
+        /// WoW executable to scan
+        file: String,
+    },
+    /// Restore an original WoW executable from a backup
+    Unpatch {
+        /// Backup file created before patching
+        #[arg(long, value_name = "FILE")]
+        backup: String,
+
+        /// Executable to restore (defaults to the standard WoW location)
+        #[arg(long, value_name = "FILE")]
+        output: Option<String>,
+
+        /// Check that the restored executable no longer contains TrinityCore keys
+        #[arg(long = "verify-after")]
+        verify_after: bool,
+    },
+    /// Print a shell completion script
+    ///
+    /// With --install the script is written to the location the shell loads
+    /// completions from instead of stdout.
+    Completions {
+        /// Shell to generate completions for
+        shell: clap_complete::Shell,
+
+        /// Install the script for the current user instead of printing it
+        #[arg(long)]
+        install: bool,
+    },
+    /// Find candidate key patterns in a known-good executable (maintainers only)
+    ///
+    /// Runs the heuristic key discovery and prints the leading 8 bytes of every
+    /// candidate as a Rust array literal.
+    #[cfg(feature = "dev-tools")]
+    UpdatePatterns {
+        /// WoW executable to scan
+        file: String,
+
+        /// Also print pattern functions ready to paste into src/patterns/mod.rs
+        #[arg(long = "emit-hex")]
+        emit_hex: bool,
+    },
 }
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// Process exit codes returned by the CLI so scripts can tell failures apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum ExitReason {
+    /// Patching (or the requested subcommand) completed successfully
+    Success = 0,
+    /// Patching failed for a reason not covered by a more specific code
+    PatchingFailed = 1,
+    /// A required pattern was not found in the executable
+    PatternNotFound = 2,
+    /// The executable already contains the replacement keys
+    AlreadyPatched = 3,
+    /// Arguments or the input file failed validation
+    InvalidInput = 4,
+    /// The input or output file could not be accessed due to permissions
+    PermissionDenied = 5,
+}
+
+impl ExitReason {
+    /// Map a `test-binary` result onto its documented exit code.
+    ///
+    /// `test-binary` gives codes 1 and 2 their own meaning: a missing required
+    /// pattern and a required pattern outside a patchable section.
+    pub fn from_test_status(status: BinaryTestStatus) -> Self {
+        match status {
+            BinaryTestStatus::Patchable => ExitReason::Success,
+            BinaryTestStatus::MissingPattern => ExitReason::PatchingFailed,
+            BinaryTestStatus::NonPatchableSection => ExitReason::PatternNotFound,
+        }
+    }
+
+    /// Classify an error into the matching exit reason.
+    pub fn from_error(error: &(dyn std::error::Error + 'static)) -> Self {
+        if let Some(err) = error.downcast_ref::<WowPatcherError>() {
+            if err.get_context("already_patched").is_some() {
+                return ExitReason::AlreadyPatched;
+            }
+
+            if let Some(io_err) = err
+                .source()
+                .and_then(|cause| cause.downcast_ref::<std::io::Error>())
+                && io_err.kind() == std::io::ErrorKind::PermissionDenied
+            {
+                return ExitReason::PermissionDenied;
+            }
+
+            // Only a missing pattern means an unsupported build, other
+            // patching errors are plain failures
+            let pattern_missing = err.get_context("pattern_missing").is_some()
+                || err.get_context("pattern").is_some();
+
+            return match err.category {
+                ErrorCategory::PatchingError if pattern_missing => ExitReason::PatternNotFound,
+                ErrorCategory::ValidationError
+                | ErrorCategory::CryptoError
+                | ErrorCategory::NetworkError => ExitReason::InvalidInput,
+                _ => ExitReason::PatchingFailed,
+            };
+        }
+
+        if let Some(io_err) = error.downcast_ref::<std::io::Error>()
+            && io_err.kind() == std::io::ErrorKind::PermissionDenied
+        {
+            return ExitReason::PermissionDenied;
+        }
+
+        ExitReason::PatchingFailed
+    }
+}
+
+fn print_pattern_list() {
+    println!("{:<22} {:<8} Purpose", "Pattern", "Section");
+    for name in crate::patterns::PATTERN_NAMES {
+        if let Some(description) = crate::patterns::describe(name) {
+            println!(
+                "{:<22} {:<8} {}",
+                description.name, description.section, description.purpose
+            );
+        }
+    }
+}
+
+/// Parse a permission mode such as "755" or "0o644" as octal.
+fn parse_octal_mode(value: &str) -> Result<u32, String> {
+    let digits = value.strip_prefix("0o").unwrap_or(value);
+    match u32::from_str_radix(digits, 8) {
+        Ok(mode) if mode <= 0o7777 => Ok(mode),
+        _ => Err(format!("'{}' is not an octal mode like 755", value)),
+    }
+}
+
+/// Ask for the executable, output file and key preset when they were not given.
+#[cfg(feature = "cli-interactive")]
+fn prompt_missing_arguments(args: &mut PatchArgs) -> Result<(), WowPatcherError> {
+    use crate::cmd::interactive::{prompt_input_path, prompt_key_preset, prompt_output_path};
+
+    let location = args
+        .location
+        .clone()
+        .unwrap_or_else(crate::platform::find_warcraft_client_executable);
+    if !Path::new(&location).is_file() {
+        args.location = Some(prompt_input_path()?.display().to_string());
+    }
+    if args.output.is_none() {
+        let location = args.location.clone().unwrap_or(location);
+        let mut patcher = Patcher::new(location);
+        if let Some(suffix) = &args.output_suffix {
+            patcher = patcher.with_output_suffix(suffix);
+        }
+        let output = prompt_output_path(&patcher.output_path())?;
+        args.output = Some(output.display().to_string());
+    }
+
+    let key_flags = [
+        &args.rsa_file,
+        &args.rsa_hex,
+        &args.rsa_base64,
+        &args.ed25519_file,
+        &args.ed25519_hex,
+        &args.ed25519_base64,
+    ];
+    if args.preset.is_none() && key_flags.iter().all(|flag| flag.is_none()) {
+        args.preset = Some(prompt_key_preset()?);
+    }
+    Ok(())
+}
+
+fn invalid_input(message: &str) -> Box<dyn std::error::Error> {
+    Box::new(WowPatcherError::new(
+        ErrorCategory::ValidationError,
+        message,
+    ))
+}
+
+/// Run the CLI and return the exit reason for the process.
+pub fn run() -> ExitReason {
     let cli = Cli::parse();
 
+    match run_command(cli) {
+        Ok(reason) => reason,
+        Err(e) => {
+            eprintln!("❌ Patching failed - the client has not been modified.");
+            eprintln!();
+            for (depth, cause) in ErrorChainIter::new(e.as_ref()).enumerate() {
+                // Causes are printed on their own lines, so show each level without its source
+                let text = match cause.downcast_ref::<WowPatcherError>() {
+                    Some(err) => match err.offset() {
+                        Some(offset) => format!(
+                            "[{}] {} at file offset 0x{:x}",
+                            err.category, err.message, offset
+                        ),
+                        None => format!("[{}] {}", err.category, err.message),
+                    },
+                    None => cause.to_string(),
+                };
+                if depth == 0 {
+                    eprintln!("Error: {}", text);
+                } else {
+                    eprintln!("  Caused by: {}", text);
+                }
+            }
+            eprintln!();
+
+            if let Some(suggestion) = e
+                .downcast_ref::<WowPatcherError>()
+                .and_then(WowPatcherError::suggestion)
+            {
+                eprintln!("💡 {}", suggestion);
+            } else {
+                eprintln!("💡 Common solutions:");
+                eprintln!("  • Ensure you have the correct path to your WoW executable");
+                eprintln!("  • Check that you have read/write permissions");
+                eprintln!("  • Verify the WoW executable is not currently running");
+                eprintln!("  • Make sure the output directory exists");
+            }
+            ExitReason::from_error(e.as_ref())
+        }
+    }
+}
+
+fn run_command(cli: Cli) -> Result<ExitReason, Box<dyn std::error::Error>> {
+    if cli.list_patterns {
+        print_pattern_list();
+        return Ok(ExitReason::Success);
+    }
+
     match cli.command {
+        Some(Commands::Patch) | None => {
+            patch_command(cli.patch)?;
+            Ok(ExitReason::Success)
+        }
         Some(Commands::Version { detailed }) => {
             if detailed {
                 println!("{}", crate::version::detailed_info());
             } else {
                 println!("{}", crate::version::info());
             }
-            Ok(())
+            Ok(ExitReason::Success)
         }
         Some(Commands::DumpText { output, wait }) => {
             let location = cli
+                .patch
                 .location
                 .unwrap_or_else(crate::platform::find_warcraft_client_executable);
 
             if location.is_empty() {
-                return Err(
-                    "No WoW executable specified. Use -l flag to specify the path.".into(),
-                );
+                return Err(invalid_input(
+                    "No WoW executable specified. Use -l flag to specify the path.",
+                ));
             }
 
             #[cfg(target_os = "windows")]
             {
-                crate::cmd::dump::win::dump_text_section(&location, &output, wait, cli.verbose)?;
-                return Ok(());
+                crate::cmd::dump::win::dump_text_section(
+                    &location,
+                    &output,
+                    wait,
+                    cli.patch.verbose,
+                )?;
+                Ok(ExitReason::Success)
             }
             #[cfg(not(target_os = "windows"))]
             {
-                let _ = (&location, &output);
-                return Err("dump-text requires Windows (or Wine). Cross-compile with: cargo build --target x86_64-pc-windows-gnu".into());
+                let _ = (&location, &output, wait);
+                Err("dump-text requires Windows (or Wine). Cross-compile with: cargo build --target x86_64-pc-windows-gnu".into())
             }
         }
-        None => {
-            // Default behavior - patch the file
+        Some(Commands::Inspect { imphash }) => {
             let location = cli
+                .patch
                 .location
                 .unwrap_or_else(crate::platform::find_warcraft_client_executable);
 
             if location.is_empty() {
-                return Err("No WoW executable specified. Use -l flag to specify the path.".into());
+                return Err(invalid_input(
+                    "No WoW executable specified. Use -l flag to specify the path.",
+                ));
             }
 
-            // Build key configuration from CLI arguments
-            let mut key_config = KeyConfig::default();
-
-            // Check for conflicting RSA arguments
-            if cli.rsa_file.is_some() && cli.rsa_hex.is_some() {
-                return Err("Cannot specify both --rsa-file and --rsa-hex at the same time".into());
+            crate::cmd::inspect::execute_inspect(Path::new(&location), imphash)?;
+            Ok(ExitReason::Success)
+        }
+        Some(Commands::TestBinary { file }) => {
+            let status = crate::cmd::test_binary::execute_test_binary(Path::new(&file))?;
+            Ok(ExitReason::from_test_status(status))
+        }
+        Some(Commands::Completions { shell, install }) => {
+            crate::cmd::completions::execute_completions(shell, install)?;
+            Ok(ExitReason::Success)
+        }
+        #[cfg(feature = "dev-tools")]
+        Some(Commands::UpdatePatterns { file, emit_hex }) => {
+            crate::cmd::update_patterns::execute_update_patterns(Path::new(&file), emit_hex)?;
+            Ok(ExitReason::Success)
+        }
+        Some(Commands::Unpatch {
+            backup,
+            output,
+            verify_after,
+        }) => {
+            let output = output.unwrap_or_else(crate::platform::find_warcraft_client_executable);
+
+            if output.is_empty() {
+                return Err(invalid_input(
+                    "No WoW executable specified. Use --output to specify the path.",
+                ));
             }
 
-            // Check for conflicting Ed25519 arguments
-            if cli.ed25519_file.is_some() && cli.ed25519_hex.is_some() {
-                return Err(
-                    "Cannot specify both --ed25519-file and --ed25519-hex at the same time".into(),
-                );
-            }
+            Patcher::unpatch_from_backup(&backup, &output)?;
+            println!("✓ Restored {} from {}", output, backup);
 
-            // Load RSA modulus from file or hex
-            if let Some(rsa_file) = &cli.rsa_file {
-                key_config = key_config.with_rsa_from_file(rsa_file)?;
-            } else if let Some(rsa_hex) = &cli.rsa_hex {
-                key_config = key_config.with_rsa_from_hex(rsa_hex)?;
+            if verify_after {
+                if Patcher::new(&output).verify()? {
+                    return Err(format!(
+                        "{} still contains TrinityCore keys after restoring",
+                        output
+                    )
+                    .into());
+                }
+                println!("✓ No TrinityCore patches found in {}", output);
             }
+            Ok(ExitReason::Success)
+        }
+    }
+}
 
-            // Load Ed25519 key from file or hex
-            if let Some(ed25519_file) = &cli.ed25519_file {
-                key_config = key_config.with_ed25519_from_file(ed25519_file)?;
-            } else if let Some(ed25519_hex) = &cli.ed25519_hex {
-                key_config = key_config.with_ed25519_from_hex(ed25519_hex)?;
-            }
+/// Patch a WoW executable as the CLI does, without parsing the command line.
+///
+/// Lets tools with their own argument parsing run the default command:
+/// build a [`PatchArgs`] from [`PatchArgs::default`] and set the fields
+/// that would otherwise come from flags.
+///
+/// # Errors
+///
+/// Returns an error if the arguments are invalid or patching fails.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wow_patcher::cli::{PatchArgs, patch_command};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// patch_command(PatchArgs {
+///     location: Some("Wow.exe".to_string()),
+///     output: Some("Wow-patched.exe".to_string()),
+///     preset: Some("azerothcore".to_string()),
+///     ..PatchArgs::default()
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn patch_command(args: PatchArgs) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "cli-interactive")]
+    let mut args = args;
+    #[cfg(feature = "cli-interactive")]
+    if args.interactive {
+        prompt_missing_arguments(&mut args)?;
+    }
 
-            // Validate URL parameters
-            if let Some(version_url) = &cli.version_url {
-                if !version_url.starts_with("http://") && !version_url.starts_with("https://") {
-                    return Err("Version URL must start with http:// or https://".into());
-                }
-                if version_url.len() > 512 {
-                    return Err("Version URL too long (max 512 characters)".into());
-                }
-            }
+    let location = args
+        .location
+        .unwrap_or_else(crate::platform::find_warcraft_client_executable);
 
-            if let Some(cdns_url) = &cli.cdns_url {
-                if !cdns_url.starts_with("http://") && !cdns_url.starts_with("https://") {
-                    return Err("CDNs URL must start with http:// or https://".into());
-                }
-                if cdns_url.len() > 512 {
-                    return Err("CDNs URL too long (max 512 characters)".into());
-                }
-            }
+    if location.is_empty() {
+        return Err(invalid_input(
+            "No WoW executable specified. Use -l flag to specify the path.",
+        ));
+    }
 
-            if cli.verbose && !key_config.is_trinity_core() {
-                println!("Using custom server keys: {}", key_config.display_info());
-            }
+    // Build key configuration from CLI arguments, starting from the preset if any
+    let mut key_config = match &args.preset {
+        Some(name) => KeyConfig::named_preset(name)?,
+        None => KeyConfig::default(),
+    };
+
+    // Check for conflicting RSA arguments
+    let rsa_sources = [&args.rsa_file, &args.rsa_hex, &args.rsa_base64];
+    if rsa_sources.iter().filter(|source| source.is_some()).count() > 1 {
+        return Err(invalid_input(
+            "Specify only one of --rsa-file, --rsa-hex and --rsa-base64",
+        ));
+    }
 
-            if cli.verbose && (cli.version_url.is_some() || cli.cdns_url.is_some()) {
-                println!("Using custom CDN URLs:");
-                if let Some(version_url) = &cli.version_url {
-                    println!("  Version URL: {}", version_url);
-                }
-                if let Some(cdns_url) = &cli.cdns_url {
-                    println!("  CDNs URL: {}", cdns_url);
-                }
-            }
+    // Check for conflicting Ed25519 arguments
+    let ed25519_sources = [&args.ed25519_file, &args.ed25519_hex, &args.ed25519_base64];
+    if ed25519_sources
+        .iter()
+        .filter(|source| source.is_some())
+        .count()
+        > 1
+    {
+        return Err(invalid_input(
+            "Specify only one of --ed25519-file, --ed25519-hex and --ed25519-base64",
+        ));
+    }
 
-            let input_path = PathBuf::from(&location);
-            let output_path = PathBuf::from(cli.output.unwrap_or_else(|| "Arctium".to_string()));
+    // Load RSA modulus from file, hex or base64
+    if let Some(rsa_file) = &args.rsa_file {
+        key_config = key_config.with_rsa_from_file(rsa_file)?;
+    } else if let Some(rsa_hex) = &args.rsa_hex {
+        key_config = key_config.with_rsa_from_hex(rsa_hex)?;
+    } else if let Some(rsa_base64) = &args.rsa_base64 {
+        key_config = key_config.with_rsa_from_base64(rsa_base64)?;
+    }
 
-            crate::cmd::execute::execute_patch(
-                &input_path,
-                &output_path,
-                key_config,
-                cli.version_url.as_deref(),
-                cli.cdns_url.as_deref(),
-                cli.dry_run,
-                cli.sign,
-                cli.verbose,
-            )?;
+    // Load Ed25519 key from file, hex or base64
+    if let Some(ed25519_file) = &args.ed25519_file {
+        key_config = key_config.with_ed25519_from_file(ed25519_file)?;
+    } else if let Some(ed25519_hex) = &args.ed25519_hex {
+        key_config = key_config.with_ed25519_from_hex(ed25519_hex)?;
+    } else if let Some(ed25519_base64) = &args.ed25519_base64 {
+        key_config = key_config.with_ed25519_from_base64(ed25519_base64)?;
+    }
+
+    // Validate URL schemes, Patcher::patch checks that the URLs fit their patterns
+    for (name, url) in [
+        ("Version URL", &args.version_url),
+        ("CDNs URL", &args.cdns_url),
+        ("v3 URL", &args.cdns_url_v3),
+        ("CDN base", &args.cdn_base),
+    ] {
+        if let Some(url) = url
+            && !url.starts_with("http://")
+            && !url.starts_with("https://")
+        {
+            return Err(invalid_input(&format!(
+                "{} must start with http:// or https://",
+                name
+            )));
+        }
+    }
+
+    let mut patcher = Patcher::new(&location)
+        .with_key_config(key_config)
+        .dry_run(args.dry_run)
+        .report_mode(if args.verbose {
+            ReportMode::Verbose
+        } else {
+            ReportMode::Normal
+        })
+        // --no-backup wins over --backup-dir
+        .skip_backup(args.no_backup);
+    patcher = match (&args.output, &args.output_suffix) {
+        (Some(output), _) => patcher.output(output),
+        (None, Some(suffix)) => patcher.with_output_suffix(suffix),
+        (None, None) => patcher.output("Arctium"),
+    };
+    patcher = match &args.sign_identity {
+        Some(identity) => patcher.strip_codesign_options(CodesignOptions {
+            action: CodesignAction::Sign(identity.clone()),
+            deep: false,
+            force: true,
+        }),
+        None => patcher.strip_codesign(args.sign),
+    };
+    // The CDN base goes first, so the URL flags override what it derives
+    if let Some(cdn_base) = &args.cdn_base {
+        patcher = patcher.custom_cdn(cdn_base);
+    }
+    if let Some(version_url) = &args.version_url {
+        patcher = patcher.version_url(version_url);
+    }
+    if let Some(cdns_url) = &args.cdns_url {
+        patcher = patcher.cdns_url(cdns_url);
+    }
+    if let Some(cdns_url_v3) = &args.cdns_url_v3 {
+        patcher = patcher.cdns_url_v3(cdns_url_v3);
+    }
+    if let Some(mode) = args.output_mode {
+        patcher = patcher.with_output_permissions(mode);
+    }
+    if let Some(backup_dir) = &args.backup_dir {
+        patcher = patcher.with_backup_dir(backup_dir);
+    }
+    if let Some(build) = args.expected_build {
+        patcher = patcher.with_expected_build(build);
+    }
+
+    patcher.patch()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_reason_codes() {
+        assert_eq!(ExitReason::Success as i32, 0);
+        assert_eq!(ExitReason::PatchingFailed as i32, 1);
+        assert_eq!(ExitReason::PatternNotFound as i32, 2);
+        assert_eq!(ExitReason::AlreadyPatched as i32, 3);
+        assert_eq!(ExitReason::InvalidInput as i32, 4);
+        assert_eq!(ExitReason::PermissionDenied as i32, 5);
+    }
+
+    #[test]
+    fn test_output_suffix_flag() {
+        let cli = Cli::try_parse_from(["wow-patcher", "--output-suffix", "-trinity"]).unwrap();
+        assert_eq!(cli.patch.output_suffix.as_deref(), Some("-trinity"));
+        assert!(
+            Cli::try_parse_from(["wow-patcher", "-o", "out.exe", "--output-suffix", "-tc"])
+                .is_err()
+        );
+    }
 
-            Ok(())
+    #[test]
+    fn test_sign_identity_flag() {
+        let cli = Cli::try_parse_from(["wow-patcher", "--sign-identity", "-"]).unwrap();
+        assert_eq!(cli.patch.sign_identity.as_deref(), Some("-"));
+        assert!(Cli::try_parse_from(["wow-patcher", "-s", "--sign-identity", "-"]).is_err());
+    }
+
+    #[test]
+    fn test_patch_subcommand() {
+        let implicit = Cli::try_parse_from(["wow-patcher", "-l", "Wow.exe", "-n"]).unwrap();
+        assert!(implicit.command.is_none());
+
+        // Options are accepted before and after the explicit subcommand
+        for args in [
+            ["wow-patcher", "patch", "-l", "Wow.exe", "-n"],
+            ["wow-patcher", "-l", "Wow.exe", "patch", "-n"],
+        ] {
+            let cli = Cli::try_parse_from(args).unwrap();
+            assert!(matches!(cli.command, Some(Commands::Patch)));
+            assert_eq!(cli.patch.location, implicit.patch.location);
+            assert!(cli.patch.dry_run);
         }
+
+        let defaults = PatchArgs::default();
+        assert!(defaults.location.is_none());
+        assert!(defaults.sign);
+        assert!(!defaults.dry_run);
+    }
+
+    #[test]
+    fn test_patch_command() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let input = temp_dir.path().join("Wow.exe");
+        let output = temp_dir.path().join("Wow-patched.exe");
+        std::fs::write(
+            &input,
+            crate::patterns::test_helpers::create_test_executable(),
+        )
+        .unwrap();
+
+        patch_command(PatchArgs {
+            location: Some(input.display().to_string()),
+            output: Some(output.display().to_string()),
+            ..PatchArgs::default()
+        })
+        .unwrap();
+        assert!(output.exists());
+
+        let err = patch_command(PatchArgs {
+            location: Some(input.display().to_string()),
+            rsa_hex: Some("00".to_string()),
+            rsa_base64: Some("AA==".to_string()),
+            ..PatchArgs::default()
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("Specify only one of"));
+    }
+
+    #[test]
+    fn test_parse_octal_mode() {
+        assert_eq!(parse_octal_mode("755"), Ok(0o755));
+        assert_eq!(parse_octal_mode("644"), Ok(0o644));
+        assert_eq!(parse_octal_mode("0o600"), Ok(0o600));
+        assert!(parse_octal_mode("789").is_err());
+        assert!(parse_octal_mode("17777").is_err());
+        assert!(parse_octal_mode("").is_err());
+    }
+
+    #[test]
+    fn test_exit_reason_from_error() {
+        let err = crate::errors::new_pattern_missing_error("pattern not found");
+        assert_eq!(ExitReason::from_error(&err), ExitReason::PatternNotFound);
+
+        let mut data = vec![0u8; 4];
+        let err = crate::binary::patch(&mut data, &vec![1, 2], &[3, 4]).unwrap_err();
+        assert_eq!(ExitReason::from_error(&err), ExitReason::PatternNotFound);
+
+        let err = crate::errors::new_patching_error(
+            "failed to patch",
+            std::io::Error::other("missing"),
+            "portal_pattern",
+        );
+        assert_eq!(ExitReason::from_error(&err), ExitReason::PatternNotFound);
+
+        let err = crate::binary::patch_region(&mut data, 3, 1, &vec![1], &[2]).unwrap_err();
+        assert_eq!(ExitReason::from_error(&err), ExitReason::PatchingFailed);
+
+        let err = WowPatcherError::new(ErrorCategory::PatchingError, "Patching task failed");
+        assert_eq!(ExitReason::from_error(&err), ExitReason::PatchingFailed);
+
+        let err = err.with_context("already_patched", true);
+        assert_eq!(ExitReason::from_error(&err), ExitReason::AlreadyPatched);
+
+        let err = WowPatcherError::new(ErrorCategory::ValidationError, "bad input");
+        assert_eq!(ExitReason::from_error(&err), ExitReason::InvalidInput);
+
+        let err = WowPatcherError::new(ErrorCategory::CryptoError, "bad key");
+        assert_eq!(ExitReason::from_error(&err), ExitReason::InvalidInput);
+
+        let err = WowPatcherError::wrap(
+            ErrorCategory::FileOperationError,
+            "write failed",
+            std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied"),
+        );
+        assert_eq!(ExitReason::from_error(&err), ExitReason::PermissionDenied);
+
+        let err = std::io::Error::other("unexpected");
+        assert_eq!(ExitReason::from_error(&err), ExitReason::PatchingFailed);
     }
 }
@@ -0,0 +1,141 @@
+use crate::cli::Cli;
+use crate::errors::{ErrorCategory, WowPatcherError};
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const BIN_NAME: &str = "wow-patcher";
+
+/// Generate a completion script for `shell`.
+///
+/// Without `install` the script is written to stdout. With `install` it is
+/// written to the location the shell loads completions from and the path is
+/// printed.
+pub fn execute_completions(shell: Shell, install: bool) -> Result<(), WowPatcherError> {
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut Cli::command(), BIN_NAME, &mut script);
+
+    if !install {
+        return std::io::stdout().write_all(&script).map_err(|e| {
+            WowPatcherError::wrap(
+                ErrorCategory::FileOperationError,
+                "Failed to write completions",
+                e,
+            )
+        });
+    }
+
+    let home = home_dir().ok_or_else(|| {
+        WowPatcherError::new(
+            ErrorCategory::ValidationError,
+            "Unable to determine the home directory",
+        )
+        .with_suggestion("Run without --install and redirect the output instead")
+    })?;
+    let path = install_path(shell, &home, zsh_fpath().as_deref()).ok_or_else(|| {
+        WowPatcherError::new(
+            ErrorCategory::ValidationError,
+            format!("--install is not supported for {}", shell),
+        )
+        .with_suggestion("Run without --install and redirect the output instead")
+    })?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| install_error(parent, e))?;
+    }
+    fs::write(&path, script).map_err(|e| install_error(&path, e))?;
+    println!("✓ Installed {} completions to {}", shell, path.display());
+    Ok(())
+}
+
+/// Location `shell` loads completions for `wow-patcher` from on this platform.
+///
+/// `zsh_fpath` is the first entry of zsh's `$fpath`, used on platforms other
+/// than macOS.
+pub(crate) fn install_path(shell: Shell, home: &Path, zsh_fpath: Option<&Path>) -> Option<PathBuf> {
+    match shell {
+        Shell::Bash if !cfg!(windows) => Some(
+            home.join(".local/share/bash-completion/completions")
+                .join(BIN_NAME),
+        ),
+        Shell::Fish if !cfg!(windows) => Some(
+            home.join(".config/fish/completions")
+                .join(format!("{}.fish", BIN_NAME)),
+        ),
+        // Homebrew's zsh loads site functions from here
+        Shell::Zsh if cfg!(target_os = "macos") => Some(
+            PathBuf::from("/usr/local/share/zsh/site-functions").join(format!("_{}", BIN_NAME)),
+        ),
+        Shell::Zsh if !cfg!(windows) => Some(zsh_fpath?.join(format!("_{}", BIN_NAME))),
+        Shell::PowerShell if cfg!(windows) => Some(
+            home.join("Documents")
+                .join("PowerShell")
+                .join(format!("{}.ps1", BIN_NAME)),
+        ),
+        _ => None,
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    std::env::var_os(var)
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+/// First entry of zsh's `$fpath`, which is not exported to the environment.
+fn zsh_fpath() -> Option<PathBuf> {
+    let output = std::process::Command::new("zsh")
+        .args(["-c", "echo $fpath[1]"])
+        .output()
+        .ok()?;
+    let path = String::from_utf8(output.stdout).ok()?;
+    let path = path.trim();
+    (output.status.success() && !path.is_empty()).then(|| PathBuf::from(path))
+}
+
+fn install_error(path: &Path, cause: std::io::Error) -> WowPatcherError {
+    WowPatcherError::wrap(
+        ErrorCategory::FileOperationError,
+        "Failed to install completions",
+        cause,
+    )
+    .with_context("path", path.display().to_string())
+    .with_suggestion("Run without --install and redirect the output instead")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_install_path_linux() {
+        let home = Path::new("/home/user");
+        assert_eq!(
+            install_path(Shell::Bash, home, None),
+            Some(PathBuf::from(
+                "/home/user/.local/share/bash-completion/completions/wow-patcher"
+            ))
+        );
+        assert_eq!(
+            install_path(Shell::Fish, home, None),
+            Some(PathBuf::from(
+                "/home/user/.config/fish/completions/wow-patcher.fish"
+            ))
+        );
+        assert_eq!(
+            install_path(
+                Shell::Zsh,
+                home,
+                Some(Path::new("/usr/share/zsh/functions"))
+            ),
+            Some(PathBuf::from("/usr/share/zsh/functions/_wow-patcher"))
+        );
+        assert_eq!(install_path(Shell::Zsh, home, None), None);
+        assert_eq!(install_path(Shell::PowerShell, home, None), None);
+        assert_eq!(install_path(Shell::Elvish, home, None), None);
+    }
+}
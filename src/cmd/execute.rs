@@ -1,38 +1,243 @@
-use crate::binary::{DataExt, PatternExt, check_offset_section, patch, validate_patch_offsets};
+use crate::binary::section::validate_offsets_in;
+use crate::binary::{Arch, BinaryFormat, DataExt, ParsedBinary, detect_format, select_slices};
+use crate::cmd::steps::{CustomBytePatch, PatchContext, PatchPipeline};
 use crate::errors::{ErrorCategory, WowPatcherError};
 use crate::keys::KeyConfig;
+use crate::patcher::PatchProgress;
 use crate::patterns::{
     cdns_url_pattern, connect_to_modulus_pattern, crypto_ed_public_key_pattern,
     crypto_rsa_modulus_pattern, portal_pattern, signature_modulus_pattern, version_url_pattern,
     version_url_v2_pattern, version_url_v3_pattern,
 };
 use crate::platform::{
-    detect_client_type, extract_version, extract_version_fallback, remove_codesigning_signature,
+    ClientType, CodesignAction, CodesignOptions, Version, codesign_identifier,
+    detect_client_type_from_binary, detect_client_type_from_path, host_macos_version,
+    remove_codesigning_signature,
 };
-use crate::trinity::{create_url_replacement, get_cdns_url, get_unified_api_url, get_version_url};
+use crate::report::{ConsoleReporter, PatchRecord, PatchReport, PatchStatus, ReportMode, Reporter};
+use crate::trinity::{ARCTIUM_CDN, PORTAL_REPLACEMENT, VersionUrlConfig};
+use std::fmt::{self, Write as _};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-#[allow(clippy::too_many_arguments)]
+/// Default maximum accepted executable size (1 GB)
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Default minimum accepted executable size (1 KB)
+pub const DEFAULT_MIN_FILE_SIZE: u64 = 1024;
+
+/// Default Unix permissions of the patched executable (`rwxr-xr-x`)
+pub const DEFAULT_OUTPUT_MODE: u32 = 0o755;
+
+/// Default limit on replacements of a required pattern (portal, RSA modulus)
+pub const DEFAULT_MAX_REQUIRED_PATCH_COUNT: usize = 1;
+
+/// Default limit on replacements of an optional pattern (Ed25519 key, URLs)
+pub const DEFAULT_MAX_OPTIONAL_PATCH_COUNT: usize = 10;
+
+/// Options controlling a single [`execute_patch`] run.
+///
+/// The default uses the TrinityCore keys, the Arctium CDN URLs, and applies
+/// the patches without dry run or code signature removal, printing in [`ReportMode::Normal`].
+pub struct PatchOptions<'a> {
+    pub key_config: KeyConfig,
+    /// Custom version URL, `None` for one generated from `version_url_config`
+    pub version_url: Option<String>,
+    /// Version service that generated version URLs point at
+    pub version_url_config: VersionUrlConfig<'a>,
+    /// Custom CDNs URL, `None` for the Arctium CDN
+    pub cdns_url: Option<String>,
+    /// Custom unified API URL for the v3 pattern, `None` to fall back to `version_url`
+    pub cdns_url_v3: Option<String>,
+    /// Port inserted into the host of every CDN URL, `None` to keep the URLs as given
+    pub cdn_port: Option<u16>,
+    pub dry_run: bool,
+    /// Remove the code signature from the output (macOS only)
+    pub strip_codesign: bool,
+    /// How `codesign` treats the output when `strip_codesign` is set
+    pub codesign_options: CodesignOptions,
+    /// Unix permissions of the output file, ignored on other platforms
+    pub output_mode: u32,
+    /// What is printed while patching
+    pub report_mode: ReportMode,
+    pub max_file_size: u64,
+    pub min_file_size: u64,
+    /// Abort unless the client reports exactly this version
+    pub expected_version: Option<Version>,
+    /// Abort unless the client reports exactly this build number
+    pub expected_build: Option<u16>,
+    /// Patch the ConnectTo and Signature RSA slots independently
+    pub dual_rsa: bool,
+    /// Bytes written over the portal host suffix (at most 18, zero padded), `None` for all zeros
+    pub portal_replacement: Option<Vec<u8>>,
+    /// Limit on the replacements of each pattern, `None` for the per-pattern defaults
+    pub max_patch_count: Option<usize>,
+    /// Patch every occurrence of each pattern instead of only the first
+    pub patch_all_occurrences: bool,
+    /// Slices of a universal Mach-O binary to patch, ignored for other binaries
+    pub architecture: Arch,
+    /// Raw byte patches run after the built-in steps, in order
+    pub custom_patches: Vec<CustomBytePatch>,
+    /// Called after each patch step and once on completion
+    pub on_progress: Option<Box<dyn FnMut(PatchProgress) + 'a>>,
+}
+
+impl Default for PatchOptions<'_> {
+    fn default() -> Self {
+        Self {
+            key_config: KeyConfig::trinity_core(),
+            version_url: None,
+            version_url_config: ARCTIUM_CDN,
+            cdns_url: None,
+            cdns_url_v3: None,
+            cdn_port: None,
+            dry_run: false,
+            strip_codesign: false,
+            codesign_options: CodesignOptions::default(),
+            output_mode: DEFAULT_OUTPUT_MODE,
+            report_mode: ReportMode::Normal,
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            min_file_size: DEFAULT_MIN_FILE_SIZE,
+            expected_version: None,
+            expected_build: None,
+            dual_rsa: false,
+            portal_replacement: None,
+            max_patch_count: None,
+            patch_all_occurrences: false,
+            architecture: Arch::All,
+            custom_patches: Vec::new(),
+            on_progress: None,
+        }
+    }
+}
+
+impl fmt::Debug for PatchOptions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PatchOptions")
+            .field("key_config", &self.key_config)
+            .field("version_url", &self.version_url)
+            .field("version_url_config", &self.version_url_config)
+            .field("cdns_url", &self.cdns_url)
+            .field("cdns_url_v3", &self.cdns_url_v3)
+            .field("cdn_port", &self.cdn_port)
+            .field("dry_run", &self.dry_run)
+            .field("strip_codesign", &self.strip_codesign)
+            .field("codesign_options", &self.codesign_options)
+            .field("output_mode", &format_args!("{:o}", self.output_mode))
+            .field("report_mode", &self.report_mode)
+            .field("max_file_size", &self.max_file_size)
+            .field("min_file_size", &self.min_file_size)
+            .field("expected_version", &self.expected_version)
+            .field("expected_build", &self.expected_build)
+            .field("dual_rsa", &self.dual_rsa)
+            .field("portal_replacement", &self.portal_replacement)
+            .field("max_patch_count", &self.max_patch_count)
+            .field("patch_all_occurrences", &self.patch_all_occurrences)
+            .field("architecture", &self.architecture)
+            .field("custom_patches", &self.custom_patches)
+            .field("on_progress", &self.on_progress.is_some())
+            .finish()
+    }
+}
+
+/// Patch the executable at `input_path` and write the result to `output_path`.
+///
+/// Returns a report with one record per patch step. In dry run mode nothing
+/// is written and the patches found are reported as [`PatchStatus::Found`],
+/// with the bytes that would be written; a required patch that cannot be
+/// applied fails the dry run as well. Output is printed according to
+/// `opts.report_mode`.
 pub fn execute_patch(
     input_path: &Path,
     output_path: &Path,
-    key_config: KeyConfig,
-    version_url: Option<&str>,
-    cdns_url: Option<&str>,
-    dry_run: bool,
-    strip_codesign: bool,
-    verbose: bool,
-) -> Result<(), WowPatcherError> {
-    // Validate input file
-    if !input_path.exists() {
+    opts: PatchOptions,
+) -> Result<PatchReport, WowPatcherError> {
+    match read_executable(input_path, opts.max_file_size, opts.min_file_size) {
+        Ok(data) => execute_patch_bytes(input_path, output_path, data, opts),
+        Err(e) => {
+            ConsoleReporter::new(opts.report_mode).error(&e);
+            Err(e)
+        }
+    }
+}
+
+/// [`execute_patch`] for an executable that has already been read into `data`.
+///
+/// `input_path` only names the executable, for client type detection and
+/// the dry run preview; nothing is read from it.
+pub(crate) fn execute_patch_bytes(
+    input_path: &Path,
+    output_path: &Path,
+    data: Vec<u8>,
+    opts: PatchOptions,
+) -> Result<PatchReport, WowPatcherError> {
+    let mut reporter = ConsoleReporter::new(opts.report_mode);
+    let result = execute_patch_with(input_path, output_path, data, opts, &mut reporter);
+    if let Err(e) = &result {
+        reporter.error(e);
+    }
+    result
+}
+
+/// [`execute_patch_bytes`] sending all output to `reporter`.
+pub(crate) fn execute_patch_with(
+    input_path: &Path,
+    output_path: &Path,
+    mut data: Vec<u8>,
+    mut opts: PatchOptions,
+    reporter: &mut dyn Reporter,
+) -> Result<PatchReport, WowPatcherError> {
+    if opts.dry_run {
+        let (header, report) = preview_patches(input_path, output_path, &data, &opts, reporter)?;
+        reporter.status(header.trim_end());
+        reporter.finish(&report);
+        return Ok(report);
+    }
+
+    let mut report = patch_data(input_path, &mut data, &mut opts, reporter)?;
+
+    write_patched_output(
+        output_path,
+        &data,
+        opts.strip_codesign.then_some(opts.codesign_options),
+        opts.output_mode,
+        reporter,
+    )?;
+
+    reporter.status(&format!(
+        "✅ Successfully applied {} patches and saved to {:?}",
+        report.applied_count(),
+        output_path
+    ));
+    reporter.status("");
+    reporter.status("The patched client can now connect to TrinityCore private servers.");
+
+    if let Some(on_progress) = opts.on_progress.as_mut() {
+        on_progress(PatchProgress::complete(
+            PATCH_STEP_NAMES.len() + opts.custom_patches.len(),
+        ));
+    }
+
+    report.platform.macos_version = host_macos_version();
+    reporter.finish(&report);
+    Ok(report)
+}
+
+/// Read the executable at `path`, checking its size before reading it.
+pub(crate) fn read_executable(
+    path: &Path,
+    max_file_size: u64,
+    min_file_size: u64,
+) -> Result<Vec<u8>, WowPatcherError> {
+    if !path.exists() {
         return Err(WowPatcherError::new(
             ErrorCategory::FileOperationError,
             "WoW executable file not found at specified location",
         ));
     }
 
-    let metadata = fs::metadata(input_path).map_err(|e| {
+    let metadata = fs::metadata(path).map_err(|e| {
         WowPatcherError::wrap(
             ErrorCategory::FileOperationError,
             "Unable to access WoW executable file",
@@ -40,617 +245,987 @@ pub fn execute_patch(
         )
     })?;
 
-    // Validate file size
-    const MAX_FILE_SIZE: u64 = 1024 * 1024 * 1024; // 1GB
-    if metadata.len() > MAX_FILE_SIZE {
+    validate_file_size(metadata.len(), max_file_size, min_file_size)?;
+
+    fs::read(path).map_err(|e| {
+        WowPatcherError::wrap(
+            ErrorCategory::FileOperationError,
+            "Failed to read WoW executable file",
+            e,
+        )
+    })
+}
+
+/// Client details resolved from an executable before it is patched.
+struct Inspection {
+    client_type: ClientType,
+    version: Option<Version>,
+    key_config: KeyConfig,
+    portal_replacement: Vec<u8>,
+}
+
+/// Validate an executable and resolve its client type, version and keys.
+///
+/// Fails if the size, the expected version, a configured URL or the section
+/// of a pattern is rejected. Keys that collide with a pattern only warn.
+fn inspect_data(
+    input_path: &Path,
+    data: &[u8],
+    opts: &PatchOptions,
+    reporter: &mut dyn Reporter,
+) -> Result<Inspection, WowPatcherError> {
+    validate_file_size(data.len() as u64, opts.max_file_size, opts.min_file_size)?;
+    let portal_replacement = portal_replacement_bytes(opts.portal_replacement.as_deref())?;
+    if let Some(url) = &opts.cdns_url_v3 {
+        validate_url_v3(url)?;
+    }
+
+    // Detect client type, falling back to the binary for custom install locations
+    let mut client_type = detect_client_type_from_path(&input_path.to_string_lossy());
+    if client_type == ClientType::Unknown {
+        client_type = detect_client_type_from_binary(data);
+    }
+    let key_config = key_config_for_client(opts.key_config.clone(), client_type);
+
+    #[cfg(target_os = "linux")]
+    if reporter.is_verbose()
+        && let Some(windows_path) = input_path
+            .to_str()
+            .and_then(crate::platform::linux::translate_wsl_path)
+    {
+        reporter.detail(&format!("WSL path maps to Windows path: {}", windows_path));
+    }
+
+    // Older WINE releases are known to break when running patched PE executables
+    #[cfg(target_os = "linux")]
+    if let Some(prefix) = crate::platform::linux::find_wine_prefix(input_path)
+        && let Some(wine_version) = crate::platform::linux::check_wine_version(&prefix)
+        && crate::platform::linux::parse_wine_major_version(&wine_version)
+            .is_some_and(|major| major < 7)
+    {
+        reporter.warning(&format!(
+            "⚠️  Detected {} in prefix {:?}. WINE versions before 7.0 have known issues with patched clients, consider upgrading.",
+            wine_version, prefix
+        ));
+    }
+
+    // Extract version information
+    let version = Version::from_binary(data);
+
+    if let Some(ref v) = version {
+        if reporter.is_verbose() {
+            match crate::version::changelog_for_build(v.build) {
+                Some(note) => {
+                    reporter.detail(&format!("Detected client version: {} ({})", v, note))
+                }
+                None => reporter.detail(&format!("Detected client version: {}", v)),
+            }
+        }
+    } else if reporter.is_verbose() {
+        reporter.detail("Unable to extract version from executable, using fallback URL");
+    }
+
+    check_expected_version(version.as_ref(), opts.expected_version, opts.expected_build)?;
+
+    if let Err(e) = key_config.validate_against_binary(data) {
+        reporter.warning(&format!("⚠️  {}", e.message));
+    }
+
+    validate_pattern_sections(
+        &ParsedBinary::new(data),
+        key_config.patches_ed25519(),
+        reporter,
+    )?;
+
+    Ok(Inspection {
+        client_type,
+        version,
+        key_config,
+        portal_replacement,
+    })
+}
+
+/// Context patching `data` as resolved by [`inspect_data`], with the URLs and modes of `opts`.
+fn patch_context<'a>(
+    data: Vec<u8>,
+    inspection: Inspection,
+    opts: &PatchOptions<'a>,
+    reporter: &'a mut dyn Reporter,
+) -> PatchContext<'a> {
+    let mut ctx = PatchContext::new(
+        data,
+        inspection.client_type,
+        inspection.version,
+        inspection.key_config,
+        reporter,
+    );
+    ctx.dual_rsa |= opts.dual_rsa;
+    ctx.portal_replacement = inspection.portal_replacement;
+    ctx.version_url = opts.version_url.clone();
+    ctx.version_url_config = opts.version_url_config;
+    ctx.cdns_url = opts.cdns_url.clone();
+    ctx.cdns_url_v3 = opts.cdns_url_v3.clone();
+    ctx.cdn_port = opts.cdn_port;
+    ctx.patch_all_occurrences = opts.patch_all_occurrences;
+    ctx.architecture = opts.architecture;
+    ctx
+}
+
+/// Validate an executable read into `data` and apply every patch to it in place.
+///
+/// This is the part of [`execute_patch`] between reading the input and
+/// writing the output, shared by every way of patching; `opts.dry_run` and
+/// the output options are ignored. `on_progress` is called after each step
+/// but not on completion. If patching fails after some steps were applied,
+/// `data` is left partially patched.
+pub(crate) fn patch_data(
+    input_path: &Path,
+    data: &mut Vec<u8>,
+    opts: &mut PatchOptions,
+    reporter: &mut dyn Reporter,
+) -> Result<PatchReport, WowPatcherError> {
+    let inspection = inspect_data(input_path, data, opts, reporter)?;
+    reporter.detail("Applying patches...");
+
+    let mut on_progress = opts.on_progress.take();
+    let mut no_progress = |_: PatchProgress| {};
+    // The context owns the buffer while patching, so it is moved in and
+    // handed back whether or not the patches succeed
+    let mut ctx = patch_context(std::mem::take(data), inspection, opts, reporter);
+    let result = apply_patches(
+        &mut ctx,
+        opts.max_patch_count,
+        &opts.custom_patches,
+        match on_progress.as_mut() {
+            Some(callback) => callback.as_mut(),
+            None => &mut no_progress,
+        },
+    );
+    let PatchContext {
+        data: patched,
+        report,
+        ..
+    } = ctx;
+    *data = patched;
+    opts.on_progress = on_progress;
+    result?;
+    Ok(report)
+}
+
+/// Validate an executable and report the patches a run would apply to it.
+///
+/// Returns the dry run header, describing the input and the output, and a
+/// report of the patches found, as [`PatchStatus::Found`]. Dry run mode
+/// prints the header followed by the report. A required patch that cannot be
+/// applied fails like it does in a real run. `data` is left untouched.
+pub(crate) fn preview_patches(
+    input_path: &Path,
+    output_path: &Path,
+    data: &[u8],
+    opts: &PatchOptions,
+    reporter: &mut dyn Reporter,
+) -> Result<(String, PatchReport), WowPatcherError> {
+    let inspection = inspect_data(input_path, data, opts, reporter)?;
+    let header = dry_run_header(
+        input_path,
+        output_path,
+        &ParsedBinary::new(data),
+        inspection.client_type,
+        opts.strip_codesign.then_some(&opts.codesign_options),
+    );
+
+    // Patch a copy silently to find where each patch would go
+    let mut silent = ConsoleReporter::new(ReportMode::Silent);
+    let mut ctx = patch_context(data.to_vec(), inspection, opts, &mut silent);
+    apply_patches(
+        &mut ctx,
+        opts.max_patch_count,
+        &opts.custom_patches,
+        &mut |_| {},
+    )?;
+    let mut report = ctx.report;
+    for record in &mut report.patches_applied {
+        if record.status == PatchStatus::Applied {
+            record.status = PatchStatus::Found;
+        }
+    }
+    Ok((header, report))
+}
+
+/// Describe the input and output of a dry run, printed ahead of its [`PatchReport`].
+///
+/// `codesign` is `None` when the code signature is left untouched.
+fn dry_run_header(
+    input_path: &Path,
+    output_path: &Path,
+    binary: &ParsedBinary,
+    client_type: ClientType,
+    codesign: Option<&CodesignOptions>,
+) -> String {
+    let mut header = String::new();
+    // Writing to a String cannot fail
+    macro_rules! out {
+        ($($arg:tt)*) => {{
+            let _ = writeln!(header, $($arg)*);
+        }};
+    }
+
+    out!("🔍 Dry Run Mode - No files will be modified");
+    out!();
+    out!("Input file:  {:?}", input_path);
+    out!("Output file: {:?}", output_path);
+    out!(
+        "File size:   {:.2} MB",
+        binary.data.len() as f64 / (1024.0 * 1024.0)
+    );
+    out!("Format:      {}", binary.format);
+    out!("Client type: {}", client_type);
+    if let Some(opts) = codesign
+        && cfg!(target_os = "macos")
+    {
+        match &opts.action {
+            CodesignAction::RemoveSignature => out!("Codesign:    Remove macOS code signing"),
+            CodesignAction::AdhocSign => out!("Codesign:    Sign with an ad-hoc identity"),
+            CodesignAction::Sign(identity) => {
+                out!("Codesign:    Re-sign with identity '{}'", identity)
+            }
+        }
+    }
+    out!();
+    out!("No changes were made. Remove --dry-run to apply patches.");
+    out!();
+    out!("Patches that would be applied:");
+    header
+}
+
+/// Distinguishes temporary output files created by the same process
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A temporary file next to an output path, removed when dropped unless committed.
+///
+/// The output is written to the temporary file first and only renamed over
+/// the final path once it is complete, so an error or a killed process never
+/// leaves a half-written executable behind. The temporary file is in the same
+/// directory as the output, which keeps the rename on one volume: it is
+/// atomic on Unix, and on Windows replaces an existing output in one step.
+#[derive(Debug)]
+pub(crate) struct TempFileGuard {
+    path: PathBuf,
+    target: PathBuf,
+    committed: bool,
+}
+
+impl TempFileGuard {
+    /// Reserve a temporary path for writing `target`.
+    ///
+    /// No file is created until something is written to [`path`](Self::path).
+    pub fn new(target: &Path) -> Self {
+        let name = target
+            .file_name()
+            .map_or_else(|| "output".into(), |name| name.to_string_lossy());
+        let path = target.with_file_name(format!(
+            ".{}.{}-{}.tmp",
+            name,
+            std::process::id(),
+            TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        Self {
+            path,
+            target: target.to_path_buf(),
+            committed: false,
+        }
+    }
+
+    /// Path of the temporary file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Rename the temporary file over the target path.
+    ///
+    /// # Errors
+    ///
+    /// Returns a file operation error if the rename fails, in which case the
+    /// temporary file is removed and the target is left as it was.
+    pub fn commit(mut self) -> Result<(), WowPatcherError> {
+        fs::rename(&self.path, &self.target).map_err(|e| {
+            WowPatcherError::wrap(
+                ErrorCategory::FileOperationError,
+                "Failed to move patched executable into place",
+                e,
+            )
+            .with_context("path", self.target.display().to_string())
+        })?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for TempFileGuard {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Write a patched executable, set its permissions on Unix and run `codesign` on macOS.
+///
+/// The file is prepared under a [`TempFileGuard`] and renamed into place
+/// last, so on any error no output is left behind. `codesign` is `None` to
+/// leave the code signature untouched; its warnings go to `reporter`.
+pub(crate) fn write_patched_output(
+    output_path: &Path,
+    data: &[u8],
+    codesign: Option<CodesignOptions>,
+    output_mode: u32,
+    reporter: &mut dyn Reporter,
+) -> Result<(), WowPatcherError> {
+    // Create output directory if needed
+    if let Some(parent) = output_path.parent() {
+        // Only check if parent exists if it's not empty or current directory
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            return Err(WowPatcherError::new(
+                ErrorCategory::FileOperationError,
+                format!("Output directory does not exist: {:?}", parent),
+            ));
+        }
+    }
+
+    // Write patched file next to the output, moved into place once complete
+    let temp = TempFileGuard::new(output_path);
+    fs::write(temp.path(), data).map_err(|e| {
+        WowPatcherError::wrap(
+            ErrorCategory::FileOperationError,
+            "Failed to write patched executable",
+            e,
+        )
+    })?;
+
+    // Set the requested permissions on Unix
+    #[cfg(not(unix))]
+    let _ = output_mode;
+    // Windows has no mode bits, but the output may inherit an ACL that locks out its writer
+    #[cfg(target_os = "windows")]
+    crate::platform::windows::ensure_readable_executable(temp.path())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(temp.path())
+            .map_err(|e| {
+                WowPatcherError::wrap(
+                    ErrorCategory::FileOperationError,
+                    "Failed to get file metadata",
+                    e,
+                )
+            })?
+            .permissions();
+        perms.set_mode(output_mode);
+        fs::set_permissions(temp.path(), perms).map_err(|e| {
+            WowPatcherError::wrap(
+                ErrorCategory::FileOperationError,
+                "Failed to set file permissions",
+                e,
+            )
+        })?;
+    }
+
+    // Remove code signing on macOS, signing under the output name rather than the temporary one
+    if let Some(opts) = codesign
+        && cfg!(target_os = "macos")
+    {
+        let identifier = codesign_identifier(output_path)?;
+        remove_codesigning_signature(temp.path(), &identifier, opts, reporter).map_err(|e| {
+            WowPatcherError::wrap(
+                ErrorCategory::PlatformError,
+                "Failed to remove code signing",
+                e,
+            )
+        })?;
+    }
+    temp.commit()
+}
+
+/// Ensure the input size lies within the configured bounds.
+pub(crate) fn validate_file_size(
+    len: u64,
+    max_file_size: u64,
+    min_file_size: u64,
+) -> Result<(), WowPatcherError> {
+    if len > max_file_size {
         return Err(WowPatcherError::new(
             ErrorCategory::ValidationError,
             format!(
                 "File size {:.2} MB exceeds maximum allowed size of {:.0} MB",
-                metadata.len() as f64 / (1024.0 * 1024.0),
-                MAX_FILE_SIZE as f64 / (1024.0 * 1024.0)
+                len as f64 / (1024.0 * 1024.0),
+                max_file_size as f64 / (1024.0 * 1024.0)
             ),
         ));
     }
 
-    if metadata.len() == 0 {
+    if len == 0 {
         return Err(WowPatcherError::new(
             ErrorCategory::ValidationError,
             "File is empty - not a valid WoW executable",
         ));
     }
 
-    if metadata.len() < 1024 {
+    if len < min_file_size {
         return Err(WowPatcherError::new(
             ErrorCategory::ValidationError,
-            format!(
-                "File too small ({} bytes) to be a valid executable",
-                metadata.len()
-            ),
+            format!("File too small ({} bytes) to be a valid executable", len),
         ));
     }
 
-    // Detect client type
-    let client_type = detect_client_type(input_path.to_str().unwrap_or(""));
-
-    // Extract version information
-    let version = extract_version(input_path).or_else(|| extract_version_fallback(input_path));
+    Ok(())
+}
 
-    if let Some(ref v) = version {
-        if verbose {
-            println!("Detected client version: {}", v);
-        }
-    } else if verbose {
-        println!("Unable to extract version from executable, using fallback URL");
+/// Skip the Ed25519 patch for clients that do not embed an Ed25519 key.
+pub(crate) fn key_config_for_client(key_config: KeyConfig, client_type: ClientType) -> KeyConfig {
+    if client_type.uses_ed25519() {
+        key_config
+    } else {
+        key_config.with_only_rsa()
     }
+}
 
-    // Read the file
-    let mut data = fs::read(input_path).map_err(|e| {
-        WowPatcherError::wrap(
-            ErrorCategory::FileOperationError,
-            "Failed to read WoW executable file",
-            e,
-        )
-    })?;
-
+/// Locate every known pattern and ensure each lies in a patchable section.
+///
+/// The Ed25519 key is only checked when `patch_ed25519` is set.
+/// Returns the offsets that were found, labelled with the pattern name.
+pub(crate) fn validate_pattern_sections(
+    binary: &ParsedBinary,
+    patch_ed25519: bool,
+    reporter: &mut dyn Reporter,
+) -> Result<Vec<(usize, &'static str)>, WowPatcherError> {
     // Validate that all patterns are in patchable sections before proceeding
     let mut offsets_to_validate = Vec::new();
 
     // Check portal pattern
-    if let Some(offset) = data.find_pattern(portal_pattern()) {
+    if let Some(offset) = binary.data.find_pattern(portal_pattern()) {
         offsets_to_validate.push((offset, "Portal (.actual.battle.net)"));
     }
 
     // Check RSA modulus patterns (multiple patterns for different client versions)
-    if let Some(offset) = data.find_pattern(connect_to_modulus_pattern()) {
+    if let Some(offset) = binary.data.find_pattern(connect_to_modulus_pattern()) {
         offsets_to_validate.push((offset, "RSA Modulus (ConnectTo)"));
     }
-    if let Some(offset) = data.find_pattern(signature_modulus_pattern()) {
+    if let Some(offset) = binary.data.find_pattern(signature_modulus_pattern()) {
         offsets_to_validate.push((offset, "RSA Modulus (Signature)"));
     }
-    if let Some(offset) = data.find_pattern(crypto_rsa_modulus_pattern()) {
+    if let Some(offset) = binary.data.find_pattern(crypto_rsa_modulus_pattern()) {
         offsets_to_validate.push((offset, "RSA Modulus (Crypto)"));
     }
 
-    // Check Ed25519 pattern (only for clients that use it)
-    if client_type.uses_ed25519()
-        && let Some(offset) = data.find_pattern(crypto_ed_public_key_pattern())
+    // Check Ed25519 pattern (only when it will be patched)
+    if patch_ed25519 && let Some(offset) = binary.data.find_pattern(crypto_ed_public_key_pattern())
     {
         offsets_to_validate.push((offset, "Ed25519 Public Key"));
     }
 
     // Check version URL patterns (v1, v2, and v3)
-    if let Some(offset) = data.find_pattern(version_url_pattern()) {
+    if let Some(offset) = binary.data.find_pattern(version_url_pattern()) {
         offsets_to_validate.push((offset, "Version URL"));
     }
-    if let Some(offset) = data.find_pattern(version_url_v2_pattern()) {
+    if let Some(offset) = binary.data.find_pattern(version_url_v2_pattern()) {
         offsets_to_validate.push((offset, "Version URL v2"));
     }
-    if let Some(offset) = data.find_pattern(version_url_v3_pattern()) {
+    if let Some(offset) = binary.data.find_pattern(version_url_v3_pattern()) {
         offsets_to_validate.push((offset, "Version URL v3"));
     }
 
     // Check CDNs URL pattern
-    if let Some(offset) = data.find_pattern(cdns_url_pattern()) {
+    if let Some(offset) = binary.data.find_pattern(cdns_url_pattern()) {
         offsets_to_validate.push((offset, "CDNs URL"));
     }
 
     // Validate all found patterns are in patchable sections
-    if let Err(validation_error) = validate_patch_offsets(&data, &offsets_to_validate) {
-        if verbose {
-            println!("⚠️  Section validation warnings:");
+    if let Err(validation_error) = validate_offsets_in(binary, &offsets_to_validate) {
+        if reporter.is_verbose() {
+            reporter.detail("⚠️  Section validation warnings:");
             for line in validation_error.lines() {
-                println!("  {}", line);
+                reporter.detail(&format!("  {}", line));
             }
-            println!();
-            println!("Binary file patching only works reliably in data sections (.rdata, .data).");
-            println!("Code sections (.text) are protected and changes will be lost at runtime.");
-            println!("Consider using Arctium's in-memory patcher for these patterns.");
-            println!();
+            reporter.detail("");
+            reporter.detail(
+                "Binary file patching only works reliably in data sections (.rdata, .data).",
+            );
+            reporter
+                .detail("Code sections (.text) are protected and changes will be lost at runtime.");
+            reporter.detail("Consider using Arctium's in-memory patcher for these patterns.");
+            reporter.detail("");
         }
-        return Err(WowPatcherError::new(
+        let mut error = WowPatcherError::new(
             ErrorCategory::ValidationError,
             format!("Pattern validation failed:\n{}", validation_error),
-        ));
-    }
-
-    if dry_run {
-        println!("🔍 Dry Run Mode - No files will be modified");
-        println!();
-        println!("Input file:  {:?}", input_path);
-        println!("Output file: {:?}", output_path);
-        println!(
-            "File size:   {:.2} MB",
-            metadata.len() as f64 / (1024.0 * 1024.0)
         );
-        println!("Client type: {}", client_type);
-        println!();
-        println!("Section Validation:");
-        for (offset, pattern_name) in &offsets_to_validate {
-            if let Some(section) = check_offset_section(&data, *offset) {
-                if section.is_patchable {
-                    println!(
-                        "  ✓ {} at 0x{:x} in '{}' (patchable)",
-                        pattern_name, offset, section.name
-                    );
-                } else {
-                    println!(
-                        "  ⚠ {} at 0x{:x} in '{}' (NOT patchable - code section)",
-                        pattern_name, offset, section.name
-                    );
-                }
-            }
+        if let Some((offset, _)) = offsets_to_validate.iter().find(|(offset, _)| {
+            !binary
+                .section_at(*offset)
+                .is_some_and(|section| section.is_patchable)
+        }) {
+            error = error.with_offset(*offset);
         }
-        println!();
-        println!("Patches that would be applied:");
+        return Err(error);
+    }
 
-        // Check each pattern
-        let mut temp_data = data.clone();
+    Ok(offsets_to_validate)
+}
 
-        if patch(&mut temp_data, portal_pattern(), &portal_pattern().empty()).is_ok() {
-            println!("  ✓ Portal pattern (.actual.battle.net → empty)");
-        } else {
-            println!("  ✗ Portal pattern not found");
+/// Build the bytes written over the portal pattern, zero padded to the pattern length.
+pub(crate) fn portal_replacement_bytes(custom: Option<&[u8]>) -> Result<Vec<u8>, WowPatcherError> {
+    let mut replacement = PORTAL_REPLACEMENT.to_vec();
+    if let Some(custom) = custom {
+        if custom.len() > replacement.len() {
+            return Err(WowPatcherError::new(
+                ErrorCategory::ValidationError,
+                format!(
+                    "Portal replacement must be at most {} bytes, got {}",
+                    replacement.len(),
+                    custom.len()
+                ),
+            ));
         }
+        replacement[..custom.len()].copy_from_slice(custom);
+    }
+    Ok(replacement)
+}
 
-        temp_data = data.clone();
-        let mut rsa_found = false;
-        let mut rsa_pattern = "";
-
-        if patch(
-            &mut temp_data,
-            connect_to_modulus_pattern(),
-            key_config.rsa_modulus(),
-        )
-        .is_ok()
-        {
-            rsa_found = true;
-            rsa_pattern = "ConnectTo";
-        } else if patch(
-            &mut temp_data,
-            signature_modulus_pattern(),
-            key_config.rsa_modulus(),
-        )
-        .is_ok()
-        {
-            rsa_found = true;
-            rsa_pattern = "Signature";
-        } else if patch(
-            &mut temp_data,
-            crypto_rsa_modulus_pattern(),
-            key_config.rsa_modulus(),
+/// Check that a v3 URL has at most the region and product `%s` placeholders.
+pub(crate) fn validate_url_v3(url: &str) -> Result<(), WowPatcherError> {
+    let placeholders = url.matches("%s").count();
+    if placeholders > 2 {
+        return Err(WowPatcherError::new(
+            ErrorCategory::ValidationError,
+            format!(
+                "v3 URL may contain at most 2 %s placeholders (region and product), got {}",
+                placeholders
+            ),
         )
-        .is_ok()
-        {
-            rsa_found = true;
-            rsa_pattern = "Crypto";
-        }
+        .with_context("url", url.to_string())
+        .with_suggestion("Use a static URL or one with `%s` for the region and product only"));
+    }
+    Ok(())
+}
 
-        if rsa_found {
-            if key_config.is_trinity_core() {
-                println!(
-                    "  ✓ RSA modulus → TrinityCore RSA key (256 bytes, {} pattern)",
-                    rsa_pattern
-                );
-            } else {
-                println!(
-                    "  ✓ RSA modulus → Custom RSA key (256 bytes, {} pattern)",
-                    rsa_pattern
-                );
-            }
-        } else {
-            println!("  ✗ RSA modulus pattern not found (tried ConnectTo, Signature, Crypto)");
-        }
+/// Hint attached to errors for builds whose required patterns are missing.
+pub(crate) const UNSUPPORTED_VERSION_HINT: &str =
+    "Run `wow-patcher test-binary <file>` to see which patterns this build is missing";
 
-        temp_data = data.clone();
-        if client_type.uses_ed25519() {
-            if patch(
-                &mut temp_data,
-                crypto_ed_public_key_pattern(),
-                key_config.ed25519_public_key(),
-            )
-            .is_ok()
-            {
-                if key_config.is_trinity_core() {
-                    println!("  ✓ Ed25519 public key → TrinityCore Ed25519 key (32 bytes)");
-                } else {
-                    println!("  ✓ Ed25519 public key → Custom Ed25519 key (32 bytes)");
-                }
-            } else {
-                println!("  ✗ Ed25519 public key pattern not found");
-            }
-        } else {
-            println!("  ⚠ Ed25519 public key not used by {} clients", client_type);
-        }
+/// Names of the steps in the default [`PatchPipeline`], in order
+pub const PATCH_STEP_NAMES: &[&str] = &[
+    "Portal",
+    "RSA modulus",
+    "Ed25519 public key",
+    "Version URL",
+    "CDNs URL",
+];
 
-        temp_data = data.clone();
-        let build_num = version.as_ref().map(|v| v.build as u32);
-        let mut version_url_found = false;
-        let mut version_url_pattern_name = "";
+/// Apply all patches to an in-memory executable image by running the default [`PatchPipeline`].
+///
+/// `custom_patches` run after the built-in steps. `on_progress` is called once
+/// after each entry in [`PATCH_STEP_NAMES`] and each custom patch. The
+/// patched image and a report with one record per patch attempted are left
+/// in `ctx`. Afterwards the replacements of each step are checked against
+/// the limits described in [`check_patch_counts`]. Universal Mach-O binaries are
+/// patched slice by slice, see [`apply_patches_to_slices`].
+pub(crate) fn apply_patches(
+    ctx: &mut PatchContext<'_>,
+    max_patch_count: Option<usize>,
+    custom_patches: &[CustomBytePatch],
+    on_progress: &mut dyn FnMut(PatchProgress),
+) -> Result<(), WowPatcherError> {
+    if detect_format(&ctx.data) == BinaryFormat::MachOFat {
+        return apply_patches_to_slices(ctx, max_patch_count, custom_patches, on_progress);
+    }
+    let first_record = ctx.report.patches_applied.len();
+    let mut pipeline = PatchPipeline::default();
+    for custom_patch in custom_patches {
+        pipeline.push(Box::new(custom_patch.clone()));
+    }
+    pipeline.run(ctx, on_progress)?;
+    check_context_patch_counts(ctx, first_record, max_patch_count)
+}
 
-        // Try v1 pattern first
-        let version_url_replacement = create_url_replacement(
-            version_url.unwrap_or(&get_version_url(build_num, None, None)),
-            version_url_pattern().len(),
-        );
-        if patch(
-            &mut temp_data,
-            version_url_pattern(),
-            &version_url_replacement,
-        )
-        .is_ok()
-        {
-            version_url_found = true;
-            version_url_pattern_name = "v1";
-        } else {
-            // Try v2 pattern
-            temp_data = data.clone();
-            let version_url_v2_replacement = create_url_replacement(
-                version_url.unwrap_or(&get_version_url(build_num, None, None)),
-                version_url_v2_pattern().len(),
-            );
-            if patch(
-                &mut temp_data,
-                version_url_v2_pattern(),
-                &version_url_v2_replacement,
-            )
-            .is_ok()
-            {
-                version_url_found = true;
-                version_url_pattern_name = "v2";
-            } else {
-                // Try v3 pattern (WoW Classic 1.15.8+ unified API)
-                temp_data = data.clone();
-                let version_url_v3_replacement = create_url_replacement(
-                    version_url.unwrap_or(&get_unified_api_url(build_num)),
-                    version_url_v3_pattern().len(),
-                );
-                if patch(
-                    &mut temp_data,
-                    version_url_v3_pattern(),
-                    &version_url_v3_replacement,
-                )
-                .is_ok()
-                {
-                    version_url_found = true;
-                    version_url_pattern_name = "v3 (unified API)";
-                }
-            }
-        }
+/// Run the built-in steps on each slice of a universal binary selected by `ctx.architecture`.
+///
+/// Every slice is patched as the thin Mach-O it is, so pattern counts and
+/// sections come from that slice's own header. Records are tagged with the
+/// slice architecture, e.g. `Portal [arm64]`, and their offsets point into
+/// the whole file. Custom patches use file offsets and run once afterwards.
+fn apply_patches_to_slices(
+    ctx: &mut PatchContext<'_>,
+    max_patch_count: Option<usize>,
+    custom_patches: &[CustomBytePatch],
+    on_progress: &mut dyn FnMut(PatchProgress),
+) -> Result<(), WowPatcherError> {
+    let slices = select_slices(&ctx.data, ctx.architecture)?;
+    let total_steps = PATCH_STEP_NAMES.len() + custom_patches.len();
 
-        if version_url_found {
-            if let Some(custom_url) = version_url {
-                println!(
-                    "  ✓ Version URL → Custom CDN ({}, {} pattern)",
-                    custom_url, version_url_pattern_name
-                );
-            } else if version_url_pattern_name.contains("v3") {
-                // v3 unified API handles both versions and cdns
-                if let Some(build_num) = build_num {
-                    println!(
-                        "  ✓ API URL → Arctium CDN (http://ngdp.arctium.io/%s/%s/{}/{{endpoint}}, {} pattern)",
-                        build_num, version_url_pattern_name
-                    );
-                } else {
-                    println!(
-                        "  ✓ API URL → Arctium CDN (http://ngdp.arctium.io/%s/%s/{{endpoint}}, {} pattern)",
-                        version_url_pattern_name
-                    );
+    let mut fat = std::mem::take(&mut ctx.data);
+    let mut result = Ok(());
+    for (index, slice) in slices.iter().enumerate() {
+        ctx.data = fat[slice.range.clone()].to_vec();
+        ctx.unified_api_patched = false;
+        let first_record = ctx.report.patches_applied.len();
+        // Progress is only reported for the last slice, so it counts up once
+        let is_last = index + 1 == slices.len();
+        result = PatchPipeline::default()
+            .run(ctx, &mut |progress| {
+                if is_last {
+                    on_progress(PatchProgress {
+                        total_steps,
+                        ..progress
+                    });
                 }
-            } else if let Some(build_num) = build_num {
-                println!(
-                    "  ✓ Version URL → Arctium CDN (http://ngdp.arctium.io/%s/%s/{}/versions, {} pattern)",
-                    build_num, version_url_pattern_name
-                );
-            } else {
-                println!(
-                    "  ✓ Version URL → Arctium CDN (http://ngdp.arctium.io/%s/%s/latest/versions, {} pattern)",
-                    version_url_pattern_name
-                );
+            })
+            .and_then(|()| check_context_patch_counts(ctx, first_record, max_patch_count));
+        for record in &mut ctx.report.patches_applied[first_record..] {
+            record.step = format!("{} [{}]", record.step, slice.name);
+            if let Some(offset) = &mut record.offset {
+                *offset += slice.range.start;
             }
-        } else {
-            println!("  ✗ Version URL pattern not found (tried v1, v2, and v3)");
         }
-
-        temp_data = data.clone();
-        let cdns_url_replacement = create_url_replacement(
-            cdns_url.unwrap_or(&get_cdns_url()),
-            cdns_url_pattern().len(),
-        );
-        if patch(&mut temp_data, cdns_url_pattern(), &cdns_url_replacement).is_ok() {
-            if let Some(custom_url) = cdns_url {
-                println!("  ✓ CDNs URL → Custom CDN ({})", custom_url);
-            } else {
-                println!("  ✓ CDNs URL → Arctium CDN (http://ngdp.arctium.io/customs/wow/cdns)");
-            }
-        } else {
-            println!("  ✗ CDNs URL pattern not found");
+        fat[slice.range.clone()].copy_from_slice(&ctx.data);
+        if result.is_err() {
+            break;
         }
+    }
+    ctx.data = fat;
+    result?;
+
+    let mut pipeline = PatchPipeline::new();
+    for custom_patch in custom_patches {
+        pipeline.push(Box::new(custom_patch.clone()));
+    }
+    pipeline.run(ctx, &mut |progress| {
+        on_progress(PatchProgress {
+            step_index: PATCH_STEP_NAMES.len() + progress.step_index,
+            total_steps,
+            ..progress
+        });
+    })
+}
 
-        if strip_codesign && cfg!(target_os = "macos") {
-            println!("  ✓ Remove macOS code signing");
+/// [`check_patch_counts`] for the records `ctx` gained from `first_record` on.
+///
+/// The limits also apply when every occurrence is patched; a
+/// `max_patch_count` of `usize::MAX` turns them off.
+fn check_context_patch_counts(
+    ctx: &PatchContext<'_>,
+    first_record: usize,
+    max_patch_count: Option<usize>,
+) -> Result<(), WowPatcherError> {
+    check_patch_counts(&ctx.report.patches_applied[first_record..], max_patch_count)
+}
+
+/// Ensure no step replaced more occurrences of its pattern than allowed.
+///
+/// Counts the applied records of each step, so a pattern that merely appears
+/// more than once is fine as long as only one copy was replaced, and of the
+/// RSA modulus variants only the one actually patched counts.
+/// `max_patch_count` applies to every step; without it required steps may
+/// make one replacement and optional ones up to 10.
+fn check_patch_counts(
+    records: &[PatchRecord],
+    max_patch_count: Option<usize>,
+) -> Result<(), WowPatcherError> {
+    let applied: Vec<&PatchRecord> = records
+        .iter()
+        .filter(|record| record.status == PatchStatus::Applied)
+        .collect();
+    for (index, record) in applied.iter().enumerate() {
+        let limit = match max_patch_count {
+            Some(n) => n,
+            None if record.required => DEFAULT_MAX_REQUIRED_PATCH_COUNT,
+            None => DEFAULT_MAX_OPTIONAL_PATCH_COUNT,
+        };
+        let same_step = |other: &&&PatchRecord| other.step == record.step;
+        if applied[..=index].iter().filter(same_step).count() > limit {
+            let count = applied.iter().filter(same_step).count();
+            let mut error = WowPatcherError::new(
+                ErrorCategory::PatchingError,
+                format!(
+                    "{} pattern was replaced {} times, more than the limit of {}",
+                    record.step, count, limit
+                ),
+            )
+            .with_suggestion(
+                "Raise the limit with max_patch_count if the repeated matches are expected",
+            );
+            if let Some(offset) = record.offset {
+                error = error.with_offset(offset);
+            }
+            return Err(error);
         }
+    }
+    Ok(())
+}
 
-        println!();
-        println!("No changes were made. Remove --dry-run to apply patches.");
+/// Ensure the detected client version matches what the caller expects.
+///
+/// `expected_version` compares the full version, `expected_build` only the build
+/// number. Patching is refused if an expectation is set but the version could
+/// not be detected.
+pub(crate) fn check_expected_version(
+    detected: Option<&Version>,
+    expected_version: Option<Version>,
+    expected_build: Option<u16>,
+) -> Result<(), WowPatcherError> {
+    if expected_version.is_none() && expected_build.is_none() {
         return Ok(());
     }
 
-    // Apply patches
-    let mut patch_count = 0;
+    let Some(detected) = detected else {
+        return Err(WowPatcherError::new(
+            ErrorCategory::ValidationError,
+            "Unable to detect client version to compare against the expected version",
+        ));
+    };
 
-    if verbose {
-        println!("Applying patches...");
+    if let Some(expected) = expected_version
+        && *detected != expected
+    {
+        return Err(WowPatcherError::new(
+            ErrorCategory::ValidationError,
+            format!(
+                "Client version mismatch: expected {}, found {}",
+                expected, detected
+            ),
+        )
+        .with_context("expected_version", expected)
+        .with_context("detected_version", *detected));
     }
 
-    // Portal pattern
-    if let Err(e) = patch(&mut data, portal_pattern(), &portal_pattern().empty()) {
-        if verbose {
-            println!("  ✗ Portal pattern not found: {}", e);
-        }
-        return Err(WowPatcherError::wrap(
-            ErrorCategory::PatchingError,
-            "Failed to patch portal pattern - unsupported WoW version",
-            e,
-        ));
-    } else {
-        patch_count += 1;
-        if verbose {
-            println!("  ✓ Portal pattern patched");
-        }
+    if let Some(expected) = expected_build
+        && detected.build != expected
+    {
+        return Err(WowPatcherError::new(
+            ErrorCategory::ValidationError,
+            format!(
+                "Client build mismatch: expected build {}, found {} ({})",
+                expected, detected.build, detected
+            ),
+        )
+        .with_context("expected_build", expected)
+        .with_context("detected_version", *detected));
     }
 
-    // RSA modulus - try all three patterns (different client versions use different patterns)
-    let mut rsa_patched = false;
-    let mut rsa_pattern_name = "";
+    Ok(())
+}
 
-    if patch(
-        &mut data,
-        connect_to_modulus_pattern(),
-        key_config.rsa_modulus(),
-    )
-    .is_ok()
-    {
-        rsa_patched = true;
-        rsa_pattern_name = "ConnectTo";
-    } else if patch(
-        &mut data,
-        signature_modulus_pattern(),
-        key_config.rsa_modulus(),
-    )
-    .is_ok()
-    {
-        rsa_patched = true;
-        rsa_pattern_name = "Signature";
-    } else if patch(
-        &mut data,
-        crypto_rsa_modulus_pattern(),
-        key_config.rsa_modulus(),
+/// Check whether the configured RSA key has already been written into the binary.
+///
+/// Only the pattern-sized prefix of the modulus is replaced, so that prefix is
+/// what a previously patched executable contains.
+pub(crate) fn is_already_patched(data: &[u8], key_config: &KeyConfig) -> bool {
+    let prefix_len = connect_to_modulus_pattern().len();
+    let key_prefix = &key_config.rsa_modulus()[..prefix_len];
+    data.windows(prefix_len).any(|window| window == key_prefix)
+}
+
+pub(crate) fn already_patched_error() -> WowPatcherError {
+    WowPatcherError::new(
+        ErrorCategory::PatchingError,
+        "Executable appears to be already patched with the configured keys",
     )
-    .is_ok()
-    {
-        rsa_patched = true;
-        rsa_pattern_name = "Crypto";
-    }
+    .with_context("already_patched", true)
+}
 
-    if !rsa_patched {
-        if verbose {
-            println!("  ✗ No RSA modulus pattern found (tried ConnectTo, Signature, Crypto)");
-        }
-        return Err(WowPatcherError::new(
-            ErrorCategory::PatchingError,
-            "Failed to patch RSA modulus - no known pattern found (unsupported WoW version)",
-        ));
-    } else {
-        patch_count += 1;
-        if verbose {
-            if key_config.is_trinity_core() {
-                println!(
-                    "  ✓ RSA modulus patched (TrinityCore key, {} pattern)",
-                    rsa_pattern_name
-                );
-            } else {
-                println!(
-                    "  ✓ RSA modulus patched (custom key, {} pattern)",
-                    rsa_pattern_name
-                );
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::Pattern;
+    use crate::trinity::get_unified_api_url;
+
+    #[test]
+    fn test_check_expected_version() {
+        let detected = Version::new(1, 15, 8, 64272);
+
+        assert!(check_expected_version(None, None, None).is_ok());
+        assert!(check_expected_version(Some(&detected), Some(detected), None).is_ok());
+        assert!(check_expected_version(Some(&detected), None, Some(64272)).is_ok());
+
+        let err =
+            check_expected_version(Some(&detected), Some(Version::new(1, 15, 7, 61582)), None)
+                .unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+        assert!(err.message.contains("expected 1.15.7.61582"));
+
+        let err = check_expected_version(Some(&detected), None, Some(61582)).unwrap_err();
+        assert!(err.message.contains("expected build 61582"));
+
+        assert!(check_expected_version(None, None, Some(64272)).is_err());
     }
 
-    // Ed25519 (optional based on client type)
-    if client_type.uses_ed25519() {
-        if let Err(e) = patch(
-            &mut data,
-            crypto_ed_public_key_pattern(),
-            key_config.ed25519_public_key(),
-        ) {
-            if verbose {
-                println!(
-                    "  ⚠ Ed25519 pattern not found (may be unsupported version): {}",
-                    e
-                );
-            }
-        } else {
-            patch_count += 1;
-            if verbose {
-                if key_config.is_trinity_core() {
-                    println!("  ✓ Ed25519 public key patched (TrinityCore key)");
-                } else {
-                    println!("  ✓ Ed25519 public key patched (custom key)");
-                }
-            }
-        }
-    } else if verbose {
-        println!("  ℹ {} clients use RSA-based authentication", client_type);
+    fn silent() -> ConsoleReporter {
+        ConsoleReporter::new(ReportMode::Silent)
     }
 
-    // Version URL patching - try v1 pattern first, then v2, then v3
-    let build_num = version.as_ref().map(|v| v.build as u32);
-    let mut version_url_patched = false;
-    let mut version_url_pattern_name = "";
+    fn pattern_bytes(pattern: &Pattern) -> Vec<u8> {
+        pattern.iter().map(|&b| b as u8).collect()
+    }
 
-    // Try v1 pattern
-    let version_url_replacement = create_url_replacement(
-        version_url.unwrap_or(&get_version_url(build_num, None, None)),
-        version_url_pattern().len(),
-    );
-    if patch(&mut data, version_url_pattern(), &version_url_replacement).is_ok() {
-        version_url_patched = true;
-        version_url_pattern_name = "v1";
-    } else {
-        // Try v2 pattern
-        let version_url_v2_replacement = create_url_replacement(
-            version_url.unwrap_or(&get_version_url(build_num, None, None)),
-            version_url_v2_pattern().len(),
+    #[test]
+    fn test_apply_patches_sections() {
+        let data = crate::patterns::test_helpers::create_test_executable();
+        let mut reporter = silent();
+        let mut ctx = PatchContext::new(
+            data,
+            ClientType::Retail,
+            None,
+            KeyConfig::trinity_core(),
+            &mut reporter,
+        );
+        apply_patches(&mut ctx, None, &[], &mut |_| {}).unwrap();
+        let report = ctx.report;
+        let portal = &report.patches_applied[0];
+        assert_eq!(portal.step, "Portal");
+        assert_eq!(portal.section.as_deref(), Some(".rdata"));
+        assert!(
+            report
+                .patches_applied
+                .iter()
+                .all(|record| record.offset.is_some() == record.section.is_some())
         );
-        if patch(
-            &mut data,
-            version_url_v2_pattern(),
-            &version_url_v2_replacement,
-        )
-        .is_ok()
-        {
-            version_url_patched = true;
-            version_url_pattern_name = "v2";
-        } else {
-            // Try v3 pattern (WoW Classic 1.15.8+ unified API)
-            let version_url_v3_replacement = create_url_replacement(
-                version_url.unwrap_or(&get_unified_api_url(build_num)),
-                version_url_v3_pattern().len(),
-            );
-            if patch(
-                &mut data,
-                version_url_v3_pattern(),
-                &version_url_v3_replacement,
-            )
-            .is_ok()
-            {
-                version_url_patched = true;
-                version_url_pattern_name = "v3 (unified API)";
-            }
-        }
     }
 
-    // Track if we used the unified v3 API (which handles both versions and cdns)
-    let used_unified_api = version_url_pattern_name.contains("v3");
-
-    if !version_url_patched {
-        if verbose {
-            println!(
-                "  ⚠ Version URL pattern not found (tried v1, v2, and v3, may be custom build)"
-            );
-        }
-    } else {
-        patch_count += 1;
-        if verbose {
-            if let Some(custom_url) = version_url {
-                println!(
-                    "  ✓ Version URL patched → Custom CDN ({}, {} pattern)",
-                    custom_url, version_url_pattern_name
-                );
-            } else if used_unified_api {
-                println!(
-                    "  ✓ API URL patched → Arctium CDN ({} pattern, handles versions+cdns)",
-                    version_url_pattern_name
-                );
-            } else {
-                println!(
-                    "  ✓ Version URL patched → Arctium CDN ({} pattern)",
-                    version_url_pattern_name
-                );
-            }
+    fn applied(step: &str, offset: usize, required: bool) -> PatchRecord {
+        PatchRecord {
+            step: step.to_string(),
+            status: PatchStatus::Applied,
+            required,
+            offset: Some(offset),
+            section: None,
+            original: Vec::new(),
+            replacement: Vec::new(),
         }
     }
 
-    // CDNs URL patching (skip if we used the unified v3 API which handles both)
-    if !used_unified_api {
-        let cdns_url_replacement = create_url_replacement(
-            cdns_url.unwrap_or(&get_cdns_url()),
-            cdns_url_pattern().len(),
+    #[test]
+    fn test_check_patch_counts() {
+        let records = vec![applied("Portal", 8, true), applied("Portal", 40, true)];
+        let err = check_patch_counts(&records, None).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::PatchingError);
+        assert!(
+            err.message
+                .starts_with("Portal pattern was replaced 2 times")
         );
-        if let Err(e) = patch(&mut data, cdns_url_pattern(), &cdns_url_replacement) {
-            if verbose {
-                println!(
-                    "  ⚠ CDNs URL pattern not found (may be custom build): {}",
-                    e
-                );
-            }
-        } else {
-            patch_count += 1;
-            if verbose {
-                if let Some(custom_url) = cdns_url {
-                    println!("  ✓ CDNs URL patched → Custom CDN ({})", custom_url);
-                } else {
-                    println!("  ✓ CDNs URL patched → Arctium CDN");
-                }
-            }
-        }
-    } else if verbose {
-        println!("  ℹ CDNs URL handled by unified API pattern");
+        // The offset is that of the first replacement over the limit
+        assert_eq!(err.offset(), Some(40));
+        assert!(check_patch_counts(&records, Some(2)).is_ok());
+
+        // Each RSA slot and unapplied records count separately
+        let mut missing = applied("RSA modulus (Signature)", 0, true);
+        missing.status = PatchStatus::Missing;
+        let records = vec![
+            applied("RSA modulus (ConnectTo)", 8, true),
+            missing.clone(),
+            missing,
+        ];
+        assert!(check_patch_counts(&records, None).is_ok());
+
+        // Optional steps may replace up to 10 copies by default
+        let cdns = |count: usize| -> Vec<PatchRecord> {
+            (0..count)
+                .map(|i| applied("CDNs URL", i * 64, false))
+                .collect()
+        };
+        assert!(check_patch_counts(&cdns(10), None).is_ok());
+        let err = check_patch_counts(&cdns(11), None).unwrap_err();
+        assert!(
+            err.message
+                .starts_with("CDNs URL pattern was replaced 11 times")
+        );
+        assert!(check_patch_counts(&cdns(2), Some(1)).is_err());
     }
 
-    // Create output directory if needed
-    if let Some(parent) = output_path.parent() {
-        // Only check if parent exists if it's not empty or current directory
-        if !parent.as_os_str().is_empty() && !parent.exists() {
-            return Err(WowPatcherError::new(
-                ErrorCategory::FileOperationError,
-                format!("Output directory does not exist: {:?}", parent),
-            ));
-        }
+    #[test]
+    fn test_repeated_required_pattern_is_patched_once() {
+        // A second copy of the portal pattern, e.g. from embedded debug data
+        let mut data = crate::patterns::test_helpers::create_test_executable();
+        let portal = pattern_bytes(portal_pattern());
+        let first = data.find_pattern(portal_pattern()).unwrap();
+        let second = first + 0x20;
+        data[second..second + portal.len()].copy_from_slice(&portal);
+
+        let mut reporter = silent();
+        let mut ctx = PatchContext::new(
+            data,
+            ClientType::Retail,
+            None,
+            KeyConfig::trinity_core(),
+            &mut reporter,
+        );
+        apply_patches(&mut ctx, None, &[], &mut |_| {}).unwrap();
+        assert_eq!(ctx.data.find_pattern(portal_pattern()), Some(second));
+        let portal_records = ctx
+            .report
+            .patches_applied
+            .iter()
+            .filter(|record| record.step == "Portal")
+            .count();
+        assert_eq!(portal_records, 1);
     }
 
-    // Write patched file
-    fs::write(output_path, data).map_err(|e| {
-        WowPatcherError::wrap(
-            ErrorCategory::FileOperationError,
-            "Failed to write patched executable",
-            e,
-        )
-    })?;
+    #[test]
+    fn test_portal_replacement_bytes() {
+        assert_eq!(portal_replacement_bytes(None).unwrap(), vec![0; 18]);
 
-    // Set executable permissions on Unix
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(output_path)
-            .map_err(|e| {
-                WowPatcherError::wrap(
-                    ErrorCategory::FileOperationError,
-                    "Failed to get file metadata",
-                    e,
-                )
-            })?
-            .permissions();
-        perms.set_mode(0o755);
-        fs::set_permissions(output_path, perms).map_err(|e| {
-            WowPatcherError::wrap(
-                ErrorCategory::FileOperationError,
-                "Failed to set file permissions",
-                e,
-            )
-        })?;
+        let replacement = portal_replacement_bytes(Some(b".example")).unwrap();
+        assert_eq!(replacement.len(), 18);
+        assert_eq!(&replacement[..8], b".example");
+        assert!(replacement[8..].iter().all(|&b| b == 0));
+
+        assert_eq!(
+            portal_replacement_bytes(Some(&[0; 18])).unwrap(),
+            vec![0; 18]
+        );
+        let err = portal_replacement_bytes(Some(&[b'a'; 19])).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
     }
 
-    // Remove code signing on macOS
-    if strip_codesign
-        && cfg!(target_os = "macos")
-        && let Err(e) = remove_codesigning_signature(output_path.to_str().unwrap_or(""))
-    {
-        return Err(WowPatcherError::wrap(
-            ErrorCategory::PlatformError,
-            "Failed to remove code signing",
-            e,
-        ));
+    #[test]
+    fn test_validate_url_v3() {
+        assert!(validate_url_v3("http://my-cdn.local/api").is_ok());
+        assert!(validate_url_v3("http://my-cdn.local/%s/%s").is_ok());
+        let err = validate_url_v3(&get_unified_api_url(None)).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
     }
 
-    println!(
-        "✅ Successfully applied {} patches and saved to {:?}",
-        patch_count, output_path
-    );
-    println!();
-    println!("The patched client can now connect to TrinityCore private servers.");
+    #[test]
+    fn test_write_patched_output_replaces_atomically() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let output = temp_dir.path().join("Wow-patched.exe");
+        fs::write(&output, b"old").unwrap();
 
-    Ok(())
+        let mut reporter = ConsoleReporter::new(ReportMode::Silent);
+        write_patched_output(&output, b"new", None, 0o755, &mut reporter).unwrap();
+        assert_eq!(fs::read(&output).unwrap(), b"new");
+        // Only the output is left in the directory
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+
+        // A guard dropped before commit leaves the existing output untouched
+        let guard = TempFileGuard::new(&output);
+        fs::write(guard.path(), b"partial").unwrap();
+        assert_eq!(guard.path().parent(), output.parent());
+        drop(guard);
+        assert_eq!(fs::read(&output).unwrap(), b"new");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_patch_options_default() {
+        let opts = PatchOptions::default();
+        assert!(opts.key_config.is_trinity_core());
+        assert!(opts.version_url.is_none());
+        assert!(opts.cdns_url.is_none());
+        assert!(!opts.dry_run);
+        assert!(!opts.strip_codesign);
+        assert_eq!(opts.output_mode, 0o755);
+        assert_eq!(opts.report_mode, ReportMode::Normal);
+        assert_eq!(opts.max_file_size, DEFAULT_MAX_FILE_SIZE);
+        assert_eq!(opts.min_file_size, DEFAULT_MIN_FILE_SIZE);
+        assert!(opts.on_progress.is_none());
+    }
 }
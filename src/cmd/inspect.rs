@@ -0,0 +1,69 @@
+use crate::binary::pe::import_hash;
+use crate::errors::{ErrorCategory, WowPatcherError};
+use crate::patterns::{PATTERN_NAMES, find_in_binary, pattern_by_name};
+use crate::platform::{Version, detect_client_type};
+use crate::report::{PatchRecord, PatchReport, PatchStatus};
+use std::fs;
+use std::path::Path;
+
+/// Print identifying information about a WoW executable without modifying it.
+pub fn execute_inspect(input_path: &Path, imphash: bool) -> Result<(), WowPatcherError> {
+    let data = fs::read(input_path).map_err(|e| {
+        WowPatcherError::wrap(
+            ErrorCategory::FileOperationError,
+            "Failed to read WoW executable file",
+            e,
+        )
+        .with_context("path", input_path.display().to_string())
+    })?;
+
+    println!("File:        {}", input_path.display());
+    println!(
+        "File size:   {:.2} MB",
+        data.len() as f64 / (1024.0 * 1024.0)
+    );
+    println!(
+        "Client type: {}",
+        detect_client_type(input_path.to_str().unwrap_or(""))
+    );
+    match Version::from_binary(&data) {
+        Some(version) => println!("Version:     {}", version),
+        None => println!("Version:     unknown"),
+    }
+
+    println!();
+    println!("Patterns:");
+    let scan = find_in_binary(&data);
+    let mut report = PatchReport::new();
+    for name in PATTERN_NAMES {
+        let offset = scan.found.get(name).copied();
+        let original = match (offset, pattern_by_name(name)) {
+            (Some(offset), Some(pattern)) => data[offset..offset + pattern.len()].to_vec(),
+            _ => Vec::new(),
+        };
+        report.push(PatchRecord {
+            step: name.to_string(),
+            status: if offset.is_some() {
+                PatchStatus::Found
+            } else {
+                PatchStatus::Missing
+            },
+            required: false,
+            offset,
+            section: scan.sections.get(name).map(|section| section.name.clone()),
+            original,
+            replacement: Vec::new(),
+        });
+    }
+    print!("{}", report);
+
+    if imphash {
+        println!();
+        match import_hash(&data) {
+            Some(hash) => println!("Imphash:     {}", hash),
+            None => println!("Imphash:     unavailable (not a PE or no import table)"),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use crate::errors::{ErrorCategory, WowPatcherError};
+use crate::keys::KeyConfig;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Input, Select};
+use std::path::{Path, PathBuf};
+
+/// Ask for the path of the WoW executable until an existing file is given.
+pub fn prompt_input_path() -> Result<PathBuf, WowPatcherError> {
+    let path: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Enter path to WoW executable")
+        .validate_with(|input: &String| {
+            if Path::new(input.trim()).is_file() {
+                Ok(())
+            } else {
+                Err("File does not exist")
+            }
+        })
+        .interact_text()
+        .map_err(aborted)?;
+    Ok(PathBuf::from(path.trim()))
+}
+
+/// Ask for the output path, accepting `default` on an empty answer.
+pub fn prompt_output_path(default: &Path) -> Result<PathBuf, WowPatcherError> {
+    let path: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Output file")
+        .default(default.display().to_string())
+        .interact_text()
+        .map_err(aborted)?;
+    Ok(PathBuf::from(path.trim()))
+}
+
+/// Offer a numbered menu of the registered key presets and return the chosen name.
+pub fn prompt_key_preset() -> Result<String, WowPatcherError> {
+    let mut names = KeyConfig::preset_names();
+    let items: Vec<String> = names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| format!("{}. {}", index + 1, name))
+        .collect();
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Server key preset")
+        .items(&items)
+        .default(0)
+        .interact()
+        .map_err(aborted)?;
+    Ok(names.swap_remove(selection))
+}
+
+/// Prompts fail when the terminal is unavailable or the user presses Ctrl+C.
+fn aborted(e: dialoguer::Error) -> WowPatcherError {
+    WowPatcherError::wrap(
+        ErrorCategory::ValidationError,
+        "Interactive session aborted",
+        e,
+    )
+    .with_suggestion("Pass the missing arguments on the command line instead")
+}
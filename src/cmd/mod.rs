@@ -1,2 +1,14 @@
+#[cfg(feature = "cli")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cli")))]
+pub mod completions;
 pub mod dump;
 pub mod execute;
+pub mod inspect;
+#[cfg(feature = "cli-interactive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cli-interactive")))]
+pub mod interactive;
+pub mod steps;
+pub mod test_binary;
+#[cfg(feature = "dev-tools")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dev-tools")))]
+pub mod update_patterns;
@@ -0,0 +1,81 @@
+use super::{
+    PatchContext, PatchStep, StepResult, applied_record, describe_offset, patch_capturing,
+    patch_later_occurrences, print_patch_bytes, unapplied_record, url_replacement,
+};
+use crate::patterns::cdns_url_pattern;
+use crate::report::PatchStatus;
+use crate::trinity::get_cdns_url;
+
+/// Point the CDNs URL at the configured CDN, skipped when the unified API URL was patched.
+pub struct CdnsUrlStep;
+
+impl PatchStep for CdnsUrlStep {
+    fn name(&self) -> &str {
+        "CDNs URL"
+    }
+
+    fn apply(&self, ctx: &mut PatchContext<'_>) -> StepResult {
+        if ctx.unified_api_patched {
+            if ctx.verbose {
+                ctx.reporter
+                    .detail("  ℹ CDNs URL handled by unified API pattern");
+            }
+            ctx.report
+                .push(unapplied_record(self.name(), PatchStatus::Skipped));
+            return Ok(PatchStatus::Skipped);
+        }
+
+        let cdns_url_replacement = url_replacement(
+            &ctx.data,
+            cdns_url_pattern(),
+            ctx.cdns_url.as_deref().unwrap_or(&get_cdns_url()),
+            ctx.cdn_port,
+        )?;
+        match patch_capturing(&mut ctx.data, cdns_url_pattern(), &cdns_url_replacement) {
+            Ok((offset, before)) => {
+                if ctx.verbose {
+                    let target = ctx
+                        .cdns_url
+                        .as_deref()
+                        .map(|url| format!("Custom CDN ({})", url))
+                        .unwrap_or_else(|| "Arctium CDN".to_string());
+                    ctx.reporter.detail(&format!(
+                        "  ✓ CDNs URL patched at {} → {}",
+                        describe_offset(&ctx.data, offset),
+                        target
+                    ));
+                    print_patch_bytes(ctx.reporter, &ctx.data, offset, &before, "CDNs URL");
+                }
+                ctx.report
+                    .push(applied_record(&ctx.data, self.name(), offset, before));
+                if ctx.patch_all_occurrences {
+                    patch_later_occurrences(
+                        &mut ctx.data,
+                        ctx.reporter,
+                        &mut ctx.report,
+                        self.name(),
+                        cdns_url_pattern(),
+                        &cdns_url_replacement,
+                        offset,
+                    );
+                }
+                Ok(PatchStatus::Applied)
+            }
+            Err(e) => {
+                if ctx.verbose {
+                    ctx.reporter.detail(&format!(
+                        "  ⚠ CDNs URL pattern not found (may be custom build): {}",
+                        e
+                    ));
+                }
+                ctx.report
+                    .push(unapplied_record(self.name(), PatchStatus::Missing));
+                Ok(PatchStatus::Missing)
+            }
+        }
+    }
+
+    fn is_required(&self) -> bool {
+        false
+    }
+}
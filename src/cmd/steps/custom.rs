@@ -0,0 +1,168 @@
+use super::{
+    PatchContext, PatchStep, StepResult, applied_record, print_patch_bytes, unapplied_record,
+};
+use crate::binary::check_offset_section;
+use crate::errors::{ErrorCategory, WowPatcherError};
+use crate::report::PatchStatus;
+
+/// Raw bytes written at a known file offset, without searching for a pattern.
+///
+/// The bytes must lie entirely within one section of the executable. An
+/// offset outside every section fails the run when the patch is required and
+/// is recorded as missing otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomBytePatch {
+    /// Name shown in progress callbacks and the report
+    pub name: String,
+    /// File offset of the first byte written
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+    pub required: bool,
+}
+
+impl CustomBytePatch {
+    pub fn new(name: &str, offset: usize, bytes: Vec<u8>, required: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            offset,
+            bytes,
+            required,
+        }
+    }
+
+    /// Check that the patched range lies within a single section of `data`.
+    fn check_bounds(&self, data: &[u8]) -> Result<(), WowPatcherError> {
+        let end = self.offset.saturating_add(self.bytes.len());
+        let section = check_offset_section(data, self.offset).ok_or_else(|| {
+            WowPatcherError::new(
+                ErrorCategory::PatchingError,
+                format!(
+                    "Custom patch '{}' at 0x{:x} is not inside any section",
+                    self.name, self.offset
+                ),
+            )
+            .with_offset(self.offset)
+        })?;
+        if self.bytes.is_empty() || end > section.raw_offset_range().end || end > data.len() {
+            return Err(WowPatcherError::new(
+                ErrorCategory::PatchingError,
+                format!(
+                    "Custom patch '{}' of {} bytes at 0x{:x} does not fit in section '{}'",
+                    self.name,
+                    self.bytes.len(),
+                    self.offset,
+                    section.name
+                ),
+            )
+            .with_offset(self.offset)
+            .with_context("section", section.name));
+        }
+        Ok(())
+    }
+}
+
+impl PatchStep for CustomBytePatch {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn apply(&self, ctx: &mut PatchContext<'_>) -> StepResult {
+        if let Err(e) = self.check_bounds(&ctx.data) {
+            if self.required {
+                return Err(e);
+            }
+            if ctx.verbose {
+                ctx.reporter.detail(&format!("  ⚠ {}", e.message));
+            }
+            ctx.report
+                .push(unapplied_record(&self.name, PatchStatus::Missing));
+            return Ok(PatchStatus::Missing);
+        }
+
+        let range = self.offset..self.offset + self.bytes.len();
+        let before = ctx.data[range.clone()].to_vec();
+        ctx.data[range].copy_from_slice(&self.bytes);
+        if ctx.verbose {
+            ctx.reporter.detail(&format!(
+                "  ✓ {} patched at 0x{:x} ({} bytes)",
+                self.name,
+                self.offset,
+                self.bytes.len()
+            ));
+            print_patch_bytes(ctx.reporter, &ctx.data, self.offset, &before, &self.name);
+        }
+        ctx.report
+            .push(applied_record(&ctx.data, &self.name, self.offset, before));
+        Ok(PatchStatus::Applied)
+    }
+
+    fn is_required(&self) -> bool {
+        self.required
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::steps::PatchPipeline;
+    use crate::keys::KeyConfig;
+    use crate::platform::ClientType;
+    use crate::report::{ConsoleReporter, PatchReport, ReportMode};
+
+    fn run(data: &mut Vec<u8>, patch: CustomBytePatch) -> Result<PatchReport, WowPatcherError> {
+        let mut reporter = ConsoleReporter::new(ReportMode::Silent);
+        let mut ctx = PatchContext::new(
+            std::mem::take(data),
+            ClientType::Retail,
+            None,
+            KeyConfig::trinity_core(),
+            &mut reporter,
+        );
+        let mut pipeline = PatchPipeline::new();
+        pipeline.push(Box::new(patch));
+        let result = pipeline.run(&mut ctx, &mut |_| {});
+        *data = ctx.data;
+        result.map(|()| ctx.report)
+    }
+
+    #[test]
+    fn test_custom_byte_patch() {
+        let mut data = crate::patterns::test_helpers::create_test_executable();
+        let patch = CustomBytePatch::new("Flag", 0x210, vec![0xEB, 0x01], true);
+        let report = run(&mut data, patch).unwrap();
+        assert_eq!(&data[0x210..0x212], &[0xEB, 0x01]);
+        let record = &report.patches_applied[0];
+        assert_eq!(record.step, "Flag");
+        assert_eq!(record.offset, Some(0x210));
+        assert_eq!(record.original, vec![0, 0]);
+        assert_eq!(record.section.as_deref(), Some(".rdata"));
+
+        // The headers precede every section, and the last byte cannot be overrun
+        let original = data.clone();
+        let err = run(
+            &mut data,
+            CustomBytePatch::new("Header", 0x10, vec![1], true),
+        )
+        .unwrap_err();
+        assert_eq!(err.category, ErrorCategory::PatchingError);
+        assert_eq!(err.offset(), Some(0x10));
+        let end = data.len() - 1;
+        assert!(
+            run(
+                &mut data,
+                CustomBytePatch::new("Tail", end, vec![1, 2], true)
+            )
+            .is_err()
+        );
+        assert_eq!(data, original);
+
+        // Optional patches outside a section are reported as missing
+        let report = run(
+            &mut data,
+            CustomBytePatch::new("Header", 0x10, vec![1], false),
+        )
+        .unwrap();
+        assert_eq!(report.patches_applied[0].status, PatchStatus::Missing);
+        assert_eq!(data, original);
+    }
+}
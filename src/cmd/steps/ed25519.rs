@@ -0,0 +1,90 @@
+use super::{
+    PatchContext, PatchStep, StepResult, applied_record, describe_offset, key_kind,
+    patch_capturing, patch_later_occurrences, print_patch_bytes, unapplied_record,
+};
+use crate::report::PatchStatus;
+
+/// Replace the Ed25519 public key, skipped for RSA-only key configs and clients without one.
+pub struct Ed25519Step;
+
+impl PatchStep for Ed25519Step {
+    fn name(&self) -> &str {
+        "Ed25519 public key"
+    }
+
+    fn apply(&self, ctx: &mut PatchContext<'_>) -> StepResult {
+        if ctx.key_config.patches_ed25519()
+            && let Some(ed25519_pattern) = ctx.patterns.ed25519_public_key
+        {
+            match patch_capturing(
+                &mut ctx.data,
+                ed25519_pattern,
+                ctx.key_config.ed25519_public_key(),
+            ) {
+                Ok((offset, before)) => {
+                    if ctx.verbose {
+                        ctx.reporter.detail(&format!(
+                            "  ✓ Ed25519 public key patched at {} ({})",
+                            describe_offset(&ctx.data, offset),
+                            key_kind(&ctx.key_config)
+                        ));
+                        print_patch_bytes(
+                            ctx.reporter,
+                            &ctx.data,
+                            offset,
+                            &before,
+                            "Ed25519 public key",
+                        );
+                    }
+                    ctx.report
+                        .push(applied_record(&ctx.data, self.name(), offset, before));
+                    if ctx.patch_all_occurrences {
+                        patch_later_occurrences(
+                            &mut ctx.data,
+                            ctx.reporter,
+                            &mut ctx.report,
+                            self.name(),
+                            ed25519_pattern,
+                            ctx.key_config.ed25519_public_key(),
+                            offset,
+                        );
+                    }
+                    Ok(PatchStatus::Applied)
+                }
+                Err(e) => {
+                    if ctx.verbose {
+                        ctx.reporter.detail(&format!(
+                            "  ⚠ Ed25519 pattern not found (may be unsupported version): {}",
+                            e
+                        ));
+                    }
+                    ctx.report
+                        .push(unapplied_record(self.name(), PatchStatus::Missing));
+                    Ok(PatchStatus::Missing)
+                }
+            }
+        } else {
+            if ctx.verbose && ctx.key_config.patches_ed25519() {
+                ctx.reporter.detail(&format!(
+                    "  ℹ {} clients have no Ed25519 key",
+                    ctx.client_type
+                ));
+            } else if ctx.verbose && ctx.client_type.uses_ed25519() {
+                ctx.reporter
+                    .detail("  ℹ Ed25519 patch disabled, only the RSA modulus is replaced");
+            } else if ctx.verbose {
+                ctx.reporter.detail(&format!(
+                    "  ℹ {} clients use RSA-based authentication",
+                    ctx.client_type
+                ));
+            }
+            ctx.report
+                .push(unapplied_record(self.name(), PatchStatus::Skipped));
+            Ok(PatchStatus::Skipped)
+        }
+    }
+
+    fn is_required(&self) -> bool {
+        false
+    }
+}
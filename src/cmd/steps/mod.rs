@@ -0,0 +1,383 @@
+//! Patch steps applied to an executable, and the pipeline running them in order.
+//!
+//! [`PatchPipeline::default`] holds the built-in steps named in
+//! [`PATCH_STEP_NAMES`](crate::cmd::execute::PATCH_STEP_NAMES). Steps can be
+//! added, removed or reordered before the pipeline runs.
+
+mod cdns_url;
+mod custom;
+mod ed25519;
+mod portal;
+mod rsa;
+mod version_url;
+
+pub use cdns_url::CdnsUrlStep;
+pub use custom::CustomBytePatch;
+pub use ed25519::Ed25519Step;
+pub use portal::PortalStep;
+pub use rsa::RsaModulusStep;
+pub use version_url::VersionUrlStep;
+
+use crate::binary::{
+    Arch, DataExt, ParsedBinary, Pattern, check_offset_section, check_offset_section_parsed,
+    find_pattern_region, hex_diff, hex_dump, patch_region,
+};
+use crate::errors::{ErrorCategory, WowPatcherError, new_pattern_missing_error};
+use crate::keys::KeyConfig;
+use crate::patcher::PatchProgress;
+use crate::patterns::{PatternSet, for_client_type};
+use crate::platform::{ClientType, Version};
+use crate::report::{PatchRecord, PatchReport, PatchStatus, Reporter};
+use crate::trinity::{
+    ARCTIUM_CDN, PORTAL_REPLACEMENT, VersionUrlConfig, create_url_replacement,
+    create_url_replacement_checked, with_port,
+};
+
+/// Outcome of a single step: the status of its patch, or the error that aborts the run.
+pub type StepResult = Result<PatchStatus, WowPatcherError>;
+
+/// A single patch applied by a [`PatchPipeline`].
+pub trait PatchStep {
+    /// Name shown in progress callbacks.
+    fn name(&self) -> &str;
+
+    /// Patch `ctx.data` and append the outcome to `ctx.report`.
+    fn apply(&self, ctx: &mut PatchContext<'_>) -> StepResult;
+
+    /// Whether the run fails when this step does not apply its patch.
+    fn is_required(&self) -> bool;
+}
+
+/// State shared by the steps of one pipeline run.
+///
+/// Steps patch `data` in place and append their outcome to `report`. Once the
+/// run finishes, take the patched image and the report back out of the context.
+pub struct PatchContext<'a> {
+    /// Executable image being patched
+    pub data: Vec<u8>,
+    pub client_type: ClientType,
+    pub version: Option<Version>,
+    pub key_config: KeyConfig,
+    /// Records of every step run so far
+    pub report: PatchReport,
+    /// Whether steps describe each patch through `reporter`
+    pub verbose: bool,
+    /// Key patterns to try for this client
+    pub patterns: PatternSet,
+    /// Patch both the ConnectTo and Signature RSA slots
+    pub dual_rsa: bool,
+    /// Bytes written over the portal host suffix
+    pub portal_replacement: Vec<u8>,
+    /// Custom version URL, `None` for one generated from `version_url_config`
+    pub version_url: Option<String>,
+    pub version_url_config: VersionUrlConfig<'a>,
+    /// Custom CDNs URL, `None` for the Arctium CDN
+    pub cdns_url: Option<String>,
+    /// Custom unified API URL for the v3 pattern, `None` to fall back to `version_url`
+    pub cdns_url_v3: Option<String>,
+    pub cdn_port: Option<u16>,
+    /// Set once the v3 unified API URL, which also serves the CDNs list, is patched
+    pub unified_api_patched: bool,
+    /// Patch every occurrence of each pattern instead of only the first
+    pub patch_all_occurrences: bool,
+    /// Slices of a universal Mach-O binary to patch
+    pub architecture: Arch,
+    pub reporter: &'a mut dyn Reporter,
+}
+
+impl<'a> PatchContext<'a> {
+    /// Create a context patching `data` with the default portal replacement and Arctium CDN URLs.
+    ///
+    /// Without a detected version the key patterns are narrowed down by
+    /// client type. A secondary RSA modulus in `key_config` enables dual RSA mode.
+    pub fn new(
+        data: Vec<u8>,
+        client_type: ClientType,
+        version: Option<Version>,
+        key_config: KeyConfig,
+        reporter: &'a mut dyn Reporter,
+    ) -> Self {
+        let patterns = match version {
+            Some(_) => PatternSet::all(),
+            None => for_client_type(client_type),
+        };
+        Self {
+            data,
+            client_type,
+            version,
+            dual_rsa: key_config.secondary_rsa_modulus().is_some(),
+            key_config,
+            report: PatchReport::new(),
+            verbose: reporter.is_verbose(),
+            patterns,
+            portal_replacement: PORTAL_REPLACEMENT.to_vec(),
+            version_url: None,
+            version_url_config: ARCTIUM_CDN,
+            cdns_url: None,
+            cdns_url_v3: None,
+            cdn_port: None,
+            unified_api_patched: false,
+            patch_all_occurrences: false,
+            architecture: Arch::All,
+            reporter,
+        }
+    }
+}
+
+/// Ordered list of patch steps.
+pub struct PatchPipeline {
+    steps: Vec<Box<dyn PatchStep>>,
+}
+
+impl Default for PatchPipeline {
+    /// The built-in steps: portal, RSA modulus, Ed25519 key, version URL and CDNs URL.
+    fn default() -> Self {
+        Self {
+            steps: vec![
+                Box::new(PortalStep),
+                Box::new(RsaModulusStep),
+                Box::new(Ed25519Step),
+                Box::new(VersionUrlStep),
+                Box::new(CdnsUrlStep),
+            ],
+        }
+    }
+}
+
+impl PatchPipeline {
+    /// Create a pipeline without any steps.
+    pub fn new() -> Self {
+        Self { steps: Vec::new() }
+    }
+
+    /// Append a step to the end of the pipeline.
+    pub fn push(&mut self, step: Box<dyn PatchStep>) {
+        self.steps.push(step);
+    }
+
+    /// Insert a step at `index`, shifting the later steps back.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of steps.
+    pub fn insert(&mut self, index: usize, step: Box<dyn PatchStep>) {
+        self.steps.insert(index, step);
+    }
+
+    /// Remove the first step called `name`, returning it if there was one.
+    pub fn remove(&mut self, name: &str) -> Option<Box<dyn PatchStep>> {
+        let index = self.steps.iter().position(|step| step.name() == name)?;
+        Some(self.steps.remove(index))
+    }
+
+    /// Names of the steps, in the order they run.
+    pub fn step_names(&self) -> Vec<&str> {
+        self.steps.iter().map(|step| step.name()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Run every step in order.
+    ///
+    /// `on_progress` is called after each step. An error from any step, or a
+    /// required step that does not apply its patch, aborts the run. Records
+    /// are marked required when the step that pushed them is. Once every step
+    /// has run, the section of each patched offset is filled in.
+    pub fn run(
+        &self,
+        ctx: &mut PatchContext<'_>,
+        on_progress: &mut dyn FnMut(PatchProgress),
+    ) -> Result<(), WowPatcherError> {
+        for (step_index, step) in self.steps.iter().enumerate() {
+            let first_record = ctx.report.patches_applied.len();
+            let status = step.apply(ctx)?;
+            for record in &mut ctx.report.patches_applied[first_record..] {
+                record.required = step.is_required();
+            }
+            if step.is_required() && status != PatchStatus::Applied {
+                return Err(WowPatcherError::new(
+                    ErrorCategory::PatchingError,
+                    format!("Required patch step '{}' was {}", step.name(), status),
+                ));
+            }
+
+            on_progress(PatchProgress {
+                step_index,
+                total_steps: self.steps.len(),
+                step_name: step.name().to_string(),
+            });
+        }
+
+        // Patching leaves the headers untouched, so one parse resolves every section
+        if let Ok(binary) = ParsedBinary::parse(&ctx.data) {
+            for record in &mut ctx.report.patches_applied {
+                record.section = record
+                    .offset
+                    .and_then(|offset| check_offset_section_parsed(&binary, offset))
+                    .map(|section| section.name);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Apply a patch, returning its offset and a copy of the original bytes.
+pub(crate) fn patch_capturing(
+    data: &mut [u8],
+    find: &Pattern,
+    replace: &[u8],
+) -> Result<(usize, Vec<u8>), WowPatcherError> {
+    let offset = data
+        .find_pattern(find)
+        .ok_or_else(|| new_pattern_missing_error("pattern not found in data"))?;
+    let before = data[offset..offset + find.len()].to_vec();
+    patch_region(data, offset, offset + find.len(), find, replace)?;
+    Ok((offset, before))
+}
+
+/// Patch the occurrences of `find` after the first one, patched at `first`.
+///
+/// Used when every occurrence is patched; each one gets its own record under
+/// `step`. Returns the number of additional patches applied.
+pub(crate) fn patch_later_occurrences(
+    data: &mut [u8],
+    reporter: &mut dyn Reporter,
+    report: &mut PatchReport,
+    step: &str,
+    find: &Pattern,
+    replace: &[u8],
+    first: usize,
+) -> usize {
+    let mut count = 0;
+    let mut start = first + find.len();
+    while let Some(offset) = find_pattern_region(data, start, data.len(), find) {
+        let before = data[offset..offset + find.len()].to_vec();
+        if patch_region(data, offset, offset + find.len(), find, replace).is_err() {
+            break;
+        }
+        if reporter.is_verbose() {
+            reporter.detail(&format!(
+                "  ✓ {} also patched at {}",
+                step,
+                describe_offset(data, offset)
+            ));
+        }
+        report.push(applied_record(data, step, offset, before));
+        count += 1;
+        start = offset + find.len();
+    }
+    count
+}
+
+/// Build the bytes written over a URL pattern, inserting `cdn_port` into the host.
+///
+/// With a port the URL must fit the pattern, which is checked only when the
+/// pattern is present in `data` so that absent variants do not fail the run.
+pub(crate) fn url_replacement(
+    data: &[u8],
+    pattern: &Pattern,
+    url: &str,
+    cdn_port: Option<u16>,
+) -> Result<Vec<u8>, WowPatcherError> {
+    match cdn_port {
+        Some(port) if data.find_pattern(pattern).is_some() => {
+            create_url_replacement_checked(&with_port(url, port), pattern.len())
+        }
+        Some(port) => Ok(create_url_replacement(&with_port(url, port), pattern.len())),
+        None => Ok(create_url_replacement(url, pattern.len())),
+    }
+}
+
+/// Record a patch that was written at `offset`, replacing `original`.
+///
+/// Whether the step is required, and the section, are filled in by
+/// [`PatchPipeline::run`].
+fn applied_record(data: &[u8], step: &str, offset: usize, original: Vec<u8>) -> PatchRecord {
+    PatchRecord {
+        step: step.to_string(),
+        status: PatchStatus::Applied,
+        required: false,
+        offset: Some(offset),
+        section: None,
+        replacement: data[offset..offset + original.len()].to_vec(),
+        original,
+    }
+}
+
+/// Record a patch step that did not write anything.
+fn unapplied_record(step: &str, status: PatchStatus) -> PatchRecord {
+    PatchRecord {
+        step: step.to_string(),
+        status,
+        required: false,
+        offset: None,
+        section: None,
+        original: Vec::new(),
+        replacement: Vec::new(),
+    }
+}
+
+/// Print the patched bytes and how they differ from the original bytes.
+fn print_patch_bytes(
+    reporter: &mut dyn Reporter,
+    data: &[u8],
+    offset: usize,
+    before: &[u8],
+    label: &str,
+) {
+    if before.is_empty() {
+        return;
+    }
+    reporter.detail(hex_dump(data, offset, before.len(), label).trim_end());
+    reporter.detail(hex_diff(before, &data[offset..], offset, before.len()).trim_end());
+}
+
+/// Format an offset together with the section it falls in, for verbose output.
+fn describe_offset(data: &[u8], offset: usize) -> String {
+    match check_offset_section(data, offset) {
+        Some(section) => format!("0x{:x} in '{}'", offset, section.name),
+        None => format!("0x{:x}", offset),
+    }
+}
+
+/// Describe the configured key for verbose output.
+fn key_kind(key_config: &KeyConfig) -> &'static str {
+    if key_config.is_trinity_core() {
+        "TrinityCore key"
+    } else {
+        "custom key"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::execute::PATCH_STEP_NAMES;
+
+    #[test]
+    fn test_pipeline_steps() {
+        let mut pipeline = PatchPipeline::default();
+        assert_eq!(pipeline.step_names(), PATCH_STEP_NAMES);
+        assert!(pipeline.steps.iter().take(2).all(|step| step.is_required()));
+        assert!(
+            pipeline
+                .steps
+                .iter()
+                .skip(2)
+                .all(|step| !step.is_required())
+        );
+
+        let cdns = pipeline.remove("CDNs URL").unwrap();
+        assert!(pipeline.remove("CDNs URL").is_none());
+        pipeline.insert(0, cdns);
+        assert_eq!(pipeline.step_names()[0], "CDNs URL");
+        assert_eq!(pipeline.len(), PATCH_STEP_NAMES.len());
+        assert!(PatchPipeline::new().is_empty());
+    }
+}
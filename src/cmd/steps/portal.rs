@@ -0,0 +1,65 @@
+use super::{
+    PatchContext, PatchStep, StepResult, applied_record, describe_offset, patch_capturing,
+    patch_later_occurrences, print_patch_bytes,
+};
+use crate::cmd::execute::{UNSUPPORTED_VERSION_HINT, already_patched_error, is_already_patched};
+use crate::errors::{ErrorCategory, WowPatcherError};
+use crate::patterns::portal_pattern;
+use crate::report::PatchStatus;
+
+/// Blank out the portal hostname so the client uses the server from its config.
+pub struct PortalStep;
+
+impl PatchStep for PortalStep {
+    fn name(&self) -> &str {
+        "Portal"
+    }
+
+    fn apply(&self, ctx: &mut PatchContext<'_>) -> StepResult {
+        match patch_capturing(&mut ctx.data, portal_pattern(), &ctx.portal_replacement) {
+            Ok((offset, before)) => {
+                if ctx.verbose {
+                    ctx.reporter.detail(&format!(
+                        "  ✓ Portal pattern patched at {}",
+                        describe_offset(&ctx.data, offset)
+                    ));
+                    print_patch_bytes(ctx.reporter, &ctx.data, offset, &before, "Portal");
+                }
+                ctx.report
+                    .push(applied_record(&ctx.data, "Portal", offset, before));
+                if ctx.patch_all_occurrences {
+                    patch_later_occurrences(
+                        &mut ctx.data,
+                        ctx.reporter,
+                        &mut ctx.report,
+                        "Portal",
+                        portal_pattern(),
+                        &ctx.portal_replacement,
+                        offset,
+                    );
+                }
+                Ok(PatchStatus::Applied)
+            }
+            Err(e) => {
+                if ctx.verbose {
+                    ctx.reporter
+                        .detail(&format!("  ✗ Portal pattern not found: {}", e));
+                }
+                if is_already_patched(&ctx.data, &ctx.key_config) {
+                    return Err(already_patched_error());
+                }
+                Err(WowPatcherError::wrap(
+                    ErrorCategory::PatchingError,
+                    "Failed to patch portal pattern - unsupported WoW version",
+                    e,
+                )
+                .with_context("pattern_missing", true)
+                .with_suggestion(UNSUPPORTED_VERSION_HINT))
+            }
+        }
+    }
+
+    fn is_required(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,268 @@
+use super::{
+    PatchContext, PatchStep, StepResult, applied_record, describe_offset, key_kind,
+    patch_capturing, patch_later_occurrences, print_patch_bytes, unapplied_record,
+};
+use crate::cmd::execute::{UNSUPPORTED_VERSION_HINT, already_patched_error, is_already_patched};
+use crate::errors::new_pattern_missing_error;
+use crate::keys::KeyConfig;
+use crate::patterns::{PatternSet, connect_to_modulus_pattern, signature_modulus_pattern};
+use crate::report::{PatchReport, PatchStatus, Reporter};
+
+/// Replace the RSA modulus, or both RSA slots in dual RSA mode.
+pub struct RsaModulusStep;
+
+impl PatchStep for RsaModulusStep {
+    fn name(&self) -> &str {
+        "RSA modulus"
+    }
+
+    fn apply(&self, ctx: &mut PatchContext<'_>) -> StepResult {
+        // Different client versions use different patterns
+        let rsa_patch_count = if ctx.dual_rsa {
+            patch_dual_rsa(
+                &mut ctx.data,
+                &ctx.key_config,
+                ctx.patch_all_occurrences,
+                ctx.reporter,
+                &mut ctx.report,
+            )
+        } else {
+            patch_rsa(
+                &mut ctx.data,
+                &ctx.key_config,
+                &ctx.patterns,
+                ctx.patch_all_occurrences,
+                ctx.reporter,
+                &mut ctx.report,
+            )
+        };
+
+        if rsa_patch_count == 0 {
+            if ctx.verbose {
+                let tried: Vec<&str> = ctx
+                    .patterns
+                    .rsa_modulus
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect();
+                ctx.reporter.detail(&format!(
+                    "  ✗ No RSA modulus pattern found (tried {})",
+                    tried.join(", ")
+                ));
+            }
+            if is_already_patched(&ctx.data, &ctx.key_config) {
+                return Err(already_patched_error());
+            }
+            return Err(new_pattern_missing_error(
+                "Failed to patch RSA modulus - no known pattern found (unsupported WoW version)",
+            )
+            .with_suggestion(UNSUPPORTED_VERSION_HINT));
+        }
+        Ok(PatchStatus::Applied)
+    }
+
+    fn is_required(&self) -> bool {
+        true
+    }
+}
+
+/// Patch the first RSA modulus pattern found, returning the number of patches applied.
+///
+/// With `all_occurrences` every copy of that pattern is patched.
+fn patch_rsa(
+    data: &mut [u8],
+    key_config: &KeyConfig,
+    patterns: &PatternSet,
+    all_occurrences: bool,
+    reporter: &mut dyn Reporter,
+    report: &mut PatchReport,
+) -> usize {
+    for &(name, pattern) in &patterns.rsa_modulus {
+        if let Ok((offset, before)) = patch_capturing(data, pattern, key_config.rsa_modulus()) {
+            if reporter.is_verbose() {
+                reporter.detail(&format!(
+                    "  ✓ RSA modulus patched at {} ({}, {} pattern)",
+                    describe_offset(data, offset),
+                    key_kind(key_config),
+                    name
+                ));
+                print_patch_bytes(reporter, data, offset, &before, "RSA modulus");
+            }
+            let step = format!("RSA modulus ({})", name);
+            report.push(applied_record(data, &step, offset, before));
+            if all_occurrences {
+                return 1 + patch_later_occurrences(
+                    data,
+                    reporter,
+                    report,
+                    &step,
+                    pattern,
+                    key_config.rsa_modulus(),
+                    offset,
+                );
+            }
+            return 1;
+        }
+    }
+    0
+}
+
+/// Patch the ConnectTo and Signature RSA slots for dual-stack servers.
+///
+/// The primary modulus goes into the ConnectTo slot and the secondary modulus
+/// (or the primary one when none is configured) into the Signature slot.
+/// Returns the number of patches applied, which with `all_occurrences` can
+/// exceed the number of slots.
+fn patch_dual_rsa(
+    data: &mut [u8],
+    key_config: &KeyConfig,
+    all_occurrences: bool,
+    reporter: &mut dyn Reporter,
+    report: &mut PatchReport,
+) -> usize {
+    let (secondary, secondary_kind) = match key_config.secondary_rsa_modulus() {
+        Some(modulus) => (modulus, "secondary key"),
+        None => (key_config.rsa_modulus(), "primary key"),
+    };
+
+    let mut count = 0;
+    for (pattern, name, modulus, key_kind) in [
+        (
+            connect_to_modulus_pattern(),
+            "ConnectTo",
+            key_config.rsa_modulus(),
+            "primary key",
+        ),
+        (
+            signature_modulus_pattern(),
+            "Signature",
+            secondary,
+            secondary_kind,
+        ),
+    ] {
+        let step = format!("RSA modulus ({})", name);
+        match patch_capturing(data, pattern, modulus) {
+            Ok((offset, before)) => {
+                count += 1;
+                if reporter.is_verbose() {
+                    reporter.detail(&format!(
+                        "  ✓ RSA modulus patched at {} ({}, {} pattern)",
+                        describe_offset(data, offset),
+                        key_kind,
+                        name
+                    ));
+                    print_patch_bytes(reporter, data, offset, &before, "RSA modulus");
+                }
+                report.push(applied_record(data, &step, offset, before));
+                if all_occurrences {
+                    count += patch_later_occurrences(
+                        data, reporter, report, &step, pattern, modulus, offset,
+                    );
+                }
+            }
+            Err(_) => {
+                if reporter.is_verbose() {
+                    reporter.detail(&format!(
+                        "  ⚠ {} RSA pattern not found, slot left unchanged",
+                        name
+                    ));
+                }
+                report.push(unapplied_record(&step, PatchStatus::Missing));
+            }
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::Pattern;
+    use crate::report::{ConsoleReporter, ReportMode};
+
+    fn silent() -> ConsoleReporter {
+        ConsoleReporter::new(ReportMode::Silent)
+    }
+
+    fn pattern_bytes(pattern: &Pattern) -> Vec<u8> {
+        pattern.iter().map(|&b| b as u8).collect()
+    }
+
+    #[test]
+    fn test_patch_dual_rsa() {
+        let mut data = vec![0u8; 16];
+        data.extend(pattern_bytes(connect_to_modulus_pattern()));
+        data.extend([0u8; 16]);
+        data.extend(pattern_bytes(signature_modulus_pattern()));
+
+        let mut secondary = vec![0x42; 256];
+        secondary[0] = 0x43;
+        let key_config = KeyConfig::trinity_core()
+            .with_secondary_rsa(secondary.clone())
+            .unwrap();
+
+        let mut report = PatchReport::new();
+        assert_eq!(
+            patch_dual_rsa(&mut data, &key_config, false, &mut silent(), &mut report),
+            2
+        );
+        assert_eq!(&data[16..24], &key_config.rsa_modulus()[..8]);
+        assert_eq!(&data[40..48], &secondary[..8]);
+        assert_eq!(report.applied_count(), 2);
+        assert_eq!(report.patches_applied[1].step, "RSA modulus (Signature)");
+        assert_eq!(report.patches_applied[1].offset, Some(40));
+        assert_eq!(report.patches_applied[1].replacement[..8], secondary[..8]);
+
+        // Without dual mode only the first matching slot is patched
+        let mut data = pattern_bytes(connect_to_modulus_pattern());
+        data.extend(pattern_bytes(signature_modulus_pattern()));
+        let mut report = PatchReport::new();
+        assert_eq!(
+            patch_rsa(
+                &mut data,
+                &KeyConfig::trinity_core(),
+                &PatternSet::all(),
+                false,
+                &mut silent(),
+                &mut report
+            ),
+            1
+        );
+        assert_eq!(report.patches_applied.len(), 1);
+        assert_eq!(&data[8..], &pattern_bytes(signature_modulus_pattern())[..]);
+    }
+
+    #[test]
+    fn test_patch_rsa_all_occurrences() {
+        // Three back-to-back copies, as in builds with a debug copy of the modulus
+        let copy = pattern_bytes(connect_to_modulus_pattern());
+        let original = copy.repeat(3);
+        let key_config = KeyConfig::trinity_core();
+        let patch = |all_occurrences: bool| {
+            let mut data = original.clone();
+            let mut report = PatchReport::new();
+            let count = patch_rsa(
+                &mut data,
+                &key_config,
+                &PatternSet::all(),
+                all_occurrences,
+                &mut silent(),
+                &mut report,
+            );
+            (data, report, count)
+        };
+
+        let (data, report, count) = patch(true);
+        assert_eq!(count, 3);
+        assert_eq!(report.applied_count(), 3);
+        for (i, chunk) in data.chunks(copy.len()).enumerate() {
+            assert_eq!(chunk, &key_config.rsa_modulus()[..copy.len()]);
+            assert_eq!(report.patches_applied[i].offset, Some(i * copy.len()));
+        }
+
+        // By default only the first copy is replaced
+        let (data, _, count) = patch(false);
+        assert_eq!(count, 1);
+        assert_eq!(&data[copy.len()..], &original[copy.len()..]);
+    }
+}
@@ -0,0 +1,110 @@
+use super::{
+    PatchContext, PatchStep, StepResult, applied_record, patch_capturing, patch_later_occurrences,
+    print_patch_bytes, unapplied_record, url_replacement,
+};
+use crate::patterns::{version_url_pattern, version_url_v2_pattern, version_url_v3_pattern};
+use crate::report::PatchStatus;
+use crate::trinity::{get_unified_api_url, get_version_url};
+
+/// Point the version URL at the configured CDN, trying the v1, v2 and v3 patterns in turn.
+///
+/// Patching the v3 unified API URL sets [`PatchContext::unified_api_patched`],
+/// since that endpoint also serves the CDNs list.
+pub struct VersionUrlStep;
+
+impl PatchStep for VersionUrlStep {
+    fn name(&self) -> &str {
+        "Version URL"
+    }
+
+    fn apply(&self, ctx: &mut PatchContext<'_>) -> StepResult {
+        if ctx.verbose
+            && let Some(port) = ctx.cdn_port
+        {
+            ctx.reporter
+                .detail(&format!("  ℹ CDN URLs use port {}", port));
+        }
+        let build_num = ctx.version.map(|v| v.build as u32);
+        let default_url = get_version_url(build_num, None, None, &ctx.version_url_config);
+        let v1_v2_url = ctx.version_url.as_deref().unwrap_or(&default_url);
+        let unified_api_url = get_unified_api_url(build_num);
+        let v3_url = ctx
+            .cdns_url_v3
+            .as_deref()
+            .or(ctx.version_url.as_deref())
+            .unwrap_or(&unified_api_url);
+
+        let mut version_url_patched = None;
+        let mut version_url_pattern_name = "";
+        for (pattern, url, pattern_name) in [
+            (version_url_pattern(), v1_v2_url, "v1"),
+            (version_url_v2_pattern(), v1_v2_url, "v2"),
+            // WoW Classic 1.15.8+ unified API
+            (version_url_v3_pattern(), v3_url, "v3 (unified API)"),
+        ] {
+            let replacement = url_replacement(&ctx.data, pattern, url, ctx.cdn_port)?;
+            if let Ok((offset, before)) = patch_capturing(&mut ctx.data, pattern, &replacement) {
+                version_url_patched = Some((offset, before, pattern, replacement));
+                version_url_pattern_name = pattern_name;
+                break;
+            }
+        }
+
+        let used_unified_api = version_url_pattern_name.contains("v3");
+        ctx.unified_api_patched = used_unified_api;
+
+        let Some((offset, before, pattern, replacement)) = version_url_patched else {
+            if ctx.verbose {
+                ctx.reporter.detail(
+                    "  ⚠ Version URL pattern not found (tried v1, v2, and v3, may be custom build)",
+                );
+            }
+            ctx.report
+                .push(unapplied_record(self.name(), PatchStatus::Missing));
+            return Ok(PatchStatus::Missing);
+        };
+
+        if ctx.verbose {
+            let custom_url = if used_unified_api {
+                ctx.cdns_url_v3.as_deref().or(ctx.version_url.as_deref())
+            } else {
+                ctx.version_url.as_deref()
+            };
+            if let Some(custom_url) = custom_url {
+                ctx.reporter.detail(&format!(
+                    "  ✓ Version URL patched → Custom CDN ({}, {} pattern)",
+                    custom_url, version_url_pattern_name
+                ));
+            } else if used_unified_api {
+                ctx.reporter.detail(&format!(
+                    "  ✓ API URL patched → Arctium CDN ({} pattern, handles versions+cdns)",
+                    version_url_pattern_name
+                ));
+            } else {
+                ctx.reporter.detail(&format!(
+                    "  ✓ Version URL patched → Arctium CDN ({} pattern)",
+                    version_url_pattern_name
+                ));
+            }
+            print_patch_bytes(ctx.reporter, &ctx.data, offset, &before, "Version URL");
+        }
+        ctx.report
+            .push(applied_record(&ctx.data, self.name(), offset, before));
+        if ctx.patch_all_occurrences {
+            patch_later_occurrences(
+                &mut ctx.data,
+                ctx.reporter,
+                &mut ctx.report,
+                self.name(),
+                pattern,
+                &replacement,
+                offset,
+            );
+        }
+        Ok(PatchStatus::Applied)
+    }
+
+    fn is_required(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,130 @@
+use crate::errors::{ErrorCategory, WowPatcherError};
+use crate::patterns::{PATTERN_NAMES, PatternScanResult, find_in_binary};
+use std::fs;
+use std::path::Path;
+
+/// Patterns that must be found for patching to succeed. Each group is
+/// satisfied by any one of its patterns (the RSA modulus has three variants).
+const REQUIRED_PATTERN_GROUPS: &[&[&str]] = &[
+    &["portal"],
+    &[
+        "connect_to_modulus",
+        "signature_modulus",
+        "crypto_rsa_modulus",
+    ],
+];
+
+/// Outcome of checking a binary for the patterns the patcher needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryTestStatus {
+    /// All required patterns were found in patchable sections
+    Patchable,
+    /// A required pattern was not found
+    MissingPattern,
+    /// A required pattern was found outside a patchable section
+    NonPatchableSection,
+}
+
+/// Scan a WoW executable and print the status of every known pattern.
+pub fn execute_test_binary(input_path: &Path) -> Result<BinaryTestStatus, WowPatcherError> {
+    let data = fs::read(input_path).map_err(|e| {
+        WowPatcherError::wrap(
+            ErrorCategory::FileOperationError,
+            "Failed to read WoW executable file",
+            e,
+        )
+        .with_context("path", input_path.display().to_string())
+    })?;
+
+    let scan = find_in_binary(&data);
+
+    println!("{:<22} Status", "Pattern");
+    for name in PATTERN_NAMES {
+        match (scan.found.get(name), scan.sections.get(name)) {
+            (Some(offset), Some(section)) if section.is_patchable => {
+                println!(
+                    "{:<22} ✓ found at 0x{:x} in '{}'",
+                    name, offset, section.name
+                )
+            }
+            (Some(offset), Some(section)) => println!(
+                "{:<22} ⚠ found at 0x{:x} in non-patchable section '{}'",
+                name, offset, section.name
+            ),
+            (Some(offset), None) => {
+                println!("{:<22} ⚠ found at 0x{:x} in unknown section", name, offset)
+            }
+            (None, _) => println!("{:<22} ✗ not found", name),
+        }
+    }
+
+    Ok(required_pattern_status(&scan))
+}
+
+/// Classify a scan by whether its required patterns can be patched.
+fn required_pattern_status(scan: &PatternScanResult) -> BinaryTestStatus {
+    let mut status = BinaryTestStatus::Patchable;
+
+    for group in REQUIRED_PATTERN_GROUPS {
+        let found: Vec<&str> = group
+            .iter()
+            .copied()
+            .filter(|name| scan.found.contains_key(name))
+            .collect();
+
+        if found.is_empty() {
+            return BinaryTestStatus::MissingPattern;
+        }
+
+        let patchable = found.iter().any(|name| {
+            scan.sections
+                .get(name)
+                .is_some_and(|section| section.is_patchable)
+        });
+        if !patchable {
+            status = BinaryTestStatus::NonPatchableSection;
+        }
+    }
+
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::SectionInfo;
+
+    fn section(name: &str, is_patchable: bool) -> SectionInfo {
+        SectionInfo {
+            name: name.to_string(),
+            virtual_address: 0,
+            virtual_size: 0,
+            file_offset: 0,
+            raw_size: 0,
+            is_patchable,
+        }
+    }
+
+    #[test]
+    fn test_required_pattern_status() {
+        let mut scan = PatternScanResult::default();
+        scan.found.insert("portal", 0x100);
+        scan.sections.insert("portal", section(".rdata", true));
+        assert_eq!(
+            required_pattern_status(&scan),
+            BinaryTestStatus::MissingPattern
+        );
+
+        scan.found.insert("signature_modulus", 0x200);
+        scan.sections
+            .insert("signature_modulus", section(".text", false));
+        assert_eq!(
+            required_pattern_status(&scan),
+            BinaryTestStatus::NonPatchableSection
+        );
+
+        scan.sections
+            .insert("signature_modulus", section(".rdata", true));
+        assert_eq!(required_pattern_status(&scan), BinaryTestStatus::Patchable);
+    }
+}
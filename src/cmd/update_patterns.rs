@@ -0,0 +1,156 @@
+use crate::binary::{check_offset_section, find_string_references};
+use crate::errors::{ErrorCategory, WowPatcherError};
+use crate::patterns::{DiscoveredPattern, DiscoveredPatternType, auto_discover};
+use std::fs;
+use std::path::Path;
+
+/// Host shared by the portal and CDN URLs, listed to help locate URL patterns in new builds
+const URL_HOST: &str = "battle.net";
+
+/// Run `auto_discover` on a known-good binary and print the leading bytes of each candidate.
+///
+/// Every reference to `battle.net` is listed afterwards with the string
+/// around it, to help locate the URL patterns of new builds.
+///
+/// With `emit_hex` the best candidate of each type is also printed as Rust
+/// source that can be pasted into `src/patterns/mod.rs`. The output is a
+/// starting point for maintainers; every candidate must be checked by hand.
+pub fn execute_update_patterns(input_path: &Path, emit_hex: bool) -> Result<(), WowPatcherError> {
+    let data = fs::read(input_path).map_err(|e| {
+        WowPatcherError::wrap(
+            ErrorCategory::FileOperationError,
+            "Failed to read WoW executable file",
+            e,
+        )
+        .with_context("path", input_path.display().to_string())
+    })?;
+
+    let discovered = auto_discover(&data);
+    if discovered.is_empty() {
+        return Err(WowPatcherError::new(
+            ErrorCategory::PatchingError,
+            "No RSA modulus or Ed25519 key candidates found",
+        )
+        .with_context("path", input_path.display().to_string()));
+    }
+
+    println!("Candidates in {}:", input_path.display());
+    for candidate in &discovered {
+        println!(
+            "  {:<18} 0x{:08x}  {:<14} {:.2}  {}",
+            candidate.pattern_type.to_string(),
+            candidate.file_offset,
+            candidate.section_name.as_deref().unwrap_or("-"),
+            candidate.confidence,
+            rust_array_literal(candidate)
+        );
+    }
+
+    let references = find_string_references(&data, URL_HOST);
+    if !references.is_empty() {
+        println!();
+        println!("References to {}:", URL_HOST);
+        for offset in references {
+            println!(
+                "  0x{:08x}  {:<14} {}",
+                offset,
+                check_offset_section(&data, offset).map_or_else(|| "-".to_string(), |s| s.name),
+                enclosing_string(&data, offset)
+            );
+        }
+    }
+
+    if emit_hex {
+        println!();
+        println!(
+            "// Generated by `wow-patcher update-patterns {} --emit-hex`, verify before committing",
+            input_path.display()
+        );
+        for (pattern_type, function, static_name) in [
+            (
+                DiscoveredPatternType::RsaModulus,
+                "connect_to_modulus_pattern",
+                "CONNECT_TO_MODULUS_PATTERN",
+            ),
+            (
+                DiscoveredPatternType::Ed25519PublicKey,
+                "crypto_ed_public_key_pattern",
+                "CRYPTO_ED_PUBLIC_KEY_PATTERN",
+            ),
+        ] {
+            // Candidates are sorted by confidence, so the first one is the best
+            if let Some(best) = discovered.iter().find(|d| d.pattern_type == pattern_type) {
+                println!();
+                println!("pub fn {}() -> &'static Pattern {{", function);
+                println!(
+                    "    {}.get_or_init(|| {})",
+                    static_name,
+                    rust_array_literal(best)
+                );
+                println!("}}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The NUL-terminated string around `offset`, cut to 128 bytes on either side.
+fn enclosing_string(data: &[u8], offset: usize) -> String {
+    const CONTEXT: usize = 128;
+    let window_start = offset.saturating_sub(CONTEXT);
+    let start = data[window_start..offset]
+        .iter()
+        .rposition(|&b| b == 0)
+        .map_or(window_start, |i| window_start + i + 1);
+    let limit = offset.saturating_add(CONTEXT).min(data.len());
+    let end = data[offset..limit]
+        .iter()
+        .position(|&b| b == 0)
+        .map_or(limit, |i| offset + i);
+    String::from_utf8_lossy(&data[start..end]).into_owned()
+}
+
+/// Format the leading bytes of a candidate as a `vec![0x.., ...]` literal.
+fn rust_array_literal(candidate: &DiscoveredPattern) -> String {
+    let bytes = hex::decode(&candidate.first_8_bytes_hex).unwrap_or_default();
+    let items: Vec<String> = bytes.iter().map(|b| format!("0x{:02X}", b)).collect();
+    format!("vec![{}]", items.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enclosing_string() {
+        let data = b"\0\0http://%s.patch.battle.net:1119/%s/cdns\0.actual.battle.net";
+        let references = find_string_references(data, URL_HOST);
+        assert_eq!(
+            enclosing_string(data, references[0]),
+            "http://%s.patch.battle.net:1119/%s/cdns"
+        );
+        assert_eq!(enclosing_string(data, references[1]), ".actual.battle.net");
+
+        // Long strings are cut off
+        let mut data = vec![b'a'; 600];
+        data.extend_from_slice(b"battle.net");
+        data.extend_from_slice(&[b'a'; 600]);
+        assert_eq!(enclosing_string(&data, 600).len(), 256);
+    }
+
+    #[test]
+    fn test_rust_array_literal() {
+        let candidate = DiscoveredPattern {
+            pattern_type: DiscoveredPatternType::RsaModulus,
+            file_offset: 0,
+            first_8_bytes_hex: "91d59bb7d4e183a5".to_string(),
+            section_name: None,
+            confidence: 1.0,
+        };
+        assert_eq!(
+            rust_array_literal(&candidate),
+            "vec![0x91, 0xD5, 0x9B, 0xB7, 0xD4, 0xE1, 0x83, 0xA5]"
+        );
+    }
+}
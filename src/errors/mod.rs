@@ -8,6 +8,10 @@ pub enum ErrorCategory {
     ValidationError,
     PatchingError,
     PlatformError,
+    /// Invalid URL format or unreachable CDN
+    NetworkError,
+    /// Malformed cryptographic key material
+    CryptoError,
 }
 
 impl fmt::Display for ErrorCategory {
@@ -17,6 +21,8 @@ impl fmt::Display for ErrorCategory {
             ErrorCategory::ValidationError => write!(f, "Validation"),
             ErrorCategory::PatchingError => write!(f, "Patching"),
             ErrorCategory::PlatformError => write!(f, "Platform"),
+            ErrorCategory::NetworkError => write!(f, "Network"),
+            ErrorCategory::CryptoError => write!(f, "Crypto"),
         }
     }
 }
@@ -27,6 +33,8 @@ pub struct WowPatcherError {
     pub message: String,
     pub cause: Option<Box<dyn Error + Send + Sync>>,
     pub context: HashMap<String, Box<dyn std::any::Any + Send + Sync>>,
+    /// Help text telling the user how to resolve the error
+    pub suggestion: Option<String>,
 }
 
 impl WowPatcherError {
@@ -36,6 +44,7 @@ impl WowPatcherError {
             message: message.into(),
             cause: None,
             context: HashMap::new(),
+            suggestion: None,
         }
     }
 
@@ -49,6 +58,7 @@ impl WowPatcherError {
             message: message.into(),
             cause: Some(Box::new(cause)),
             context: HashMap::new(),
+            suggestion: None,
         }
     }
 
@@ -64,13 +74,93 @@ impl WowPatcherError {
     pub fn get_context(&self, key: &str) -> Option<&(dyn std::any::Any + Send + Sync)> {
         self.context.get(key).map(|v| v.as_ref())
     }
+
+    /// Record the file offset the error refers to, see [`WowPatcherError::offset`].
+    pub fn with_offset(self, offset: usize) -> Self {
+        self.with_context("offset", offset)
+    }
+
+    /// The file offset recorded with [`WowPatcherError::with_offset`].
+    pub fn offset(&self) -> Option<usize> {
+        self.get_context("offset")?.downcast_ref::<usize>().copied()
+    }
+
+    pub fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+
+    pub fn suggestion(&self) -> Option<&str> {
+        self.suggestion.as_deref()
+    }
+
+    /// Copy this error, for reporting one failure in several places.
+    ///
+    /// Context values of the types this crate records (`String`, `&str`,
+    /// `usize`, `i32` and `bool`) are copied, others are dropped. The cause
+    /// becomes an I/O error with the same message, keeping the kind of an
+    /// I/O cause.
+    pub fn duplicate(&self) -> Self {
+        let cause = self.cause.as_ref().map(|cause| {
+            let kind = cause
+                .downcast_ref::<std::io::Error>()
+                .map_or(std::io::ErrorKind::Other, std::io::Error::kind);
+            Box::new(std::io::Error::new(kind, cause.to_string())) as Box<dyn Error + Send + Sync>
+        });
+        let context = self
+            .context
+            .iter()
+            .filter_map(|(key, value)| {
+                Some((key.clone(), duplicate_context_value(value.as_ref())?))
+            })
+            .collect();
+        Self {
+            category: self.category,
+            message: self.message.clone(),
+            cause,
+            context,
+            suggestion: self.suggestion.clone(),
+        }
+    }
+
+    /// Iterate over this error followed by each of its causes
+    pub fn error_chain(&self) -> ErrorChainIter<'_> {
+        ErrorChainIter::new(self)
+    }
+
+    /// The category, message and cause, without the suggestion
+    pub fn summary(&self) -> String {
+        match &self.cause {
+            Some(cause) => format!("[{}] {}: {}", self.category, self.message, cause),
+            None => format!("[{}] {}", self.category, self.message),
+        }
+    }
+}
+
+/// Copy a context value of one of the types [`WowPatcherError::duplicate`] supports.
+fn duplicate_context_value(
+    value: &(dyn std::any::Any + Send + Sync),
+) -> Option<Box<dyn std::any::Any + Send + Sync>> {
+    if let Some(value) = value.downcast_ref::<String>() {
+        Some(Box::new(value.clone()))
+    } else if let Some(value) = value.downcast_ref::<&'static str>() {
+        Some(Box::new(*value))
+    } else if let Some(value) = value.downcast_ref::<usize>() {
+        Some(Box::new(*value))
+    } else if let Some(value) = value.downcast_ref::<i32>() {
+        Some(Box::new(*value))
+    } else {
+        value
+            .downcast_ref::<bool>()
+            .map(|value| Box::new(*value) as Box<dyn std::any::Any + Send + Sync>)
+    }
 }
 
 impl fmt::Display for WowPatcherError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "[{}] {}", self.category, self.message)?;
-        if let Some(cause) = &self.cause {
-            write!(f, ": {}", cause)?;
+        write!(f, "{}", self.summary())?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n  Hint: {}", suggestion)?;
         }
         Ok(())
     }
@@ -84,6 +174,38 @@ impl Error for WowPatcherError {
     }
 }
 
+/// Errors are equal when their category and message match.
+///
+/// The cause, context and suggestion are ignored, so tests can compare a
+/// returned error against one built with [`WowPatcherError::new`].
+impl PartialEq for WowPatcherError {
+    fn eq(&self, other: &Self) -> bool {
+        self.category == other.category && self.message == other.message
+    }
+}
+
+/// Iterator over an error and its chain of sources
+#[derive(Debug, Clone)]
+pub struct ErrorChainIter<'a> {
+    next: Option<&'a (dyn Error + 'static)>,
+}
+
+impl<'a> ErrorChainIter<'a> {
+    pub fn new(error: &'a (dyn Error + 'static)) -> Self {
+        Self { next: Some(error) }
+    }
+}
+
+impl<'a> Iterator for ErrorChainIter<'a> {
+    type Item = &'a (dyn Error + 'static);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        self.next = current.source();
+        Some(current)
+    }
+}
+
 pub fn new_file_error(
     message: impl Into<String>,
     cause: impl Error + Send + Sync + 'static,
@@ -111,10 +233,16 @@ pub fn new_patching_error(
 ) -> WowPatcherError {
     WowPatcherError::wrap(ErrorCategory::PatchingError, message, cause)
         .with_context("pattern", pattern.into())
-        .with_context(
-            "suggestion",
-            "This may be an unsupported WoW version or a pre-patched executable".to_string(),
-        )
+        .with_suggestion("This may be an unsupported WoW version or a pre-patched executable")
+}
+
+/// A required pattern that is absent from the data being patched.
+///
+/// The `pattern_missing` context tells these apart from other patching
+/// failures, so the CLI can report an unsupported build with its own exit code.
+pub fn new_pattern_missing_error(message: impl Into<String>) -> WowPatcherError {
+    WowPatcherError::new(ErrorCategory::PatchingError, message)
+        .with_context("pattern_missing", true)
 }
 
 pub fn new_platform_error(
@@ -140,6 +268,8 @@ mod tests {
         assert_eq!(ErrorCategory::ValidationError.to_string(), "Validation");
         assert_eq!(ErrorCategory::PatchingError.to_string(), "Patching");
         assert_eq!(ErrorCategory::PlatformError.to_string(), "Platform");
+        assert_eq!(ErrorCategory::NetworkError.to_string(), "Network");
+        assert_eq!(ErrorCategory::CryptoError.to_string(), "Crypto");
     }
 
     #[test]
@@ -184,6 +314,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_duplicate() {
+        let err = WowPatcherError::wrap(
+            ErrorCategory::FileOperationError,
+            "write failed",
+            std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied"),
+        )
+        .with_context("already_patched", true)
+        .with_context("path", "Wow.exe".to_string())
+        .with_offset(0x40)
+        .with_suggestion("hint");
+
+        let copy = err.duplicate();
+        assert_eq!(copy, err);
+        assert_eq!(copy.to_string(), err.to_string());
+        assert_eq!(copy.offset(), Some(0x40));
+        assert!(copy.get_context("already_patched").is_some());
+        assert_eq!(
+            copy.get_context("path")
+                .and_then(|v| v.downcast_ref::<String>())
+                .map(String::as_str),
+            Some("Wow.exe")
+        );
+        let kind = copy
+            .source()
+            .and_then(|cause| cause.downcast_ref::<std::io::Error>())
+            .map(std::io::Error::kind);
+        assert_eq!(kind, Some(std::io::ErrorKind::PermissionDenied));
+    }
+
+    #[test]
+    fn test_error_eq() {
+        let err = WowPatcherError::wrap(
+            ErrorCategory::PatchingError,
+            "pattern not found in data",
+            std::io::Error::other("cause"),
+        )
+        .with_context("pattern", "portal".to_string())
+        .with_suggestion("hint");
+        assert_eq!(
+            err,
+            WowPatcherError::new(ErrorCategory::PatchingError, "pattern not found in data")
+        );
+        assert_ne!(
+            err,
+            WowPatcherError::new(ErrorCategory::ValidationError, "pattern not found in data")
+        );
+        assert_ne!(
+            err,
+            WowPatcherError::new(ErrorCategory::PatchingError, "other message")
+        );
+
+        let mut data = vec![0u8; 4];
+        assert_eq!(
+            crate::binary::patch(&mut data, &vec![1, 2], &[3, 4]).unwrap_err(),
+            WowPatcherError::new(ErrorCategory::PatchingError, "pattern not found in data")
+        );
+    }
+
     #[test]
     fn test_with_context() {
         let err = WowPatcherError::new(ErrorCategory::ValidationError, "test error")
@@ -209,6 +398,19 @@ mod tests {
         assert!(err.get_context("missing").is_none());
     }
 
+    #[test]
+    fn test_with_offset() {
+        let err = WowPatcherError::new(ErrorCategory::PatchingError, "test");
+        assert_eq!(err.offset(), None);
+
+        let err = err.with_offset(0x1234);
+        assert_eq!(err.offset(), Some(0x1234));
+
+        // A non-usize value under the same key is not an offset
+        let err = err.with_context("offset", "0x1234");
+        assert_eq!(err.offset(), None);
+    }
+
     #[test]
     fn test_new_file_error() {
         let base_err =
@@ -258,16 +460,26 @@ mod tests {
             .and_then(|v| v.downcast_ref::<String>());
         assert_eq!(pattern, Some(&"portal_pattern".to_string()));
 
-        let suggestion = patch_err
-            .get_context("suggestion")
-            .and_then(|v| v.downcast_ref::<String>());
+        let suggestion = patch_err.suggestion();
         assert!(suggestion.is_some());
         assert!(suggestion.unwrap().contains("unsupported WoW version"));
     }
 
+    #[test]
+    fn test_suggestion_display() {
+        let err = WowPatcherError::new(ErrorCategory::PatchingError, "pattern not found")
+            .with_suggestion("Try a supported client build");
+
+        assert_eq!(err.summary(), "[Patching] pattern not found");
+        assert_eq!(
+            err.to_string(),
+            "[Patching] pattern not found\n  Hint: Try a supported client build"
+        );
+    }
+
     #[test]
     fn test_new_platform_error() {
-        let base_err = std::io::Error::new(std::io::ErrorKind::Other, "command failed");
+        let base_err = std::io::Error::other("command failed");
         let plat_err = new_platform_error("codesign failed", base_err, "remove_signature");
 
         assert_eq!(plat_err.category, ErrorCategory::PlatformError);
@@ -291,11 +503,28 @@ mod tests {
         let level2 = WowPatcherError::wrap(
             ErrorCategory::ValidationError,
             "validation error",
-            std::io::Error::new(std::io::ErrorKind::Other, level1.to_string()),
+            std::io::Error::other(level1.to_string()),
         );
 
         let err_msg = level2.to_string();
         assert!(err_msg.contains("Validation"));
         assert!(err_msg.contains("validation error"));
     }
+
+    #[test]
+    fn test_error_chain() {
+        let root_err = std::io::Error::new(std::io::ErrorKind::NotFound, "root cause");
+        let level1 =
+            WowPatcherError::wrap(ErrorCategory::FileOperationError, "file error", root_err);
+        let level2 = WowPatcherError::wrap(ErrorCategory::PatchingError, "patch error", level1);
+
+        let chain: Vec<String> = level2.error_chain().map(|e| e.to_string()).collect();
+        assert_eq!(chain.len(), 3);
+        assert!(chain[0].starts_with("[Patching] patch error"));
+        assert!(chain[1].starts_with("[File Operation] file error"));
+        assert_eq!(chain[2], "root cause");
+
+        let single = WowPatcherError::new(ErrorCategory::ValidationError, "invalid");
+        assert_eq!(single.error_chain().count(), 1);
+    }
 }
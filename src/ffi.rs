@@ -0,0 +1,124 @@
+//! C API for embedding the patcher in non-Rust applications.
+//!
+//! Building with the `ffi` feature generates `wow_patcher.h` in
+//! `target/include`. Buffers returned by [`wow_patcher_patch_bytes`] must be
+//! released with [`wow_patcher_free`].
+
+use crate::keys::KeyConfig;
+use crate::patcher::Patcher;
+use crate::trinity::{CRYPTO_ED25519_PUBLIC_KEY, RSA_MODULUS};
+use std::ptr;
+use std::slice;
+
+/// Apply all standard patches to an in-memory executable.
+///
+/// `rsa_modulus` must point to 256 bytes and `ed25519_key` to 32 bytes;
+/// either may be null to use the TrinityCore key. On success the patched
+/// executable is returned and its length stored in `out_len`. On failure
+/// null is returned and `out_len` is set to 0.
+///
+/// # Safety
+///
+/// `data` must be valid for reads of `len` bytes, the key pointers must be
+/// null or valid for reads of their key size, and `out_len` must be valid
+/// for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wow_patcher_patch_bytes(
+    data: *const u8,
+    len: usize,
+    rsa_modulus: *const u8,
+    ed25519_key: *const u8,
+    out_len: *mut usize,
+) -> *mut u8 {
+    if out_len.is_null() {
+        return ptr::null_mut();
+    }
+    // SAFETY: checked for null above, the caller guarantees it is writable
+    unsafe { *out_len = 0 };
+    if data.is_null() {
+        return ptr::null_mut();
+    }
+
+    // SAFETY: the caller guarantees the sizes documented above
+    let (input, rsa, ed25519) = unsafe {
+        (
+            slice::from_raw_parts(data, len),
+            key_or_default(rsa_modulus, RSA_MODULUS),
+            key_or_default(ed25519_key, CRYPTO_ED25519_PUBLIC_KEY),
+        )
+    };
+    let Ok(key_config) = KeyConfig::new(rsa, ed25519) else {
+        return ptr::null_mut();
+    };
+
+    let mut patcher = Patcher::new("");
+    patcher.key_config = Some(key_config);
+    match patcher.patch_bytes(input) {
+        Ok(patched) => {
+            let patched = patched.into_boxed_slice();
+            // SAFETY: checked for null above
+            unsafe { *out_len = patched.len() };
+            Box::into_raw(patched) as *mut u8
+        }
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Free a buffer returned by [`wow_patcher_patch_bytes`].
+///
+/// # Safety
+///
+/// `ptr` must be null or a pointer returned by [`wow_patcher_patch_bytes`]
+/// together with the length it reported, and must not be freed twice.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wow_patcher_free(ptr: *mut u8, len: usize) {
+    if ptr.is_null() {
+        return;
+    }
+    // SAFETY: the caller passes back a buffer allocated as a boxed slice of `len` bytes
+    drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(ptr, len)) });
+}
+
+/// Borrow a key of `default.len()` bytes from `key`, or `default` when it is null.
+///
+/// # Safety
+///
+/// `key` must be null or valid for reads of `default.len()` bytes.
+unsafe fn key_or_default(key: *const u8, default: &[u8]) -> &[u8] {
+    if key.is_null() {
+        default
+    } else {
+        // SAFETY: guaranteed by the caller
+        unsafe { slice::from_raw_parts(key, default.len()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::test_helpers::create_test_executable;
+
+    #[test]
+    fn test_patch_bytes_round_trip() {
+        let input = create_test_executable();
+        let mut out_len = 0;
+        let patched = unsafe {
+            wow_patcher_patch_bytes(
+                input.as_ptr(),
+                input.len(),
+                ptr::null(),
+                ptr::null(),
+                &mut out_len,
+            )
+        };
+        assert!(!patched.is_null());
+        assert_eq!(out_len, input.len());
+        unsafe { wow_patcher_free(patched, out_len) };
+
+        let patched = unsafe {
+            wow_patcher_patch_bytes(input.as_ptr(), 16, ptr::null(), ptr::null(), &mut out_len)
+        };
+        assert!(patched.is_null());
+        assert_eq!(out_len, 0);
+    }
+}
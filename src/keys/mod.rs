@@ -1,15 +1,76 @@
 use crate::errors::{ErrorCategory, WowPatcherError};
 use crate::trinity::{CRYPTO_ED25519_PUBLIC_KEY, RSA_MODULUS};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// Named key sets, keyed by lowercase preset name
+type PresetRegistry = Mutex<HashMap<String, KeyConfig>>;
+
+static PRESET_REGISTRY: OnceLock<PresetRegistry> = OnceLock::new();
+
+/// Registry of named key presets, created with the built-in presets on first use.
+fn preset_registry() -> &'static PresetRegistry {
+    PRESET_REGISTRY.get_or_init(|| {
+        let mut presets = HashMap::new();
+        presets.insert("trinitycore".to_string(), KeyConfig::trinity_core());
+        // AzerothCore does not publish a key set of its own and uses the TrinityCore keys
+        let azeroth_core = KeyConfig {
+            source: KeySource::AzerothCore,
+            ..KeyConfig::trinity_core()
+        };
+        presets.insert("azerothcore".to_string(), azeroth_core);
+        Mutex::new(presets)
+    })
+}
+
+/// Where the keys of a [`KeyConfig`] came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySource {
+    /// Built-in TrinityCore keys
+    TrinityCore,
+    /// Built-in AzerothCore preset
+    AzerothCore,
+    /// Preset registered with [`KeyConfig::register_preset`]
+    Preset(String),
+    /// Loaded from a binary key file
+    File,
+    /// Parsed from a hex string
+    Hex,
+    /// Decoded from a base64 string
+    Base64,
+    /// Raw key bytes passed to [`KeyConfig::custom`] or [`KeyConfig::new`]
+    Custom,
+}
+
+impl std::fmt::Display for KeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeySource::TrinityCore => write!(f, "built-in TrinityCore"),
+            KeySource::AzerothCore => write!(f, "built-in AzerothCore"),
+            KeySource::Preset(name) => write!(f, "preset '{}'", name),
+            KeySource::File => write!(f, "from file"),
+            KeySource::Hex => write!(f, "from hex string"),
+            KeySource::Base64 => write!(f, "from base64 string"),
+            KeySource::Custom => write!(f, "custom bytes"),
+        }
+    }
+}
 
 /// Configuration for cryptographic keys used in patching
 #[derive(Debug, Clone)]
 pub struct KeyConfig {
     /// RSA modulus (256 bytes) for authentication
     pub rsa_modulus: Vec<u8>,
-    /// Ed25519 public key (32 bytes) for modern authentication
+    /// Ed25519 public key (32 bytes) for modern authentication, empty to skip the Ed25519 patch
     pub ed25519_public_key: Vec<u8>,
+    /// Second RSA modulus (256 bytes) for the Signature slot in dual RSA mode
+    pub secondary_rsa_modulus: Option<Vec<u8>>,
+    /// Where the most recently loaded key came from
+    pub source: KeySource,
 }
 
 impl Default for KeyConfig {
@@ -24,6 +85,8 @@ impl KeyConfig {
         Self {
             rsa_modulus: RSA_MODULUS.to_vec(),
             ed25519_public_key: CRYPTO_ED25519_PUBLIC_KEY.to_vec(),
+            secondary_rsa_modulus: None,
+            source: KeySource::TrinityCore,
         }
     }
 
@@ -40,11 +103,89 @@ impl KeyConfig {
         let config = Self {
             rsa_modulus,
             ed25519_public_key,
+            secondary_rsa_modulus: None,
+            source: KeySource::Custom,
         };
         config.validate()?;
         Ok(config)
     }
 
+    /// Register a named key set for later use with [`KeyConfig::named_preset`].
+    ///
+    /// Names are case-insensitive. Registering an existing name replaces it.
+    /// The stored key set reports [`KeySource::Preset`] as its source.
+    pub fn register_preset(name: &str, mut config: KeyConfig) -> Result<(), WowPatcherError> {
+        if name.trim().is_empty() {
+            return Err(WowPatcherError::new(
+                ErrorCategory::ValidationError,
+                "Preset name cannot be empty",
+            ));
+        }
+        config.validate()?;
+
+        let name = name.trim().to_lowercase();
+        config.source = KeySource::Preset(name.clone());
+        preset_registry()
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(name, config);
+        Ok(())
+    }
+
+    /// Look up a key set by preset name, such as `trinitycore` or `azerothcore`.
+    pub fn named_preset(name: &str) -> Result<Self, WowPatcherError> {
+        let presets = preset_registry()
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(config) = presets.get(&name.trim().to_lowercase()) {
+            return Ok(config.clone());
+        }
+
+        drop(presets);
+        Err(WowPatcherError::new(
+            ErrorCategory::ValidationError,
+            format!("Unknown key preset '{}'", name),
+        )
+        .with_suggestion(format!(
+            "Available presets: {}",
+            Self::preset_names().join(", ")
+        )))
+    }
+
+    /// Names of all registered presets, sorted alphabetically.
+    pub fn preset_names() -> Vec<String> {
+        let mut names: Vec<String> = preset_registry()
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .keys()
+            .cloned()
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Names of the registered presets using the same keys as this configuration, sorted alphabetically.
+    ///
+    /// Built-in presets may share keys, so more than one name can match.
+    pub fn matching_presets(&self) -> Vec<String> {
+        let mut names: Vec<String> = preset_registry()
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .filter(|(_, preset)| self.same_keys(preset))
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Whether both configurations patch in the same keys, regardless of their source
+    fn same_keys(&self, other: &KeyConfig) -> bool {
+        self.rsa_modulus == other.rsa_modulus
+            && self.ed25519_public_key == other.ed25519_public_key
+            && self.secondary_rsa_modulus == other.secondary_rsa_modulus
+    }
+
     /// Load keys from files
     pub fn from_files<P: AsRef<Path>>(
         rsa_file: P,
@@ -74,7 +215,7 @@ impl KeyConfig {
 
         if rsa_data.len() != 256 {
             return Err(WowPatcherError::new(
-                ErrorCategory::ValidationError,
+                ErrorCategory::CryptoError,
                 format!(
                     "RSA modulus must be exactly 256 bytes, got {} bytes",
                     rsa_data.len()
@@ -83,6 +224,7 @@ impl KeyConfig {
         }
 
         self.rsa_modulus = rsa_data;
+        self.source = KeySource::File;
         self.validate()?;
         Ok(self)
     }
@@ -105,7 +247,7 @@ impl KeyConfig {
 
         if ed25519_data.len() != 32 {
             return Err(WowPatcherError::new(
-                ErrorCategory::ValidationError,
+                ErrorCategory::CryptoError,
                 format!(
                     "Ed25519 public key must be exactly 32 bytes, got {} bytes",
                     ed25519_data.len()
@@ -114,11 +256,16 @@ impl KeyConfig {
         }
 
         self.ed25519_public_key = ed25519_data;
+        self.source = KeySource::File;
         self.validate()?;
         Ok(self)
     }
 
     /// Load RSA modulus from a hex string
+    ///
+    /// Characters other than hex digits are ignored, so whitespace, `:`
+    /// separators and the `\r\n` line endings of files saved on Windows are
+    /// all accepted.
     pub fn with_rsa_from_hex(mut self, hex_str: &str) -> Result<Self, WowPatcherError> {
         let cleaned_hex = hex_str
             .chars()
@@ -127,7 +274,7 @@ impl KeyConfig {
 
         if cleaned_hex.len() != 512 {
             return Err(WowPatcherError::new(
-                ErrorCategory::ValidationError,
+                ErrorCategory::CryptoError,
                 format!(
                     "RSA modulus hex string must be exactly 512 hex characters (256 bytes), got {} characters",
                     cleaned_hex.len()
@@ -137,18 +284,22 @@ impl KeyConfig {
 
         let rsa_data = hex::decode(&cleaned_hex).map_err(|e| {
             WowPatcherError::wrap(
-                ErrorCategory::ValidationError,
+                ErrorCategory::CryptoError,
                 "Invalid hex format for RSA modulus",
                 e,
             )
         })?;
 
         self.rsa_modulus = rsa_data;
+        self.source = KeySource::Hex;
         self.validate()?;
         Ok(self)
     }
 
     /// Load Ed25519 public key from a hex string
+    ///
+    /// Like [`KeyConfig::with_rsa_from_hex`], anything but hex digits,
+    /// including `\r\n` line endings, is ignored.
     pub fn with_ed25519_from_hex(mut self, hex_str: &str) -> Result<Self, WowPatcherError> {
         let cleaned_hex = hex_str
             .chars()
@@ -157,7 +308,7 @@ impl KeyConfig {
 
         if cleaned_hex.len() != 64 {
             return Err(WowPatcherError::new(
-                ErrorCategory::ValidationError,
+                ErrorCategory::CryptoError,
                 format!(
                     "Ed25519 public key hex string must be exactly 64 hex characters (32 bytes), got {} characters",
                     cleaned_hex.len()
@@ -167,22 +318,102 @@ impl KeyConfig {
 
         let ed25519_data = hex::decode(&cleaned_hex).map_err(|e| {
             WowPatcherError::wrap(
-                ErrorCategory::ValidationError,
+                ErrorCategory::CryptoError,
                 "Invalid hex format for Ed25519 public key",
                 e,
             )
         })?;
 
         self.ed25519_public_key = ed25519_data;
+        self.source = KeySource::Hex;
         self.validate()?;
         Ok(self)
     }
 
+    /// Load RSA modulus from a standard base64 string
+    pub fn with_rsa_from_base64(mut self, b64: &str) -> Result<Self, WowPatcherError> {
+        let rsa_data = decode_base64(b64).ok_or_else(|| {
+            WowPatcherError::new(
+                ErrorCategory::CryptoError,
+                "Invalid base64 format for RSA modulus",
+            )
+        })?;
+
+        if rsa_data.len() != 256 {
+            return Err(WowPatcherError::new(
+                ErrorCategory::CryptoError,
+                format!(
+                    "RSA modulus must be exactly 256 bytes, got {} bytes",
+                    rsa_data.len()
+                ),
+            ));
+        }
+
+        self.rsa_modulus = rsa_data;
+        self.source = KeySource::Base64;
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Load Ed25519 public key from a standard base64 string
+    pub fn with_ed25519_from_base64(mut self, b64: &str) -> Result<Self, WowPatcherError> {
+        let ed25519_data = decode_base64(b64).ok_or_else(|| {
+            WowPatcherError::new(
+                ErrorCategory::CryptoError,
+                "Invalid base64 format for Ed25519 public key",
+            )
+        })?;
+
+        if ed25519_data.len() != 32 {
+            return Err(WowPatcherError::new(
+                ErrorCategory::CryptoError,
+                format!(
+                    "Ed25519 public key must be exactly 32 bytes, got {} bytes",
+                    ed25519_data.len()
+                ),
+            ));
+        }
+
+        self.ed25519_public_key = ed25519_data;
+        self.source = KeySource::Base64;
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Add a second RSA modulus for servers supporting old and new clients.
+    ///
+    /// In dual RSA mode the primary modulus replaces the ConnectTo key and the
+    /// secondary modulus replaces the Signature key.
+    pub fn with_secondary_rsa(mut self, modulus: Vec<u8>) -> Result<Self, WowPatcherError> {
+        self.secondary_rsa_modulus = Some(modulus);
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Patch only the RSA modulus and leave the client's Ed25519 key untouched.
+    ///
+    /// Clears the Ed25519 public key; an empty key means the Ed25519 patch is
+    /// skipped. Loading an Ed25519 key afterwards re-enables the patch.
+    pub fn with_only_rsa(mut self) -> Self {
+        self.ed25519_public_key = Vec::new();
+        self
+    }
+
+    /// Check whether the Ed25519 public key will be patched
+    pub fn patches_ed25519(&self) -> bool {
+        !self.ed25519_public_key.is_empty()
+    }
+
     /// Get the RSA modulus as a byte slice
     pub fn rsa_modulus(&self) -> &[u8] {
         &self.rsa_modulus
     }
 
+    /// Get the secondary RSA modulus, if one is configured
+    pub fn secondary_rsa_modulus(&self) -> Option<&[u8]> {
+        self.secondary_rsa_modulus.as_deref()
+    }
+
     /// Get the Ed25519 public key as a byte slice
     pub fn ed25519_public_key(&self) -> &[u8] {
         &self.ed25519_public_key
@@ -190,38 +421,20 @@ impl KeyConfig {
 
     /// Validate that the keys meet cryptographic requirements
     pub fn validate(&self) -> Result<(), WowPatcherError> {
-        // Validate RSA modulus
-        if self.rsa_modulus.len() != 256 {
-            return Err(WowPatcherError::new(
-                ErrorCategory::ValidationError,
-                format!(
-                    "RSA modulus must be exactly 256 bytes, got {}",
-                    self.rsa_modulus.len()
-                ),
-            ));
+        validate_rsa_modulus(&self.rsa_modulus, "RSA modulus")?;
+        if let Some(secondary) = &self.secondary_rsa_modulus {
+            validate_rsa_modulus(secondary, "Secondary RSA modulus")?;
         }
 
-        // Check that RSA modulus is not all zeros
-        if self.rsa_modulus.iter().all(|&b| b == 0) {
-            return Err(WowPatcherError::new(
-                ErrorCategory::ValidationError,
-                "RSA modulus cannot be all zeros",
-            ));
-        }
-
-        // Check that RSA modulus has reasonable entropy
-        let first_byte = self.rsa_modulus[0];
-        if self.rsa_modulus.iter().all(|&b| b == first_byte) {
-            return Err(WowPatcherError::new(
-                ErrorCategory::ValidationError,
-                "RSA modulus cannot contain all identical bytes",
-            ));
+        // An empty Ed25519 key is the RSA-only sentinel
+        if !self.patches_ed25519() {
+            return Ok(());
         }
 
         // Validate Ed25519 public key
         if self.ed25519_public_key.len() != 32 {
             return Err(WowPatcherError::new(
-                ErrorCategory::ValidationError,
+                ErrorCategory::CryptoError,
                 format!(
                     "Ed25519 public key must be exactly 32 bytes, got {}",
                     self.ed25519_public_key.len()
@@ -232,7 +445,7 @@ impl KeyConfig {
         // Check that Ed25519 key is not all zeros
         if self.ed25519_public_key.iter().all(|&b| b == 0) {
             return Err(WowPatcherError::new(
-                ErrorCategory::ValidationError,
+                ErrorCategory::CryptoError,
                 "Ed25519 public key cannot be all zeros",
             ));
         }
@@ -241,7 +454,7 @@ impl KeyConfig {
         let first_byte = self.ed25519_public_key[0];
         if self.ed25519_public_key.iter().all(|&b| b == first_byte) {
             return Err(WowPatcherError::new(
-                ErrorCategory::ValidationError,
+                ErrorCategory::CryptoError,
                 "Ed25519 public key cannot contain all identical bytes",
             ));
         }
@@ -249,23 +462,125 @@ impl KeyConfig {
         Ok(())
     }
 
+    /// Check that no key starts with one of the known search patterns.
+    ///
+    /// A key matching a pattern is found again by that pattern once it is
+    /// written, so a second run would patch the replacement key instead of
+    /// the original one. The error is a warning: patching once still works.
+    /// When the colliding pattern occurs in `data`, the error carries the
+    /// offset of its first match.
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error naming the first key and pattern that collide.
+    pub fn validate_against_binary(&self, data: &[u8]) -> Result<(), WowPatcherError> {
+        use crate::binary::DataExt;
+        use crate::patterns::{PATTERN_NAMES, pattern_by_name};
+
+        let mut keys = vec![("RSA modulus", self.rsa_modulus())];
+        if let Some(secondary) = self.secondary_rsa_modulus() {
+            keys.push(("Secondary RSA modulus", secondary));
+        }
+        if self.patches_ed25519() {
+            keys.push(("Ed25519 public key", self.ed25519_public_key()));
+        }
+
+        for (key_name, key) in keys {
+            for &pattern_name in PATTERN_NAMES {
+                let Some(pattern) = pattern_by_name(pattern_name) else {
+                    continue;
+                };
+                if pattern.is_empty() || key.find_pattern(pattern) != Some(0) {
+                    continue;
+                }
+                let mut err = WowPatcherError::new(
+                    ErrorCategory::ValidationError,
+                    format!(
+                        "{} starts with the {} search pattern, a second patch run would find the replacement key",
+                        key_name, pattern_name
+                    ),
+                )
+                .with_context("key_prefix", hex::encode(&key[..key.len().min(8)]))
+                .with_suggestion("Generate a different key pair before patching");
+                if let Some(offset) = data.find_pattern(pattern) {
+                    err = err.with_offset(offset);
+                }
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
     /// Check if this configuration uses the default TrinityCore keys
     pub fn is_trinity_core(&self) -> bool {
-        self.rsa_modulus == RSA_MODULUS && self.ed25519_public_key == CRYPTO_ED25519_PUBLIC_KEY
+        self.rsa_modulus == RSA_MODULUS
+            && (!self.patches_ed25519() || self.ed25519_public_key == CRYPTO_ED25519_PUBLIC_KEY)
     }
 
-    /// Display information about the keys (first 8 bytes for identification)
+    /// Display information about the keys (first 8 bytes for identification),
+    /// their source and the presets they match
     pub fn display_info(&self) -> String {
+        let ed25519 = if self.patches_ed25519() {
+            format!(
+                "{}... ({} bytes)",
+                hex::encode(&self.ed25519_public_key[..8]),
+                self.ed25519_public_key.len()
+            )
+        } else {
+            "not patched".to_string()
+        };
+        let presets = self.matching_presets();
+        let preset = if presets.is_empty() {
+            "none".to_string()
+        } else {
+            presets.join(", ")
+        };
         format!(
-            "RSA modulus: {}... ({} bytes), Ed25519 key: {}... ({} bytes)",
+            "RSA modulus: {}... ({} bytes), Ed25519 key: {}, source: {}, matching preset: {}",
             hex::encode(&self.rsa_modulus[..8]),
             self.rsa_modulus.len(),
-            hex::encode(&self.ed25519_public_key[..8]),
-            self.ed25519_public_key.len()
+            ed25519,
+            self.source,
+            preset
         )
     }
 }
 
+/// Decode standard padded base64, ignoring whitespace such as YAML line breaks.
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    let cleaned: String = input.split_ascii_whitespace().collect();
+    STANDARD.decode(cleaned).ok()
+}
+
+/// Validate the length and entropy of an RSA modulus
+fn validate_rsa_modulus(modulus: &[u8], label: &str) -> Result<(), WowPatcherError> {
+    if modulus.len() != 256 {
+        return Err(WowPatcherError::new(
+            ErrorCategory::CryptoError,
+            format!("{} must be exactly 256 bytes, got {}", label, modulus.len()),
+        ));
+    }
+
+    // Check that RSA modulus is not all zeros
+    if modulus.iter().all(|&b| b == 0) {
+        return Err(WowPatcherError::new(
+            ErrorCategory::CryptoError,
+            format!("{} cannot be all zeros", label),
+        ));
+    }
+
+    // Check that RSA modulus has reasonable entropy
+    let first_byte = modulus[0];
+    if modulus.iter().all(|&b| b == first_byte) {
+        return Err(WowPatcherError::new(
+            ErrorCategory::CryptoError,
+            format!("{} cannot contain all identical bytes", label),
+        ));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -323,7 +638,31 @@ mod tests {
         // Test that keys with all identical bytes are rejected
         let result = KeyConfig::custom(vec![0x42; 256], vec![0x37; 32]);
         assert!(result.is_err());
-        assert!(result.unwrap_err().message.contains("identical bytes"));
+        let err = result.unwrap_err();
+        assert_eq!(err.category, ErrorCategory::CryptoError);
+        assert!(err.message.contains("identical bytes"));
+    }
+
+    #[test]
+    fn test_hex_with_crlf() {
+        let rsa_hex = hex::encode(KeyConfig::trinity_core().rsa_modulus());
+        let (first, second) = rsa_hex.split_at(256);
+        let config = KeyConfig::trinity_core()
+            .with_rsa_from_hex(&format!("{}\r\n{}\r\n", first, second))
+            .unwrap();
+        assert_eq!(
+            config.rsa_modulus(),
+            KeyConfig::trinity_core().rsa_modulus()
+        );
+
+        let ed_hex = hex::encode(KeyConfig::trinity_core().ed25519_public_key());
+        let config = KeyConfig::trinity_core()
+            .with_ed25519_from_hex(&format!("{}\r\n", ed_hex))
+            .unwrap();
+        assert_eq!(
+            config.ed25519_public_key(),
+            KeyConfig::trinity_core().ed25519_public_key()
+        );
     }
 
     #[test]
@@ -389,6 +728,7 @@ mod tests {
 
         assert_eq!(config.rsa_modulus(), &custom_rsa);
         assert_eq!(config.ed25519_public_key(), &custom_ed25519);
+        assert_eq!(config.source, KeySource::File);
 
         Ok(())
     }
@@ -401,5 +741,201 @@ mod tests {
         assert!(info.contains("Ed25519 key: 02596f0d"));
         assert!(info.contains("256 bytes"));
         assert!(info.contains("32 bytes"));
+        assert!(info.contains("source: built-in TrinityCore"));
+        assert!(info.contains("matching preset: azerothcore, trinitycore"));
+
+        let mut rsa = RSA_MODULUS.to_vec();
+        rsa[1] ^= 0xFF;
+        let config = KeyConfig::trinity_core()
+            .with_rsa_from_hex(&hex::encode(&rsa))
+            .unwrap();
+        let info = config.display_info();
+        assert!(info.contains("source: from hex string"));
+        assert!(info.contains("matching preset: none"));
+    }
+
+    #[test]
+    fn test_key_source() {
+        assert_eq!(KeyConfig::default().source, KeySource::TrinityCore);
+        assert_eq!(
+            KeyConfig::named_preset("trinitycore").unwrap().source,
+            KeySource::TrinityCore
+        );
+        assert_eq!(
+            KeyConfig::named_preset("azerothcore").unwrap().source,
+            KeySource::AzerothCore
+        );
+        assert_eq!(
+            KeyConfig::new(RSA_MODULUS, CRYPTO_ED25519_PUBLIC_KEY)
+                .unwrap()
+                .source,
+            KeySource::Custom
+        );
+        assert_eq!(
+            KeyConfig::from_hex(
+                &hex::encode(RSA_MODULUS),
+                &hex::encode(CRYPTO_ED25519_PUBLIC_KEY)
+            )
+            .unwrap()
+            .source,
+            KeySource::Hex
+        );
+        assert_eq!(
+            KeyConfig::trinity_core()
+                .with_ed25519_from_base64(&STANDARD.encode(CRYPTO_ED25519_PUBLIC_KEY))
+                .unwrap()
+                .source,
+            KeySource::Base64
+        );
+
+        // The source does not affect which presets the keys match
+        let config = KeyConfig::new(RSA_MODULUS, CRYPTO_ED25519_PUBLIC_KEY).unwrap();
+        assert_eq!(config.matching_presets(), ["azerothcore", "trinitycore"]);
+        assert!(
+            KeyConfig::trinity_core()
+                .with_only_rsa()
+                .matching_presets()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_keys_from_base64() {
+        let rsa_b64 = STANDARD.encode(RSA_MODULUS);
+        let ed25519_b64 = STANDARD.encode(CRYPTO_ED25519_PUBLIC_KEY);
+
+        let config = KeyConfig::trinity_core()
+            .with_rsa_from_base64(&rsa_b64)
+            .unwrap()
+            .with_ed25519_from_base64(&ed25519_b64)
+            .unwrap();
+        assert!(config.is_trinity_core());
+
+        let err = KeyConfig::trinity_core()
+            .with_rsa_from_base64(&ed25519_b64)
+            .unwrap_err();
+        assert_eq!(err.category, ErrorCategory::CryptoError);
+        assert!(err.message.contains("256 bytes"));
+
+        assert!(
+            KeyConfig::trinity_core()
+                .with_ed25519_from_base64("not base64!")
+                .is_err()
+        );
+        assert!(
+            KeyConfig::trinity_core()
+                .with_ed25519_from_base64(&STANDARD.encode([0u8; 32]))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_named_presets() {
+        assert!(
+            KeyConfig::named_preset("trinitycore")
+                .unwrap()
+                .is_trinity_core()
+        );
+        assert!(KeyConfig::named_preset("AzerothCore").is_ok());
+
+        let err = KeyConfig::named_preset("unknown-server").unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+        assert!(err.suggestion.unwrap().contains("trinitycore"));
+
+        let mut rsa = RSA_MODULUS.to_vec();
+        rsa[0] ^= 0xFF;
+        let config = KeyConfig::trinity_core()
+            .with_rsa_from_hex(&hex::encode(&rsa))
+            .unwrap();
+        KeyConfig::register_preset("My Server", config).unwrap();
+        let preset = KeyConfig::named_preset("my server").unwrap();
+        assert_eq!(preset.rsa_modulus(), rsa.as_slice());
+        assert_eq!(preset.source, KeySource::Preset("my server".to_string()));
+
+        let names = KeyConfig::preset_names();
+        assert!(names.is_sorted());
+        assert!(names.contains(&"my server".to_string()));
+
+        assert!(KeyConfig::register_preset(" ", KeyConfig::trinity_core()).is_err());
+    }
+
+    #[test]
+    fn test_with_only_rsa() {
+        let config = KeyConfig::trinity_core().with_only_rsa();
+        assert!(!config.patches_ed25519());
+        assert!(config.ed25519_public_key().is_empty());
+        assert!(config.validate().is_ok());
+        assert!(config.is_trinity_core());
+        assert!(config.display_info().contains("Ed25519 key: not patched"));
+
+        // Loading a key afterwards re-enables the Ed25519 patch
+        let config = config
+            .with_ed25519_from_hex(&hex::encode(CRYPTO_ED25519_PUBLIC_KEY))
+            .unwrap();
+        assert!(config.patches_ed25519());
+    }
+
+    #[test]
+    fn test_secondary_rsa() {
+        let mut secondary = vec![0x42; 256];
+        secondary[0] = 0x43;
+
+        let config = KeyConfig::trinity_core()
+            .with_secondary_rsa(secondary.clone())
+            .unwrap();
+        assert_eq!(config.secondary_rsa_modulus(), Some(secondary.as_slice()));
+        assert!(KeyConfig::trinity_core().secondary_rsa_modulus().is_none());
+
+        let result = KeyConfig::trinity_core().with_secondary_rsa(vec![0x42; 128]);
+        assert!(
+            result
+                .unwrap_err()
+                .message
+                .contains("Secondary RSA modulus must be exactly 256 bytes")
+        );
+        assert!(
+            KeyConfig::trinity_core()
+                .with_secondary_rsa(vec![0; 256])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_validate_against_binary() {
+        use crate::patterns::{connect_to_modulus_pattern, signature_modulus_pattern};
+
+        let data = crate::patterns::test_helpers::create_test_executable();
+        assert!(
+            KeyConfig::trinity_core()
+                .validate_against_binary(&data)
+                .is_ok()
+        );
+
+        // A modulus starting with the ConnectTo pattern is found again after patching
+        let mut rsa = RSA_MODULUS.to_vec();
+        for (byte, &p) in rsa.iter_mut().zip(connect_to_modulus_pattern()) {
+            *byte = p as u8;
+        }
+        let config = KeyConfig::new(&rsa, CRYPTO_ED25519_PUBLIC_KEY).unwrap();
+        let err = config.validate_against_binary(&data).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+        assert!(err.message.contains("connect_to_modulus"));
+        assert_eq!(err.offset(), Some(0x500));
+
+        // Ed25519 keys are checked too, and a pattern missing from the binary has no offset
+        let mut ed25519 = CRYPTO_ED25519_PUBLIC_KEY.to_vec();
+        for (byte, &p) in ed25519.iter_mut().zip(signature_modulus_pattern()) {
+            *byte = p as u8;
+        }
+        let config = KeyConfig::new(RSA_MODULUS, &ed25519).unwrap();
+        let err = config.validate_against_binary(&data).unwrap_err();
+        assert!(err.message.starts_with("Ed25519 public key"));
+        assert_eq!(err.offset(), None);
+        assert!(
+            config
+                .with_only_rsa()
+                .validate_against_binary(&data)
+                .is_ok()
+        );
     }
 }
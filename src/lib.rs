@@ -59,6 +59,37 @@
 //! - [`patterns`] - Pattern definitions for binary search
 //! - [`errors`] - Error types
 //!
+//! # Async Feature
+//!
+//! Enabling the `tokio` feature adds [`AsyncPatcher`](crate::async_patcher::AsyncPatcher),
+//! which performs file I/O with tokio and runs the patching work on a blocking thread.
+//!
+//! # Rayon Feature
+//!
+//! Enabling the `rayon` feature makes [`patterns::find_in_binary`] split large
+//! binaries into chunks that are searched for all known patterns in parallel.
+//!
+//! # Tracing Feature
+//!
+//! Enabling the `tracing` feature makes [`binary::patch`] emit a
+//! `tracing::debug!` event with the offset and the bytes before and after
+//! every applied patch, for use with any `tracing` subscriber.
+//!
+//! # Serde Feature
+//!
+//! Enabling the `serde` feature derives `serde::Serialize` and
+//! `serde::Deserialize` for [`report::PatchReport`] and its records.
+//!
+//! # FFI Feature
+//!
+//! Enabling the `ffi` feature exports a C API from [`ffi`] and generates the
+//! matching `wow_patcher.h` header in `target/include`.
+//!
+//! # WASM Feature
+//!
+//! Enabling the `wasm` feature adds [`WasmPatcher`](crate::wasm::WasmPatcher), a
+//! `wasm-bindgen` wrapper around [`Patcher::patch_bytes`] for in-browser patching.
+//!
 //! # CLI Feature
 //!
 //! The library includes an optional CLI binary. To use only the library without CLI dependencies:
@@ -68,19 +99,39 @@
 //! wow-patcher = { version = "0.1", default-features = false }
 //! ```
 
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub mod async_patcher;
 pub mod binary;
 #[cfg(feature = "cli")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cli")))]
 pub mod cli;
 pub mod cmd;
 pub mod errors;
+#[cfg(feature = "ffi")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ffi")))]
+pub mod ffi;
 pub mod keys;
+#[cfg(feature = "network")]
+#[cfg_attr(docsrs, doc(cfg(feature = "network")))]
+pub mod network;
 pub mod patcher;
 pub mod patterns;
 pub mod platform;
+pub mod report;
 pub mod trinity;
 pub mod version;
+#[cfg(feature = "wasm")]
+#[cfg_attr(docsrs, doc(cfg(feature = "wasm")))]
+pub mod wasm;
 
 // Re-export the main API
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub use async_patcher::AsyncPatcher;
 pub use errors::WowPatcherError;
 pub use keys::KeyConfig;
-pub use patcher::Patcher;
+pub use patcher::{PatchProgress, Patcher};
+pub use report::PatchReport;
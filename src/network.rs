@@ -0,0 +1,268 @@
+//! Downloading the client executable over HTTP for [`Patcher::from_url`](crate::Patcher::from_url).
+
+use crate::errors::{ErrorCategory, WowPatcherError};
+use std::fs::{self, File};
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// File name used when the URL path does not end in one
+const DEFAULT_DOWNLOAD_NAME: &str = "Wow.exe";
+
+/// Distinguishes downloads started by the same process
+static DOWNLOAD_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A downloaded executable in its own temporary directory, removed when dropped.
+#[derive(Debug)]
+pub struct DownloadedFile {
+    path: PathBuf,
+}
+
+impl DownloadedFile {
+    /// Path of the downloaded file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for DownloadedFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+        if let Some(dir) = self.path.parent() {
+            let _ = fs::remove_dir(dir);
+        }
+    }
+}
+
+/// Stream the file at `url` into a new temporary directory.
+///
+/// The file keeps the last segment of the URL path as its name, so client
+/// detection from the file name still works. Downloads larger than
+/// `max_size` are aborted. `on_progress` is called after each chunk with the
+/// bytes received so far and the `Content-Length`, when the server sent one.
+///
+/// # Errors
+///
+/// Returns a network error for URLs that are not `http://` or `https://`,
+/// failed requests, error statuses and oversized downloads, and a file
+/// operation error if the temporary file cannot be written.
+pub fn download_to_temp(
+    url: &str,
+    max_size: u64,
+    on_progress: &mut dyn FnMut(u64, Option<u64>),
+) -> Result<DownloadedFile, WowPatcherError> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(WowPatcherError::new(
+            ErrorCategory::NetworkError,
+            "Download URL must start with http:// or https://",
+        )
+        .with_context("url", url.to_string()));
+    }
+
+    let network_error = |message: &str, e: ureq::Error| {
+        WowPatcherError::wrap(ErrorCategory::NetworkError, message, e)
+            .with_context("url", url.to_string())
+    };
+    let response = ureq::get(url)
+        .call()
+        .map_err(|e| network_error("Failed to download executable", e))?;
+    let content_length = response.body().content_length();
+    if let Some(len) = content_length
+        && len > max_size
+    {
+        return Err(too_large(url, max_size));
+    }
+
+    let dir = std::env::temp_dir().join(format!(
+        "wow-patcher-download-{}-{}",
+        std::process::id(),
+        DOWNLOAD_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let file_error = |e: std::io::Error| {
+        WowPatcherError::wrap(
+            ErrorCategory::FileOperationError,
+            "Failed to write downloaded executable",
+            e,
+        )
+        .with_context("path", dir.display().to_string())
+    };
+    fs::create_dir_all(&dir).map_err(file_error)?;
+    // Created before writing so a failed download cleans up after itself
+    let download = DownloadedFile {
+        path: dir.join(file_name_from_url(url)),
+    };
+
+    let mut file = File::create(&download.path).map_err(file_error)?;
+    let mut reader = response.into_body().into_reader();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut received: u64 = 0;
+    loop {
+        let n = reader.read(&mut buffer).map_err(|e| {
+            WowPatcherError::wrap(ErrorCategory::NetworkError, "Download interrupted", e)
+                .with_context("url", url.to_string())
+        })?;
+        if n == 0 {
+            break;
+        }
+        received += n as u64;
+        if received > max_size {
+            return Err(too_large(url, max_size));
+        }
+        file.write_all(&buffer[..n]).map_err(file_error)?;
+        on_progress(received, content_length);
+    }
+    file.flush().map_err(file_error)?;
+    Ok(download)
+}
+
+/// Progress callback printing a percentage to stderr when it is a terminal.
+pub(crate) fn terminal_progress() -> impl FnMut(u64, Option<u64>) {
+    let interactive = std::io::stderr().is_terminal();
+    let mut last_percent = None;
+    move |received, total| {
+        let Some(total) = total.filter(|&total| interactive && total > 0) else {
+            return;
+        };
+        let percent = received * 100 / total;
+        if last_percent != Some(percent) {
+            last_percent = Some(percent);
+            eprint!("\rDownloading executable... {}%", percent);
+            if received >= total {
+                eprintln!();
+            }
+        }
+    }
+}
+
+fn too_large(url: &str, max_size: u64) -> WowPatcherError {
+    WowPatcherError::new(
+        ErrorCategory::NetworkError,
+        format!(
+            "Download exceeds the maximum executable size of {:.0} MB",
+            max_size as f64 / (1024.0 * 1024.0)
+        ),
+    )
+    .with_context("url", url.to_string())
+}
+
+/// Last segment of the URL path, or [`DEFAULT_DOWNLOAD_NAME`] when it is empty or unsafe.
+fn file_name_from_url(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let after_scheme = path.split_once("://").map_or(path, |(_, rest)| rest);
+    after_scheme
+        .split_once('/')
+        .and_then(|(_, path)| path.rsplit('/').next())
+        .filter(|name| !name.is_empty() && *name != "." && *name != ".." && !name.contains('\\'))
+        .unwrap_or(DEFAULT_DOWNLOAD_NAME)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// Serve one HTTP response on a local port and return the URL of `path`.
+    fn serve_once(path: &str, response: Vec<u8>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}{}", listener.local_addr().unwrap(), path);
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request);
+            let _ = stream.write_all(&response);
+        });
+        url
+    }
+
+    fn ok_response(body: &[u8]) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    #[test]
+    fn test_download_to_temp() {
+        let body = vec![0x4D; 200 * 1024];
+        let url = serve_once("/clients/WowClassic.exe?build=1", ok_response(&body));
+
+        let mut progress = Vec::new();
+        let download = download_to_temp(&url, 1024 * 1024, &mut |received, total| {
+            progress.push((received, total))
+        })
+        .unwrap();
+        let path = download.path().to_path_buf();
+        assert_eq!(path.file_name().unwrap(), "WowClassic.exe");
+        assert_eq!(fs::read(&path).unwrap(), body);
+        assert_eq!(
+            progress.last(),
+            Some(&(body.len() as u64, Some(body.len() as u64)))
+        );
+
+        drop(download);
+        assert!(!path.exists());
+        assert!(!path.parent().unwrap().exists());
+    }
+
+    #[test]
+    fn test_download_to_temp_errors() {
+        let url = serve_once("/Wow.exe", ok_response(&[0; 4096]));
+        let err = download_to_temp(&url, 1024, &mut |_, _| {}).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::NetworkError);
+        assert!(err.message.contains("maximum executable size"));
+
+        let url = serve_once(
+            "/missing.exe",
+            b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+        );
+        let err = download_to_temp(&url, 1024, &mut |_, _| {}).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::NetworkError);
+
+        let err = download_to_temp("ftp://example.com/Wow.exe", 1024, &mut |_, _| {}).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::NetworkError);
+    }
+
+    #[test]
+    fn test_patcher_from_url() {
+        let url = serve_once("/_classic_/WowClassic.exe", ok_response(b"MZ"));
+        let patcher = crate::Patcher::from_url(&url).unwrap();
+        let input = patcher.input.clone();
+        assert_eq!(fs::read(&input).unwrap(), b"MZ");
+        assert_eq!(
+            patcher.output_path(),
+            PathBuf::from("WowClassic-patched.exe")
+        );
+
+        // The download outlives clones and is removed with the last one
+        let clone = patcher.clone();
+        drop(patcher);
+        assert!(input.exists());
+        drop(clone);
+        assert!(!input.exists());
+    }
+
+    #[test]
+    fn test_file_name_from_url() {
+        assert_eq!(file_name_from_url("https://cdn.local/a/Wow.exe"), "Wow.exe");
+        assert_eq!(
+            file_name_from_url("http://cdn.local/World%20of%20Warcraft?x=1"),
+            "World%20of%20Warcraft"
+        );
+        assert_eq!(
+            file_name_from_url("http://cdn.local/"),
+            DEFAULT_DOWNLOAD_NAME
+        );
+        assert_eq!(
+            file_name_from_url("http://cdn.local"),
+            DEFAULT_DOWNLOAD_NAME
+        );
+        assert_eq!(
+            file_name_from_url("http://cdn.local/a/.."),
+            DEFAULT_DOWNLOAD_NAME
+        );
+    }
+}
@@ -41,10 +41,46 @@
 //! # }
 //! ```
 
-use crate::cmd::execute::execute_patch;
-use crate::errors::WowPatcherError;
+use crate::binary::{Arch, DataExt};
+use crate::cmd::execute::{
+    DEFAULT_MAX_FILE_SIZE, DEFAULT_MIN_FILE_SIZE, DEFAULT_OUTPUT_MODE, PATCH_STEP_NAMES,
+    PatchOptions, execute_patch_with, is_already_patched, patch_data, preview_patches,
+    read_executable, write_patched_output,
+};
+use crate::cmd::steps::CustomBytePatch;
+use crate::errors::{ErrorCategory, WowPatcherError};
 use crate::keys::KeyConfig;
+#[cfg(feature = "network")]
+use crate::network::{DownloadedFile, download_to_temp, terminal_progress};
+use crate::patterns::{
+    PatternScanResult, cdns_url_pattern, find_in_binary, portal_pattern, version_url_pattern,
+    version_url_v2_pattern, version_url_v3_pattern,
+};
+use crate::platform::{CodesignAction, CodesignOptions, Version};
+use crate::report::{ConsoleReporter, PatchReport, ReportMode, Reporter};
+use crate::trinity::{ARCTIUM_CDN, PORTAL_ORIGINAL, VersionUrlConfig, with_port};
+use std::fmt;
+use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, mpsc};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Size of the in-memory buffer scanned to calibrate patch time estimates.
+const CALIBRATION_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Suffix of the default output path when none is configured
+pub const DEFAULT_OUTPUT_SUFFIX: &str = "-patched";
+
+/// Contents of an executable handed to [`Patcher::from_bytes`] or [`Patcher::from_reader`].
+#[derive(Clone)]
+pub(crate) struct InputBytes(Arc<[u8]>);
+
+impl fmt::Debug for InputBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "InputBytes({} bytes)", self.0.len())
+    }
+}
 
 /// A builder for patching World of Warcraft executables.
 ///
@@ -53,21 +89,71 @@ use std::path::{Path, PathBuf};
 #[derive(Debug, Clone)]
 pub struct Patcher {
     /// Path to input WoW executable
-    input: PathBuf,
-    /// Path to output patched executable (optional, defaults to input + "-patched")
-    output: Option<PathBuf>,
+    pub(crate) input: PathBuf,
+    /// Path to output patched executable (optional, defaults to input + `output_suffix`)
+    pub(crate) output: Option<PathBuf>,
+    /// Suffix for the default output path, `None` for [`DEFAULT_OUTPUT_SUFFIX`]
+    pub(crate) output_suffix: Option<String>,
     /// Key configuration (RSA + Ed25519)
-    key_config: Option<KeyConfig>,
+    pub(crate) key_config: Option<KeyConfig>,
     /// Custom version URL
-    version_url: Option<String>,
+    pub(crate) version_url: Option<String>,
+    /// Base URL of a custom CDN that generated version URLs point at
+    pub(crate) cdn_base: Option<String>,
     /// Custom CDNs URL
-    cdns_url: Option<String>,
+    pub(crate) cdns_url: Option<String>,
+    /// Custom unified API URL for the v3 pattern
+    pub(crate) cdns_url_v3: Option<String>,
+    /// Port inserted into the host of every CDN URL
+    pub(crate) cdn_port: Option<u16>,
     /// Dry run mode (preview changes without modifying files)
-    dry_run: bool,
+    pub(crate) dry_run: bool,
     /// Strip macOS code signing
-    strip_codesign: bool,
-    /// Verbose output
-    verbose: bool,
+    pub(crate) strip_codesign: bool,
+    /// How `codesign` treats the output when `strip_codesign` is set
+    pub(crate) codesign_options: CodesignOptions,
+    /// Unix permissions of the patched executable
+    pub(crate) output_mode: u32,
+    /// What is printed while patching
+    pub(crate) report_mode: ReportMode,
+    /// Maximum accepted input file size in bytes
+    pub(crate) max_file_size: u64,
+    /// Minimum accepted input file size in bytes
+    pub(crate) min_file_size: u64,
+    /// Client version the input must match before it is patched
+    pub(crate) expected_version: Option<Version>,
+    /// Client build number the input must have before it is patched
+    pub(crate) expected_build: Option<u16>,
+    /// Channel receiving progress updates while patching
+    pub(crate) progress_sender: Option<ProgressSender>,
+    /// Directory receiving a copy of the input before it is patched
+    pub(crate) backup_dir: Option<PathBuf>,
+    /// Patch both the ConnectTo and Signature RSA slots
+    pub(crate) dual_rsa: bool,
+    /// Patch only the RSA modulus and leave the Ed25519 key untouched
+    pub(crate) skip_ed25519: bool,
+    /// Do not copy the input to `backup_dir` before patching
+    pub(crate) skip_backup: bool,
+    /// Allow URLs longer than their patterns to be truncated
+    pub(crate) skip_url_validation: bool,
+    /// Bytes written over the portal host suffix, `None` for all zeros
+    pub(crate) portal_replacement: Option<Vec<u8>>,
+    /// Limit on the replacements of each pattern, `None` for the per-pattern defaults
+    pub(crate) max_patch_count: Option<usize>,
+    /// Patch every occurrence of each pattern instead of only the first
+    pub(crate) patch_all_occurrences: bool,
+    /// Slices of a universal Mach-O binary to patch
+    pub(crate) architecture: Arch,
+    /// Raw byte patches applied after the built-in patches
+    pub(crate) custom_patches: Vec<CustomBytePatch>,
+    /// Prompt for a missing input, output or key preset before patching
+    #[cfg(feature = "cli-interactive")]
+    pub(crate) interactive: bool,
+    /// Temporary download behind `input`, removed once the last clone is dropped
+    #[cfg(feature = "network")]
+    pub(crate) downloaded_input: Option<Arc<DownloadedFile>>,
+    /// In-memory contents read instead of `input`, which then only names the executable
+    pub(crate) input_bytes: Option<InputBytes>,
 }
 
 impl Patcher {
@@ -88,18 +174,159 @@ impl Patcher {
         Self {
             input: input.as_ref().to_path_buf(),
             output: None,
+            output_suffix: None,
             key_config: None,
             version_url: None,
+            cdn_base: None,
             cdns_url: None,
+            cdns_url_v3: None,
+            cdn_port: None,
             dry_run: false,
             strip_codesign: false,
-            verbose: false,
+            codesign_options: CodesignOptions::default(),
+            output_mode: DEFAULT_OUTPUT_MODE,
+            report_mode: ReportMode::Normal,
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            min_file_size: DEFAULT_MIN_FILE_SIZE,
+            expected_version: None,
+            expected_build: None,
+            progress_sender: None,
+            backup_dir: None,
+            dual_rsa: false,
+            skip_ed25519: false,
+            skip_backup: false,
+            skip_url_validation: false,
+            portal_replacement: None,
+            max_patch_count: None,
+            patch_all_occurrences: false,
+            architecture: Arch::All,
+            custom_patches: Vec::new(),
+            #[cfg(feature = "cli-interactive")]
+            interactive: false,
+            #[cfg(feature = "network")]
+            downloaded_input: None,
+            input_bytes: None,
         }
     }
 
+    /// Create a new `Patcher` for a WoW executable already held in memory.
+    ///
+    /// `name` is never read: it is only used to detect the client type and
+    /// to derive the default output path. No backup is made of in-memory
+    /// input.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Path the executable would have on disk, such as `_retail_/Wow.exe`
+    /// * `data` - Contents of the WoW executable
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let data = std::fs::read("Wow.exe")?;
+    /// let patched = Patcher::from_bytes("_retail_/Wow.exe", data)
+    ///     .trinity_core_keys()
+    ///     .patch_in_memory()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_bytes<P: AsRef<Path>>(name: P, data: impl Into<Vec<u8>>) -> Self {
+        let mut patcher = Self::new(name);
+        patcher.input_bytes = Some(InputBytes(data.into().into()));
+        patcher
+    }
+
+    /// Create a new `Patcher` for a WoW executable read from `reader`.
+    ///
+    /// The reader is drained immediately. Behaves like
+    /// [`Patcher::from_bytes`] with the bytes read.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Path the executable would have on disk, such as `_retail_/Wow.exe`
+    /// * `reader` - Source of the WoW executable
+    ///
+    /// # Errors
+    ///
+    /// Returns a file operation error if reading fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let file = std::fs::File::open("Wow.exe")?;
+    /// let patched = Patcher::from_reader("Wow.exe", file)?.patch_in_memory()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_reader<P: AsRef<Path>>(
+        name: P,
+        mut reader: impl Read,
+    ) -> Result<Self, WowPatcherError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).map_err(|e| {
+            WowPatcherError::wrap(
+                ErrorCategory::FileOperationError,
+                "Failed to read WoW executable",
+                e,
+            )
+            .with_context("path", name.as_ref().display().to_string())
+        })?;
+        Ok(Self::from_bytes(name, data))
+    }
+
+    /// Create a new `Patcher` for a WoW executable downloaded over HTTP.
+    ///
+    /// The executable is streamed to a temporary file, which is deleted once
+    /// the patcher has been consumed by [`Patcher::patch`] or dropped.
+    /// Downloads larger than 1 GB are rejected. When stderr is a terminal
+    /// and the server sends a `Content-Length`, download progress is shown.
+    ///
+    /// The default output path is the file name from the URL in the current
+    /// directory with the output suffix added, so `https://host/Wow.exe`
+    /// is written to `Wow-patched.exe`.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - `http://` or `https://` URL of the WoW executable
+    ///
+    /// # Errors
+    ///
+    /// Returns a network error if the URL is not HTTP(S), the request
+    /// fails or the download is too large, and a file operation error if the
+    /// temporary file cannot be written.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::from_url("https://files.example.com/clients/Wow.exe")?
+    ///     .output("Wow-patched.exe")
+    ///     .trinity_core_keys()
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "network")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "network")))]
+    pub fn from_url(url: &str) -> Result<Self, WowPatcherError> {
+        let download = download_to_temp(url, DEFAULT_MAX_FILE_SIZE, &mut terminal_progress())?;
+        let mut patcher = Self::new(download.path());
+        patcher.downloaded_input = Some(Arc::new(download));
+        Ok(patcher)
+    }
+
     /// Set the output path for the patched executable.
     ///
-    /// If not specified, defaults to the input filename with "-patched" appended.
+    /// If not specified, defaults to the input filename with "-patched"
+    /// appended, see [`Patcher::with_output_suffix`].
     ///
     /// # Arguments
     ///
@@ -118,6 +345,72 @@ impl Patcher {
         self
     }
 
+    /// Set the suffix of the default output path.
+    ///
+    /// The suffix goes before a `.exe` extension and is appended to other
+    /// file names, so `Wow.exe` with `"-trinity"` is written to
+    /// `Wow-trinity.exe`. Ignored when [`Patcher::output`] is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `suffix` - Text added to the input file name, `"-patched"` by default
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // Writes Wow-private.exe next to Wow.exe
+    /// Patcher::new("Wow.exe")
+    ///     .with_output_suffix("-private")
+    ///     .trinity_core_keys()
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_output_suffix(mut self, suffix: &str) -> Self {
+        self.output_suffix = Some(suffix.to_string());
+        self
+    }
+
+    /// Copy this configuration for patching a different executable.
+    ///
+    /// Keys, CDN URLs, flags and every other option are kept, while the
+    /// input is replaced and the output is reset so that it defaults to
+    /// the new input name with the output suffix added.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Path to the WoW executable to patch
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let retail = Patcher::new("_retail_/Wow.exe")
+    ///     .trinity_core_keys()
+    ///     .custom_cdn("https://cdn.example.com");
+    /// let classic = retail.clone_for_input("_classic_/WowClassic.exe");
+    ///
+    /// retail.patch()?;
+    /// classic.patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn clone_for_input<P: AsRef<Path>>(&self, input: P) -> Self {
+        Self {
+            input: input.as_ref().to_path_buf(),
+            output: None,
+            #[cfg(feature = "network")]
+            downloaded_input: None,
+            input_bytes: None,
+            ..self.clone()
+        }
+    }
+
     /// Use TrinityCore's default RSA and Ed25519 keys.
     ///
     /// This is the most common configuration for TrinityCore private servers.
@@ -244,9 +537,66 @@ impl Patcher {
         Ok(self)
     }
 
+    /// Use the keys of a named server preset.
+    ///
+    /// `trinitycore` and `azerothcore` are built in; more can be added with
+    /// [`KeyConfig::register_preset`].
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Preset name, case-insensitive
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no preset with this name is registered.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .server_preset("azerothcore")?
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn server_preset(mut self, name: &str) -> Result<Self, WowPatcherError> {
+        self.key_config = Some(KeyConfig::named_preset(name)?);
+        Ok(self)
+    }
+
+    /// Use a key configuration that was built beforehand.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_config` - RSA modulus and Ed25519 key to patch in
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::{KeyConfig, Patcher};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let keys = KeyConfig::trinity_core().with_rsa_from_file("server.rsa")?;
+    /// Patcher::new("Wow.exe")
+    ///     .with_key_config(keys)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_key_config(mut self, key_config: KeyConfig) -> Self {
+        self.key_config = Some(key_config);
+        self
+    }
+
     /// Set a custom CDN URL for version and CDNs endpoints.
     ///
-    /// This sets both version and CDNs URLs to the same base.
+    /// This points the version, CDNs and v3 unified API URLs at the same base,
+    /// so clients using any version of the URL pattern are covered. Version
+    /// URLs keep the Arctium path layout, including the client build, on the
+    /// given host (see [`VersionUrlConfig::from_base_url`]).
     ///
     /// # Arguments
     ///
@@ -266,8 +616,9 @@ impl Patcher {
     /// ```
     pub fn custom_cdn<S: Into<String>>(mut self, cdn_url: S) -> Self {
         let url = cdn_url.into();
-        self.version_url = Some(format!("{}/{{region}}/{{product}}/versions", url));
-        self.cdns_url = Some(format!("{}/{{region}}/{{product}}/cdns", url));
+        self.cdns_url = Some(cdn_base_cdns_url(&url));
+        self.cdns_url_v3 = Some(cdn_base_v3_url(&url));
+        self.cdn_base = Some(url);
         self
     }
 
@@ -317,8 +668,78 @@ impl Patcher {
         self
     }
 
+    /// Set a custom URL for the v3 unified API pattern (WoW Classic 1.15.8+).
+    ///
+    /// The URL may contain at most two `%s` placeholders, which the client
+    /// fills with the region and product, or none for a static URL. Without
+    /// this the v3 pattern falls back to the version URL. The placeholder
+    /// count is checked when [`Patcher::patch`] runs.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - Custom unified API URL
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .cdns_url_v3("http://my-cdn.local/%s/%s")
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cdns_url_v3(mut self, url: &str) -> Self {
+        self.cdns_url_v3 = Some(url.to_string());
+        self
+    }
+
+    /// Serve the CDN URLs from a non-standard port.
+    ///
+    /// `:<port>` is inserted into the host of the version, CDNs and v3 URLs,
+    /// custom or default. Even with [`Patcher::skip_url_validation`],
+    /// [`Patcher::patch`] fails when a URL with the port is longer than the
+    /// pattern it replaces. The maximum URL lengths are:
+    ///
+    /// | Pattern | Maximum length |
+    /// |---------|----------------|
+    /// | Version URL v1 (`http://%s.patch.battle.net:1119/%s/versions`) | 43 |
+    /// | Version URL v2 (`https://%s.version.battle.net/v2/products/%s/versions`) | 53 |
+    /// | Unified API v3 (`https://%s.version.battle.net/v2/products/%s/%s`) | 47 |
+    /// | CDNs URL (`http://%s.patch.battle.net:1119/%s/cdns`) | 39 |
+    ///
+    /// The default Arctium URLs already fill the v1 and CDNs patterns, so a
+    /// port usually needs a shorter host set with [`Patcher::custom_cdn`].
+    ///
+    /// # Arguments
+    ///
+    /// * `port` - TCP port of the CDN
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .custom_cdn("http://cdn.lan")
+    ///     .with_cdn_port(8080)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_cdn_port(mut self, port: u16) -> Self {
+        self.cdn_port = Some(port);
+        self
+    }
+
     /// Enable dry run mode (preview changes without modifying files).
     ///
+    /// The preview is printed to stdout; use [`Patcher::dry_run_to_string`]
+    /// to capture it instead.
+    ///
     /// # Arguments
     ///
     /// * `enabled` - Whether to enable dry run mode
@@ -365,41 +786,55 @@ impl Patcher {
         self
     }
 
-    /// Enable verbose output.
+    /// Run `codesign` on the output with custom options (macOS only).
+    ///
+    /// [`Patcher::strip_codesign`] removes the signature. Use this to sign
+    /// the output with an ad-hoc identity instead, or to pass `--deep` or
+    /// `--force`. Setting options enables the `codesign` step.
     ///
     /// # Arguments
     ///
-    /// * `enabled` - Whether to enable verbose logging
+    /// * `opts` - Action and flags for the `codesign` command
     ///
     /// # Examples
     ///
     /// ```no_run
     /// use wow_patcher::Patcher;
+    /// use wow_patcher::platform::{CodesignAction, CodesignOptions};
     ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// Patcher::new("Wow.exe")
-    ///     .verbose(true)
+    /// Patcher::new("Wow.app/Contents/MacOS/World of Warcraft")
+    ///     .strip_codesign_options(CodesignOptions {
+    ///         action: CodesignAction::AdhocSign,
+    ///         deep: false,
+    ///         force: true,
+    ///     })
     ///     .patch()?;
     /// # Ok(())
     /// # }
     /// ```
-    pub fn verbose(mut self, enabled: bool) -> Self {
-        self.verbose = enabled;
+    pub fn strip_codesign_options(mut self, opts: CodesignOptions) -> Self {
+        self.strip_codesign = true;
+        self.codesign_options = opts;
         self
     }
 
-    /// Execute the patching operation.
+    /// Patch the executable and re-sign the output with a signing identity (macOS only).
     ///
-    /// This applies all configured patches to the WoW executable.
+    /// The existing signature is removed and the patched output is signed
+    /// with `codesign --sign <identity> --force --timestamp`. Pass `"-"` to
+    /// sign ad hoc, which needs no certificate and gets no timestamp. The
+    /// `deep` flag of [`Patcher::strip_codesign_options`] is kept. On other
+    /// platforms this behaves like [`Patcher::patch`].
+    ///
+    /// # Arguments
+    ///
+    /// * `identity` - Keychain certificate name or SHA-1 hash, or `"-"` for ad-hoc signing
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - The input file doesn't exist or is invalid
-    /// - No key configuration was set
-    /// - Pattern matching fails
-    /// - File operations fail
-    /// - Patterns are found in non-patchable sections
+    /// Returns a validation error if `identity` is empty, a platform error
+    /// if `codesign` fails, and any error of [`Patcher::patch`].
     ///
     /// # Examples
     ///
@@ -407,66 +842,1602 @@ impl Patcher {
     /// use wow_patcher::Patcher;
     ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// Patcher::new("Wow.exe")
-    ///     .output("Wow-patched.exe")
+    /// Patcher::new("Wow.app/Contents/MacOS/World of Warcraft")
     ///     .trinity_core_keys()
-    ///     .patch()?;
+    ///     .patch_and_sign("Developer ID Application: Example (TEAMID)")?;
     /// # Ok(())
     /// # }
     /// ```
-    pub fn patch(self) -> Result<(), WowPatcherError> {
-        // Determine output path
-        let output = self.output.unwrap_or_else(|| {
-            let input_str = self.input.to_string_lossy();
-            let output_str = if input_str.ends_with(".exe") {
-                input_str.replace(".exe", "-patched.exe")
-            } else {
-                format!("{}-patched", input_str)
-            };
-            PathBuf::from(output_str)
-        });
-
-        // Use TrinityCore keys if no custom keys specified
-        let key_config = self.key_config.unwrap_or_else(KeyConfig::trinity_core);
-
-        // Execute the patch
-        execute_patch(
-            &self.input,
-            &output,
-            key_config,
-            self.version_url.as_deref(),
-            self.cdns_url.as_deref(),
-            self.dry_run,
-            self.strip_codesign,
-            self.verbose,
-        )
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_patcher_new() {
-        let patcher = Patcher::new("Wow.exe");
-        assert_eq!(patcher.input, PathBuf::from("Wow.exe"));
-        assert!(patcher.output.is_none());
-        assert!(patcher.key_config.is_none());
-        assert!(!patcher.dry_run);
-        assert!(!patcher.verbose);
+    pub fn patch_and_sign(self, identity: &str) -> Result<PatchReport, WowPatcherError> {
+        if identity.trim().is_empty() {
+            return Err(WowPatcherError::new(
+                ErrorCategory::ValidationError,
+                "Code signing identity is empty",
+            )
+            .with_suggestion("Pass a keychain identity name or \"-\" for ad-hoc signing"));
+        }
+        let deep = self.codesign_options.deep;
+        self.strip_codesign_options(CodesignOptions {
+            action: CodesignAction::Sign(identity.to_string()),
+            deep,
+            force: true,
+        })
+        .patch()
     }
 
-    #[test]
-    fn test_patcher_output() {
-        let patcher = Patcher::new("Wow.exe").output("custom-out.exe");
-        assert_eq!(patcher.output, Some(PathBuf::from("custom-out.exe")));
+    /// Set the Unix permissions of the patched executable.
+    ///
+    /// Defaults to `0o755`. A mode without the execute bits, such as `0o644`,
+    /// produces a copy that cannot be started until it is made executable
+    /// with `chmod +x`. Has no effect on Windows.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - Permission bits applied to the output file
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .with_output_permissions(0o644)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_output_permissions(mut self, mode: u32) -> Self {
+        self.output_mode = mode;
+        self
     }
 
-    #[test]
-    fn test_patcher_trinity_core_keys() {
-        let patcher = Patcher::new("Wow.exe").trinity_core_keys();
-        assert!(patcher.key_config.is_some());
+    /// Enable verbose output.
+    ///
+    /// Shorthand for [`Patcher::report_mode`] with [`ReportMode::Verbose`],
+    /// or [`ReportMode::Normal`] when disabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to enable verbose logging
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .verbose(true)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn verbose(self, enabled: bool) -> Self {
+        self.report_mode(if enabled {
+            ReportMode::Verbose
+        } else {
+            ReportMode::Normal
+        })
+    }
+
+    /// Choose what the patcher prints to stdout.
+    ///
+    /// The default, [`ReportMode::Normal`], prints warnings, the dry run
+    /// preview and a summary. [`ReportMode::Json`] prints a single JSON
+    /// object describing the result instead, for tools that parse the output.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - How much to print
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    /// use wow_patcher::report::ReportMode;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .report_mode(ReportMode::Silent)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn report_mode(mut self, mode: ReportMode) -> Self {
+        self.report_mode = mode;
+        self
+    }
+
+    /// Prompt on the terminal for anything [`Patcher::patch`] is missing.
+    ///
+    /// When the input does not exist the user is asked for its path, when no
+    /// output is set the default output is offered for confirmation, and when
+    /// no keys are set a menu of the registered presets is shown. Pressing
+    /// Ctrl+C aborts the session with an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to prompt for missing options
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .interactive_mode(true)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "cli-interactive")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cli-interactive")))]
+    pub fn interactive_mode(mut self, enabled: bool) -> Self {
+        self.interactive = enabled;
+        self
+    }
+
+    /// Override the maximum accepted input file size.
+    ///
+    /// Defaults to 1 GB.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_bytes` - Largest executable size that will be patched
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .with_max_file_size(2 * 1024 * 1024 * 1024)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_max_file_size(mut self, max_bytes: u64) -> Self {
+        self.max_file_size = max_bytes;
+        self
+    }
+
+    /// Override the minimum accepted input file size.
+    ///
+    /// Defaults to 1024 bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_bytes` - Smallest executable size that will be patched
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .with_min_file_size(10 * 1024 * 1024)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_min_file_size(mut self, min_bytes: u64) -> Self {
+        self.min_file_size = min_bytes;
+        self
+    }
+
+    /// Require the input executable to be a specific client version.
+    ///
+    /// Patching fails with a validation error if the detected version differs
+    /// or cannot be determined.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The client version the server expects
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    /// use wow_patcher::platform::Version;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .with_expected_version(Version::new(11, 0, 7, 58867))
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_expected_version(mut self, version: Version) -> Self {
+        self.expected_version = Some(version);
+        self
+    }
+
+    /// Require the input executable to have a specific client build number.
+    ///
+    /// Unlike [`Patcher::with_expected_version`] only the build is compared,
+    /// so any client of that build is accepted.
+    ///
+    /// # Arguments
+    ///
+    /// * `build` - The client build the server expects
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .with_expected_build(58867)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_expected_build(mut self, build: u16) -> Self {
+        self.expected_build = Some(build);
+        self
+    }
+
+    /// Report patching progress over a standard library channel.
+    ///
+    /// The receiver gets one [`PatchProgress`] per patch step and a final
+    /// message with `step_index == total_steps` once the output is written.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let (patcher, progress) = Patcher::new("Wow.exe").with_progress_channel();
+    ///
+    /// let handle = std::thread::spawn(move || patcher.patch());
+    /// for update in progress {
+    ///     println!("{}/{} {}", update.step_index, update.total_steps, update.step_name);
+    /// }
+    /// handle.join().unwrap()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_progress_channel(mut self) -> (Self, mpsc::Receiver<PatchProgress>) {
+        let (sender, receiver) = mpsc::channel();
+        self.progress_sender = Some(ProgressSender::Std(sender));
+        (self, receiver)
+    }
+
+    /// Report patching progress over a tokio channel.
+    ///
+    /// Behaves like [`Patcher::with_progress_channel`] but the receiver can be
+    /// awaited from async code.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    pub fn with_async_progress_channel(
+        mut self,
+    ) -> (Self, tokio::sync::mpsc::UnboundedReceiver<PatchProgress>) {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        self.progress_sender = Some(ProgressSender::Tokio(sender));
+        (self, receiver)
+    }
+
+    /// Patch both the ConnectTo and Signature RSA slots.
+    ///
+    /// Hybrid servers supporting pre- and post-8.0 clients need both slots
+    /// replaced. The Signature slot receives the secondary modulus from
+    /// [`KeyConfig::with_secondary_rsa`] when one is configured, and the primary
+    /// modulus otherwise. Configuring a secondary modulus enables this mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to patch both RSA slots
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .trinity_core_keys()
+    ///     .dual_rsa_mode(true)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn dual_rsa_mode(mut self, enabled: bool) -> Self {
+        self.dual_rsa = enabled;
+        self
+    }
+
+    /// Fail if any pattern is replaced more than `n` times.
+    ///
+    /// Guards against an unexpected repetition, for example from embedded
+    /// debug data, being patched along with the real copy. By default
+    /// required patterns (portal, RSA modulus) may be replaced once and
+    /// optional ones (Ed25519 key, URLs) up to 10 times. The limit applies
+    /// to each pattern separately and counts replacements, not matches.
+    /// Pass `usize::MAX` to turn the limit off.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - Maximum number of replacements allowed per pattern
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .max_patch_count(2)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn max_patch_count(mut self, n: usize) -> Self {
+        self.max_patch_count = Some(n);
+        self
+    }
+
+    /// Patch every occurrence of each pattern instead of only the first.
+    ///
+    /// Some builds embed the same key more than once, for example a debug
+    /// copy of the RSA modulus next to the one used for ConnectTo. With this
+    /// enabled every non-overlapping match is replaced and gets its own
+    /// record in the report. The replacements still count towards
+    /// [`max_patch_count`](Self::max_patch_count), so raise it to the number
+    /// of copies expected.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to patch all occurrences (default: `false`)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .patch_all_occurrences(true)
+    ///     .max_patch_count(2)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn patch_all_occurrences(mut self, enabled: bool) -> Self {
+        self.patch_all_occurrences = enabled;
+        self
+    }
+
+    /// Choose which slices of a universal (fat) Mach-O binary are patched.
+    ///
+    /// macOS clients ship as universal binaries with an `arm64` and an
+    /// `x86_64` slice. Each selected slice is patched on its own, with
+    /// sections looked up in that slice's header, and its report records
+    /// are tagged with the architecture, e.g. `Portal [arm64]`. Binaries
+    /// that are not universal are patched as usual.
+    ///
+    /// # Arguments
+    ///
+    /// * `arch` - [`Arch::Arm64`], [`Arch::X86_64`] or [`Arch::All`] (default)
+    ///
+    /// # Errors
+    ///
+    /// Patching fails with a validation error if the binary has no slice for
+    /// `arch`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    /// use wow_patcher::binary::Arch;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("World of Warcraft.app/Contents/MacOS/World of Warcraft")
+    ///     .architecture(Arch::Arm64)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn architecture(mut self, arch: Arch) -> Self {
+        self.architecture = arch;
+        self
+    }
+
+    /// Write raw bytes at a known file offset after the built-in patches.
+    ///
+    /// This is the lowest-level patch: no pattern is searched for, the bytes
+    /// are written wherever `offset` points. The whole range must lie within
+    /// one section of the executable. Custom patches run in the order they
+    /// were added.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name shown in progress updates and the patch report
+    /// * `offset` - File offset of the first byte to write
+    /// * `bytes` - Bytes to write
+    /// * `required` - Fail the run if the range is outside every section,
+    ///   instead of recording the patch as missing
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .add_custom_byte_patch("Skip launcher check", 0x1a2b3c, vec![0xEB], true)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_custom_byte_patch(
+        mut self,
+        name: &str,
+        offset: usize,
+        bytes: Vec<u8>,
+        required: bool,
+    ) -> Self {
+        self.custom_patches
+            .push(CustomBytePatch::new(name, offset, bytes, required));
+        self
+    }
+
+    /// Write a custom string over the `.actual.battle.net` portal host suffix.
+    ///
+    /// The string is zero padded to 18 bytes. See
+    /// [`Patcher::portal_replacement_bytes`] for the rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `replacement` - Replacement text, at most 18 bytes of UTF-8
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .portal_replacement(".example.org")
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn portal_replacement(self, replacement: &str) -> Self {
+        self.portal_replacement_bytes(replacement.as_bytes().to_vec())
+    }
+
+    /// Write custom bytes over the `.actual.battle.net` portal host suffix.
+    ///
+    /// By default all 18 bytes of the suffix are zeroed. The replacement is
+    /// zero padded to 18 bytes, so the result is always null terminated
+    /// unless all 18 bytes are given. Replacements longer than 18 bytes are
+    /// rejected when patching.
+    ///
+    /// # Arguments
+    ///
+    /// * `replacement` - Raw replacement bytes, at most 18
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // Null out only the leading '.' and keep the rest of the string
+    /// Patcher::new("Wow.exe")
+    ///     .portal_replacement_bytes(b"\0actual.battle.net".to_vec())
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn portal_replacement_bytes(mut self, replacement: Vec<u8>) -> Self {
+        self.portal_replacement = Some(replacement);
+        self
+    }
+
+    /// Skip the Ed25519 patch and replace only the RSA modulus.
+    ///
+    /// Classic and Classic Era clients never get the Ed25519 patch. This
+    /// option also skips it for other clients, see [`KeyConfig::with_only_rsa`].
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to skip the Ed25519 patch
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("WowClassic.exe")
+    ///     .trinity_core_keys()
+    ///     .skip_ed25519(true)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn skip_ed25519(mut self, enabled: bool) -> Self {
+        self.skip_ed25519 = enabled;
+        self
+    }
+
+    /// Back up the input executable before patching.
+    ///
+    /// The original file is copied to `<dir>/<input_filename>.bak.YYYYMMDD_HHMMSS`
+    /// (UTC) before anything is patched. If the backup cannot be written, the
+    /// patch is aborted. [`Patcher::skip_backup`] overrides this setting.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Existing directory that receives the backup
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .with_backup_dir("backups")
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_backup_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.backup_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Never back up the input executable, even if a backup directory is set.
+    ///
+    /// This takes precedence over [`Patcher::with_backup_dir`]. Without a
+    /// backup a failed or unwanted patch cannot be undone with
+    /// [`Patcher::unpatch_from_backup`]; only use this when the original
+    /// executable can be restored another way, for example in CI.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to skip the backup
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .with_backup_dir("backups")
+    ///     .skip_backup(true)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn skip_backup(mut self, enabled: bool) -> Self {
+        self.skip_backup = enabled;
+        self
+    }
+
+    /// Let patching truncate URLs that are too long for their patterns.
+    ///
+    /// By default [`Patcher::validate_cdn_urls`] runs before patching and
+    /// rejects such URLs. Only enable this if a truncated URL is intended.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to skip the URL length check
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::new("Wow.exe")
+    ///     .cdns_url("http://a-very-long-host-name.example.com/cdns")
+    ///     .skip_url_validation(true)
+    ///     .patch()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn skip_url_validation(mut self, enabled: bool) -> Self {
+        self.skip_url_validation = enabled;
+        self
+    }
+
+    /// Restore an original executable from a backup.
+    ///
+    /// Copies `backup_path` over `output`, undoing a previous patch.
+    ///
+    /// # Arguments
+    ///
+    /// * `backup_path` - Backup created by [`Patcher::with_backup_dir`]
+    /// * `output` - Path where the original executable is restored
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// Patcher::unpatch_from_backup("backups/Wow.exe.bak.20250101_120000", "Wow.exe")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn unpatch_from_backup(
+        backup_path: impl AsRef<Path>,
+        output: impl AsRef<Path>,
+    ) -> Result<(), WowPatcherError> {
+        let backup_path = backup_path.as_ref();
+        if !backup_path.is_file() {
+            return Err(WowPatcherError::new(
+                ErrorCategory::FileOperationError,
+                format!("Backup file not found: {}", backup_path.display()),
+            ));
+        }
+
+        fs::copy(backup_path, output.as_ref()).map_err(|e| {
+            WowPatcherError::wrap(
+                ErrorCategory::FileOperationError,
+                "Failed to restore executable from backup",
+                e,
+            )
+            .with_context("backup", backup_path.display().to_string())
+        })?;
+        Ok(())
+    }
+
+    /// Check whether the input executable has been patched with the configured keys.
+    ///
+    /// Uses the TrinityCore keys unless custom keys were set. Returns `true`
+    /// if the executable contains those keys and the original portal host
+    /// suffix ([`PORTAL_ORIGINAL`]) has been replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input file cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// if Patcher::new("Wow.exe").verify()? {
+    ///     println!("Wow.exe is patched for TrinityCore");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn verify(&self) -> Result<bool, WowPatcherError> {
+        let data = self.read_input()?;
+        let key_config = self
+            .key_config
+            .clone()
+            .unwrap_or_else(KeyConfig::trinity_core);
+        let portal_intact = data
+            .windows(PORTAL_ORIGINAL.len())
+            .any(|window| window == PORTAL_ORIGINAL);
+        Ok(is_already_patched(&data, &key_config) && !portal_intact)
+    }
+
+    /// Scan the input executable for every known pattern without modifying it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input file cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let scan = Patcher::new("Wow.exe").inspect()?;
+    /// println!("Missing patterns: {:?}", scan.missing);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn inspect(&self) -> Result<PatternScanResult, WowPatcherError> {
+        let data = self.read_input()?;
+        Ok(find_in_binary(&data))
+    }
+
+    /// Check that every configured URL fits the pattern it replaces.
+    ///
+    /// The version URL may be written over the v1, v2 or v3 pattern, so it is
+    /// checked against the shortest of them. The CDNs and v3 URLs are checked
+    /// against their own patterns. A port set with [`Patcher::with_cdn_port`]
+    /// counts towards the length. Runs automatically before every patch or
+    /// dry run, on files and in memory, unless [`Patcher::skip_url_validation`]
+    /// is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error listing every URL that is too long.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// let patcher = Patcher::new("Wow.exe").cdns_url("http://cdn.lan/cdns");
+    /// assert!(patcher.validate_cdn_urls().is_ok());
+    /// ```
+    pub fn validate_cdn_urls(&self) -> Result<(), WowPatcherError> {
+        let version_url_limit = [
+            version_url_pattern(),
+            version_url_v2_pattern(),
+            version_url_v3_pattern(),
+        ]
+        .iter()
+        .map(|pattern| pattern.len())
+        .min()
+        .unwrap_or_default();
+
+        let violations: Vec<String> = [
+            ("version URL", &self.version_url, version_url_limit),
+            ("CDNs URL", &self.cdns_url, cdns_url_pattern().len()),
+            ("v3 URL", &self.cdns_url_v3, version_url_v3_pattern().len()),
+        ]
+        .into_iter()
+        .filter_map(|(name, url, limit)| {
+            let url = match self.cdn_port {
+                Some(port) => with_port(url.as_ref()?, port),
+                None => url.clone()?,
+            };
+            (url.len() > limit).then(|| {
+                format!(
+                    "{} is {} bytes, at most {} fit: {}",
+                    name,
+                    url.len(),
+                    limit,
+                    url
+                )
+            })
+        })
+        .collect();
+
+        if violations.is_empty() {
+            return Ok(());
+        }
+        Err(WowPatcherError::new(
+            ErrorCategory::ValidationError,
+            format!(
+                "URLs too long for their patterns:\n{}",
+                violations.join("\n")
+            ),
+        )
+        .with_suggestion(
+            "Use a shorter host or path, or call skip_url_validation(true) to truncate them",
+        ))
+    }
+
+    /// Execute the patching operation.
+    ///
+    /// This applies all configured patches to the WoW executable and returns
+    /// a report with one record per patch attempted. In dry run mode nothing
+    /// is written and the patches found are reported as
+    /// [`PatchStatus::Found`](crate::report::PatchStatus::Found).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A configured URL is too long, see [`Patcher::validate_cdn_urls`]
+    /// - The input file doesn't exist or is invalid
+    /// - No key configuration was set
+    /// - Pattern matching fails
+    /// - File operations fail
+    /// - Patterns are found in non-patchable sections
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let report = Patcher::new("Wow.exe")
+    ///     .output("Wow-patched.exe")
+    ///     .trinity_core_keys()
+    ///     .patch()?;
+    /// println!("{}", report);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn patch(self) -> Result<PatchReport, WowPatcherError> {
+        #[cfg(feature = "cli-interactive")]
+        if self.interactive {
+            return self.prompt_missing()?.interactive_mode(false).patch();
+        }
+
+        let mut reporter = ConsoleReporter::new(self.report_mode);
+        let result = self.patch_with(&mut reporter);
+        if let Err(e) = &result {
+            reporter.error(e);
+        }
+        result
+    }
+
+    /// [`Patcher::patch`] sending all output to `reporter`.
+    fn patch_with(&self, reporter: &mut dyn Reporter) -> Result<PatchReport, WowPatcherError> {
+        let opts = self.patch_options()?;
+        report_configuration(&opts, reporter);
+
+        if let Some(backup_path) = self.backup_path() {
+            fs::copy(&self.input, &backup_path).map_err(|e| backup_error(&backup_path, e))?;
+            reporter.status(&format!(
+                "✓ Backed up original executable to {}",
+                backup_path.display()
+            ));
+        }
+
+        let data = match &self.input_bytes {
+            Some(InputBytes(data)) => data.to_vec(),
+            None => read_executable(&self.input, opts.max_file_size, opts.min_file_size)?,
+        };
+        execute_patch_with(&self.input, &self.output_path(), data, opts, reporter)
+    }
+
+    /// Execute the patching operation and return the patched executable.
+    ///
+    /// The input is read from the bytes given to [`Patcher::from_bytes`] or
+    /// [`Patcher::from_reader`], or otherwise from the input path, and the
+    /// patched copy is returned instead of written. The output path, backups
+    /// and dry run mode do not apply. [`Patcher::strip_codesign`] is ignored
+    /// too, since `codesign` only works on a file: strip the signature after
+    /// writing the bytes if the client needs it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A configured URL is too long, see [`Patcher::validate_cdn_urls`]
+    /// - The input file cannot be read
+    /// - The data fails validation
+    /// - A required pattern is missing or found in a non-patchable section
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let data = std::fs::read("Wow.exe")?;
+    /// let patched = Patcher::from_bytes("Wow.exe", data)
+    ///     .trinity_core_keys()
+    ///     .patch_in_memory()?;
+    /// println!("Patched {} bytes", patched.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn patch_in_memory(self) -> Result<Vec<u8>, WowPatcherError> {
+        let mut data = self.read_input()?;
+        self.patch_bytes_in_place(&mut data)?;
+        Ok(data)
+    }
+
+    /// Patch the input once and write the result to several output paths.
+    ///
+    /// The input is read and patched in memory a single time, then written
+    /// to each output in turn. Every output gets its own result, so a failed
+    /// write does not stop the remaining ones. If reading or patching fails,
+    /// every entry holds that error. In dry run mode nothing is written.
+    ///
+    /// # Arguments
+    ///
+    /// * `outputs` - Paths to write the patched executable to
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// let results = Patcher::new("Wow.exe")
+    ///     .patch_multiple_outputs(vec!["group-a/Wow.exe", "group-b/Wow.exe"]);
+    /// for result in results {
+    ///     if let Err(e) = result {
+    ///         eprintln!("{}", e);
+    ///     }
+    /// }
+    /// ```
+    pub fn patch_multiple_outputs<P: AsRef<Path>>(
+        self,
+        outputs: Vec<P>,
+    ) -> Vec<Result<(), WowPatcherError>> {
+        let patched = self.read_input().and_then(|data| {
+            let patched = self.patch_bytes(&data)?;
+            if let Some(backup_path) = self.backup_path() {
+                fs::copy(&self.input, &backup_path).map_err(|e| backup_error(&backup_path, e))?;
+            }
+            Ok(patched)
+        });
+
+        let mut reporter = ConsoleReporter::new(self.report_mode);
+        match patched {
+            Ok(data) => outputs
+                .iter()
+                .map(|output| {
+                    if self.dry_run {
+                        return Ok(());
+                    }
+                    write_patched_output(
+                        output.as_ref(),
+                        &data,
+                        self.strip_codesign.then(|| self.codesign_options.clone()),
+                        self.output_mode,
+                        &mut reporter,
+                    )
+                    .map_err(|e| e.with_context("path", output.as_ref().display().to_string()))
+                })
+                .collect(),
+            // The error is not Clone, so each output gets an equivalent copy
+            Err(e) => outputs.iter().map(|_| Err(e.duplicate())).collect(),
+        }
+    }
+
+    /// Run the dry run checks on the input and return the report as a string.
+    ///
+    /// This produces the same report that [`Patcher::dry_run`] prints, so it
+    /// can be shown in a UI instead. The input is read but nothing is
+    /// written, and no backup is made.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The input cannot be read
+    /// - The file fails size or version validation
+    /// - A configured URL is too long for its pattern
+    /// - Patterns are found in non-patchable sections
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let report = Patcher::new("Wow.exe").dry_run_to_string()?;
+    /// println!("{}", report);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn dry_run_to_string(&self) -> Result<String, WowPatcherError> {
+        let opts = self.patch_options()?;
+        let data = self.read_input()?;
+        let (header, report) = preview_patches(
+            &self.input,
+            &self.output_path(),
+            &data,
+            &opts,
+            &mut ConsoleReporter::new(self.report_mode),
+        )?;
+        Ok(format!("{}{}", header, report))
+    }
+
+    /// Apply all configured patches to an in-memory executable.
+    ///
+    /// Nothing is read from or written to disk: the input path is only used
+    /// to detect the client type, and backups, dry run and code signature
+    /// removal do not apply. Returns the patched copy of `input`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a configured URL is too long (see
+    /// [`Patcher::validate_cdn_urls`]), the data fails validation or a
+    /// required pattern is missing.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let data = std::fs::read("Wow.exe")?;
+    /// let patched = Patcher::new("Wow.exe").patch_bytes(&data)?;
+    /// std::fs::write("Wow-patched.exe", patched)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn patch_bytes(&self, input: &[u8]) -> Result<Vec<u8>, WowPatcherError> {
+        let mut data = input.to_vec();
+        self.patch_bytes_in_place(&mut data)?;
+        Ok(data)
+    }
+
+    /// Apply all configured patches to an in-memory executable without copying it.
+    ///
+    /// Behaves like [`Patcher::patch_bytes`], but modifies `data` directly
+    /// and returns the report of every patch step. If patching fails after
+    /// some steps were applied, `data` is left partially patched.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Contents of the WoW executable, patched in place
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a configured URL is too long (see
+    /// [`Patcher::validate_cdn_urls`]), the data fails validation or a
+    /// required pattern is missing.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let patcher = Patcher::new("Wow.exe").trinity_core_keys();
+    /// let mut data = std::fs::read("Wow.exe")?;
+    /// let report = patcher.patch_bytes_in_place(&mut data)?;
+    /// println!("{} patches applied", report.applied_count());
+    /// std::fs::write("Wow-patched.exe", data)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn patch_bytes_in_place(&self, data: &mut Vec<u8>) -> Result<PatchReport, WowPatcherError> {
+        let mut reporter = ConsoleReporter::new(self.report_mode);
+        let report = patch_data(&self.input, data, &mut self.patch_options()?, &mut reporter)?;
+        if let Some(sender) = &self.progress_sender {
+            sender.send(PatchProgress::complete(self.total_steps()));
+        }
+        reporter.finish(&report);
+        Ok(report)
+    }
+
+    /// Write a script that runs the equivalent `wow-patcher` CLI command.
+    ///
+    /// The script is a `.sh` file on Unix and a `.bat` file on Windows. Both
+    /// keys are embedded as `--rsa-hex` and `--ed25519-hex`, so running the
+    /// script reproduces the same patched executable. Call it on a clone of
+    /// the patcher after [`Patcher::patch`] succeeds.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the script to write
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the patcher uses an option the CLI has no flag for
+    /// (such as dual RSA mode, `skip_ed25519` or a CDN port) or the script
+    /// cannot be written.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let patcher = Patcher::new("Wow.exe").custom_cdn("http://my-cdn.local");
+    /// patcher.clone().patch()?;
+    /// patcher.emit_patch_script("repatch.sh")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn emit_patch_script<P: AsRef<Path>>(&self, path: P) -> Result<(), WowPatcherError> {
+        let path = path.as_ref();
+        let script = self.patch_script(cfg!(windows))?;
+        fs::write(path, script).map_err(|e| {
+            WowPatcherError::wrap(
+                ErrorCategory::FileOperationError,
+                "Failed to write patch script",
+                e,
+            )
+            .with_context("path", path.display().to_string())
+        })?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o755)).map_err(|e| {
+                WowPatcherError::wrap(
+                    ErrorCategory::FileOperationError,
+                    "Failed to set file permissions",
+                    e,
+                )
+                .with_context("path", path.display().to_string())
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Render the CLI command reproducing this patcher as a shell or batch script.
+    fn patch_script(&self, windows: bool) -> Result<String, WowPatcherError> {
+        let unsupported: Vec<&str> = [
+            (self.input_bytes.is_some(), "from_bytes"),
+            (self.dual_rsa, "dual_rsa_mode"),
+            (self.skip_ed25519, "skip_ed25519"),
+            (self.cdn_port.is_some(), "with_cdn_port"),
+            (self.portal_replacement.is_some(), "portal_replacement"),
+            (!self.custom_patches.is_empty(), "add_custom_byte_patch"),
+            (self.patch_all_occurrences, "patch_all_occurrences"),
+            (self.architecture != Arch::All, "architecture"),
+            (
+                !matches!(self.report_mode, ReportMode::Normal | ReportMode::Verbose),
+                "report_mode",
+            ),
+            (self.expected_version.is_some(), "expected_version"),
+            (self.max_file_size != DEFAULT_MAX_FILE_SIZE, "max_file_size"),
+            (self.min_file_size != DEFAULT_MIN_FILE_SIZE, "min_file_size"),
+            // The CLI always strips the code signature on macOS
+            (
+                !self.strip_codesign && cfg!(target_os = "macos"),
+                "strip_codesign(false)",
+            ),
+            (
+                self.codesign_options != CodesignOptions::default(),
+                "strip_codesign_options",
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect();
+        if !unsupported.is_empty() {
+            return Err(WowPatcherError::new(
+                ErrorCategory::ValidationError,
+                "Patcher options have no CLI equivalent",
+            )
+            .with_context("options", unsupported.join(", "))
+            .with_suggestion("Remove these options or patch through the library instead"));
+        }
+
+        let key_config = self
+            .key_config
+            .clone()
+            .unwrap_or_else(KeyConfig::trinity_core);
+        let mut args = vec![
+            "--warcraft-exe".to_string(),
+            self.input.display().to_string(),
+            "--output-file".to_string(),
+            self.output_path().display().to_string(),
+            "--rsa-hex".to_string(),
+            hex::encode_upper(key_config.rsa_modulus()),
+            "--ed25519-hex".to_string(),
+            hex::encode_upper(key_config.ed25519_public_key()),
+        ];
+        // --cdn-base generates the version URL from the detected build like
+        // custom_cdn does, so only URLs set on top of it need their own flag
+        if let Some(base) = &self.cdn_base {
+            args.push("--cdn-base".to_string());
+            args.push(base.clone());
+        }
+        let from_base = |url: fn(&str) -> String| self.cdn_base.as_deref().map(url);
+        for (flag, value, derived) in [
+            ("--version-url", &self.version_url, None),
+            ("--cdns-url", &self.cdns_url, from_base(cdn_base_cdns_url)),
+            (
+                "--cdns-url-v3",
+                &self.cdns_url_v3,
+                from_base(cdn_base_v3_url),
+            ),
+        ] {
+            if let Some(value) = value
+                && Some(value) != derived.as_ref()
+            {
+                args.push(flag.to_string());
+                args.push(value.clone());
+            }
+        }
+        if self.skip_backup {
+            args.push("--no-backup".to_string());
+        } else if let Some(dir) = &self.backup_dir {
+            args.push("--backup-dir".to_string());
+            args.push(dir.display().to_string());
+        }
+        if self.dry_run {
+            args.push("--dry-run".to_string());
+        }
+        if let Some(build) = self.expected_build {
+            args.push("--expected-build".to_string());
+            args.push(build.to_string());
+        }
+        if self.output_mode != DEFAULT_OUTPUT_MODE {
+            args.push("--output-mode".to_string());
+            args.push(format!("{:o}", self.output_mode));
+        }
+        if self.report_mode == ReportMode::Verbose {
+            args.push("--verbose".to_string());
+        }
+
+        let version = env!("CARGO_PKG_VERSION");
+        if windows {
+            let args: Vec<String> = args
+                .iter()
+                .map(|arg| format!("\"{}\"", arg.replace('%', "%%")))
+                .collect();
+            Ok(format!(
+                "@echo off\r\nrem Generated by wow-patcher {}\r\nwow-patcher {}\r\n",
+                version,
+                args.join(" ")
+            ))
+        } else {
+            let args: Vec<String> = args
+                .iter()
+                .map(|arg| format!("'{}'", arg.replace('\'', "'\\''")))
+                .collect();
+            Ok(format!(
+                "#!/bin/sh\n# Generated by wow-patcher {}\nexec wow-patcher {}\n",
+                version,
+                args.join(" ")
+            ))
+        }
+    }
+
+    /// Estimate how long [`Patcher::patch`] will take for the input file.
+    ///
+    /// The estimate is a linear model over the input file size, calibrated by
+    /// scanning a 1 MB in-memory buffer the first time it is called. It is
+    /// approximate and intended for "time remaining" displays.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input file cannot be accessed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wow_patcher::Patcher;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let estimate = Patcher::new("Wow.exe").estimate_patch_time()?;
+    /// println!("About {} seconds remaining", estimate.as_secs());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn estimate_patch_time(&self) -> Result<Duration, WowPatcherError> {
+        let len = match &self.input_bytes {
+            Some(InputBytes(data)) => data.len() as u64,
+            None => fs::metadata(&self.input)
+                .map_err(|e| {
+                    WowPatcherError::wrap(
+                        ErrorCategory::FileOperationError,
+                        "Unable to access WoW executable file",
+                        e,
+                    )
+                    .with_context("path", self.input.display().to_string())
+                })?
+                .len(),
+        };
+
+        // Each patch step scans the file for its pattern roughly once
+        let nanos = len as f64 * scan_nanos_per_byte() * PATCH_STEP_NAMES.len() as f64;
+        Ok(Duration::from_nanos(nanos as u64))
+    }
+
+    /// Number of patch steps reported through progress updates.
+    pub(crate) fn total_steps(&self) -> usize {
+        PATCH_STEP_NAMES.len() + self.custom_patches.len()
+    }
+
+    /// Read the whole input executable.
+    pub(crate) fn read_input(&self) -> Result<Vec<u8>, WowPatcherError> {
+        if let Some(InputBytes(data)) = &self.input_bytes {
+            return Ok(data.to_vec());
+        }
+        fs::read(&self.input).map_err(|e| {
+            WowPatcherError::wrap(
+                ErrorCategory::FileOperationError,
+                "Failed to read WoW executable file",
+                e,
+            )
+            .with_context("path", self.input.display().to_string())
+        })
+    }
+
+    /// Fill in the input, output and keys from terminal prompts.
+    #[cfg(feature = "cli-interactive")]
+    fn prompt_missing(mut self) -> Result<Self, WowPatcherError> {
+        use crate::cmd::interactive::{prompt_input_path, prompt_key_preset, prompt_output_path};
+
+        if self.input_bytes.is_none() && !self.input.is_file() {
+            self.input = prompt_input_path()?;
+        }
+        if self.output.is_none() {
+            self.output = Some(prompt_output_path(&self.output_path())?);
+        }
+        if self.key_config.is_none() {
+            self.key_config = Some(KeyConfig::named_preset(&prompt_key_preset()?)?);
+        }
+        Ok(self)
+    }
+
+    /// Version service for generated version URLs, the Arctium CDN unless a custom CDN is set.
+    pub(crate) fn version_url_config(&self) -> VersionUrlConfig<'_> {
+        self.cdn_base
+            .as_deref()
+            .map_or(ARCTIUM_CDN, VersionUrlConfig::from_base_url)
+    }
+
+    /// Options running this patcher's keys, URLs and limits through the shared patch path.
+    ///
+    /// Every way of patching builds its options here, so this is where
+    /// [`Patcher::validate_cdn_urls`] runs unless [`Patcher::skip_url_validation`]
+    /// is enabled.
+    pub(crate) fn patch_options(&self) -> Result<PatchOptions<'_>, WowPatcherError> {
+        if !self.skip_url_validation {
+            self.validate_cdn_urls()?;
+        }
+
+        let mut key_config = self
+            .key_config
+            .clone()
+            .unwrap_or_else(KeyConfig::trinity_core);
+        if self.skip_ed25519 {
+            key_config = key_config.with_only_rsa();
+        }
+
+        Ok(PatchOptions {
+            key_config,
+            version_url: self.version_url.clone(),
+            version_url_config: self.version_url_config(),
+            cdns_url: self.cdns_url.clone(),
+            cdns_url_v3: self.cdns_url_v3.clone(),
+            cdn_port: self.cdn_port,
+            dry_run: self.dry_run,
+            strip_codesign: self.strip_codesign,
+            codesign_options: self.codesign_options.clone(),
+            output_mode: self.output_mode,
+            report_mode: self.report_mode,
+            max_file_size: self.max_file_size,
+            min_file_size: self.min_file_size,
+            expected_version: self.expected_version,
+            expected_build: self.expected_build,
+            dual_rsa: self.dual_rsa,
+            portal_replacement: self.portal_replacement.clone(),
+            max_patch_count: self.max_patch_count,
+            patch_all_occurrences: self.patch_all_occurrences,
+            architecture: self.architecture,
+            custom_patches: self.custom_patches.clone(),
+            on_progress: Some(Box::new(|progress| {
+                if let Some(sender) = &self.progress_sender {
+                    sender.send(progress);
+                }
+            })),
+        })
+    }
+
+    /// Resolve the backup file path, if backups are enabled and this is not a dry run.
+    pub(crate) fn backup_path(&self) -> Option<PathBuf> {
+        if self.dry_run || self.skip_backup || self.input_bytes.is_some() {
+            return None;
+        }
+        let dir = self.backup_dir.as_ref()?;
+        Some(backup_file_path(&self.input, dir))
+    }
+
+    /// Resolve the output path, defaulting to the input name with the output suffix added.
+    pub(crate) fn output_path(&self) -> PathBuf {
+        self.output.clone().unwrap_or_else(|| {
+            suffixed_output_path(
+                &self.default_output_base(),
+                self.output_suffix
+                    .as_deref()
+                    .unwrap_or(DEFAULT_OUTPUT_SUFFIX),
+            )
+        })
+    }
+
+    /// Path the default output is derived from: the input, or just its file
+    /// name when the input is a temporary download.
+    fn default_output_base(&self) -> PathBuf {
+        #[cfg(feature = "network")]
+        if self.downloaded_input.is_some()
+            && let Some(name) = self.input.file_name()
+        {
+            return PathBuf::from(name);
+        }
+        self.input.clone()
+    }
+}
+
+/// CDNs URL that [`Patcher::custom_cdn`] sets for a CDN base.
+fn cdn_base_cdns_url(base: &str) -> String {
+    format!("{}/{{region}}/{{product}}/cdns", base)
+}
+
+/// v3 unified API URL that [`Patcher::custom_cdn`] sets for a CDN base.
+fn cdn_base_v3_url(base: &str) -> String {
+    format!("{}/%s/%s", base)
+}
+
+/// Insert `suffix` before a trailing `.exe`, or append it to other file names.
+pub(crate) fn suffixed_output_path(input: &Path, suffix: &str) -> PathBuf {
+    let input_str = input.to_string_lossy();
+    match input_str.strip_suffix(".exe") {
+        Some(stem) => PathBuf::from(format!("{}{}.exe", stem, suffix)),
+        None => PathBuf::from(format!("{}{}", input_str, suffix)),
+    }
+}
+
+/// Show the keys and custom CDN URLs about to be patched in, in verbose mode.
+fn report_configuration(opts: &PatchOptions, reporter: &mut dyn Reporter) {
+    if !reporter.is_verbose() {
+        return;
+    }
+    reporter.detail(&format!(
+        "Using server keys: {}",
+        opts.key_config.display_info()
+    ));
+    if opts.version_url.is_some() || opts.cdns_url.is_some() {
+        reporter.detail("Using custom CDN URLs:");
+        if let Some(version_url) = &opts.version_url {
+            reporter.detail(&format!("  Version URL: {}", version_url));
+        }
+        if let Some(cdns_url) = &opts.cdns_url {
+            reporter.detail(&format!("  CDNs URL: {}", cdns_url));
+        }
+    }
+}
+
+/// Error for a backup that could not be written, aborting the patch.
+pub(crate) fn backup_error(backup_path: &Path, cause: std::io::Error) -> WowPatcherError {
+    WowPatcherError::wrap(
+        ErrorCategory::FileOperationError,
+        "Failed to back up WoW executable, aborting before patching",
+        cause,
+    )
+    .with_context("backup", backup_path.display().to_string())
+}
+
+/// Path of a new backup of `input` in `dir`, named `<input_filename>.bak.YYYYMMDD_HHMMSS`.
+pub(crate) fn backup_file_path(input: &Path, dir: &Path) -> PathBuf {
+    let file_name = input
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "Wow".to_string());
+    dir.join(format!(
+        "{}.bak.{}",
+        file_name,
+        backup_timestamp(SystemTime::now())
+    ))
+}
+
+/// Format a time as a `YYYYMMDD_HHMMSS` UTC timestamp for backup file names.
+fn backup_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let days = (secs / 86_400) as i64;
+    let secs_of_day = secs % 86_400;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}{:02}{:02}_{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3_600,
+        (secs_of_day % 3_600) / 60,
+        secs_of_day % 60
+    )
+}
+
+/// Measured cost of a pattern scan in nanoseconds per byte, calibrated once.
+fn scan_nanos_per_byte() -> f64 {
+    static CALIBRATION: OnceLock<f64> = OnceLock::new();
+
+    *CALIBRATION.get_or_init(|| {
+        let buffer = vec![0u8; CALIBRATION_BUFFER_SIZE];
+        let start = Instant::now();
+        std::hint::black_box(buffer.find_pattern(portal_pattern()));
+        let elapsed = start.elapsed().as_nanos().max(1) as f64;
+        elapsed / CALIBRATION_BUFFER_SIZE as f64
+    })
+}
+
+/// Sending half of a progress channel created by the builder.
+#[derive(Debug, Clone)]
+pub(crate) enum ProgressSender {
+    Std(mpsc::Sender<PatchProgress>),
+    #[cfg(feature = "tokio")]
+    Tokio(tokio::sync::mpsc::UnboundedSender<PatchProgress>),
+}
+
+impl ProgressSender {
+    /// Send a progress update, ignoring a dropped receiver.
+    pub(crate) fn send(&self, progress: PatchProgress) {
+        match self {
+            ProgressSender::Std(sender) => {
+                let _ = sender.send(progress);
+            }
+            #[cfg(feature = "tokio")]
+            ProgressSender::Tokio(sender) => {
+                let _ = sender.send(progress);
+            }
+        }
+    }
+}
+
+/// Progress update emitted while patches are applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchProgress {
+    /// Index of the step that just finished; equals `total_steps` once patching is complete
+    pub step_index: usize,
+    /// Total number of patch steps
+    pub total_steps: usize,
+    /// Human-readable name of the step
+    pub step_name: String,
+}
+
+impl PatchProgress {
+    /// Final progress message signalling that all steps have completed.
+    pub fn complete(total_steps: usize) -> Self {
+        Self {
+            step_index: total_steps,
+            total_steps,
+            step_name: "Complete".to_string(),
+        }
+    }
+
+    /// Whether this message marks the end of patching.
+    pub fn is_complete(&self) -> bool {
+        self.step_index >= self.total_steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::PatchStatus;
+    use crate::trinity::get_version_url;
+
+    #[test]
+    fn test_patcher_new() {
+        let patcher = Patcher::new("Wow.exe");
+        assert_eq!(patcher.input, PathBuf::from("Wow.exe"));
+        assert!(patcher.output.is_none());
+        assert!(patcher.key_config.is_none());
+        assert!(!patcher.dry_run);
+        assert_eq!(patcher.report_mode, ReportMode::Normal);
+    }
+
+    #[test]
+    fn test_patcher_output() {
+        let patcher = Patcher::new("Wow.exe").output("custom-out.exe");
+        assert_eq!(patcher.output, Some(PathBuf::from("custom-out.exe")));
+    }
+
+    #[test]
+    fn test_clone_for_input() {
+        let patcher = Patcher::new("_retail_/Wow.exe")
+            .output("custom-out.exe")
+            .with_output_suffix("-tc")
+            .trinity_core_keys()
+            .custom_cdn("http://test.local")
+            .dry_run(true);
+        let clone = patcher.clone_for_input("_classic_/WowClassic.exe");
+
+        assert_eq!(clone.input, PathBuf::from("_classic_/WowClassic.exe"));
+        assert!(clone.output.is_none());
+        assert_eq!(
+            clone.output_path(),
+            PathBuf::from("_classic_/WowClassic-tc.exe")
+        );
+        assert!(clone.key_config.unwrap().is_trinity_core());
+        assert_eq!(clone.cdns_url, patcher.cdns_url);
+        assert!(clone.dry_run);
+        // The original is unchanged
+        assert_eq!(patcher.output, Some(PathBuf::from("custom-out.exe")));
+    }
+
+    #[test]
+    fn test_patcher_trinity_core_keys() {
+        let patcher = Patcher::new("Wow.exe").trinity_core_keys();
+        assert!(patcher.key_config.is_some());
         assert!(patcher.key_config.unwrap().is_trinity_core());
     }
 
@@ -498,9 +2469,427 @@ mod tests {
     #[test]
     fn test_patcher_custom_cdn() {
         let patcher = Patcher::new("Wow.exe").custom_cdn("http://test.local");
-        assert!(patcher.version_url.is_some());
         assert!(patcher.cdns_url.is_some());
-        assert!(patcher.version_url.unwrap().contains("http://test.local"));
+        assert_eq!(
+            patcher.cdns_url_v3,
+            Some("http://test.local/%s/%s".to_string())
+        );
+        // Version URLs are generated per build on the custom host
+        assert!(patcher.version_url.is_none());
+        let config = patcher.version_url_config();
+        assert_eq!(config.host, "test.local");
+        assert_eq!(
+            get_version_url(Some(1), None, None, &config),
+            "http://test.local/%s/%s/1/versions"
+        );
+    }
+
+    #[test]
+    fn test_patch_bytes() {
+        let input = crate::patterns::test_helpers::create_test_executable();
+        let (patcher, rx) = Patcher::new("game.bin").with_progress_channel();
+
+        let patched = patcher.patch_bytes(&input).unwrap();
+        assert_eq!(patched.len(), input.len());
+        assert!(patched.find_pattern(portal_pattern()).is_none());
+        assert!(
+            patched
+                .find_pattern(crate::patterns::connect_to_modulus_pattern())
+                .is_none()
+        );
+        assert!(rx.try_iter().last().unwrap().is_complete());
+
+        let err = Patcher::new("game.bin")
+            .patch_bytes(&[0u8; 16])
+            .unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+    }
+
+    #[test]
+    fn test_patch_bytes_in_place() {
+        let input = crate::patterns::test_helpers::create_test_executable();
+        let patcher = Patcher::new("game.bin").report_mode(ReportMode::Silent);
+
+        let mut data = input.clone();
+        let capacity = data.capacity();
+        let report = patcher.patch_bytes_in_place(&mut data).unwrap();
+        assert_eq!(data, patcher.patch_bytes(&input).unwrap());
+        // The caller's buffer is reused rather than replaced by a copy
+        assert_eq!(data.capacity(), capacity);
+        assert!(report.applied_count() > 0);
+        assert!(
+            report
+                .patches_applied
+                .iter()
+                .any(|record| record.step == "Portal")
+        );
+
+        let mut data = vec![0u8; 16];
+        let err = patcher.patch_bytes_in_place(&mut data).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+        assert_eq!(data, vec![0u8; 16]);
+    }
+
+    #[test]
+    fn test_patch_all_occurrences() {
+        // Three back-to-back copies of the RSA modulus pattern
+        let pattern = crate::patterns::connect_to_modulus_pattern();
+        let copy: Vec<u8> = pattern.iter().map(|&b| b as u8).collect();
+        let mut input = crate::patterns::test_helpers::create_test_executable();
+        let first = input.find_pattern(pattern).unwrap();
+        input[first..first + 3 * copy.len()].copy_from_slice(&copy.repeat(3));
+
+        // By default only the first copy is replaced
+        let patcher = Patcher::new("game.bin").report_mode(ReportMode::Silent);
+        let patched = patcher.patch_bytes(&input).unwrap();
+        assert_eq!(
+            crate::binary::find_pattern_all(&patched, pattern),
+            vec![first + copy.len(), first + 2 * copy.len()]
+        );
+
+        // Every copy is replaced, which the default limit of one rejects
+        let patcher = patcher.patch_all_occurrences(true);
+        let mut data = input.clone();
+        let err = patcher.patch_bytes_in_place(&mut data).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::PatchingError);
+        assert_eq!(err.offset(), Some(first + copy.len()));
+
+        let mut data = input.clone();
+        let report = patcher
+            .max_patch_count(3)
+            .patch_bytes_in_place(&mut data)
+            .unwrap();
+        assert!(crate::binary::find_pattern_all(&data, pattern).is_empty());
+        let key_config = KeyConfig::trinity_core();
+        for i in 0..3 {
+            let offset = first + i * copy.len();
+            assert_eq!(
+                &data[offset..offset + copy.len()],
+                &key_config.rsa_modulus()[..copy.len()]
+            );
+        }
+        let rsa_records = report
+            .patches_applied
+            .iter()
+            .filter(|record| record.step.starts_with("RSA modulus"))
+            .count();
+        assert_eq!(rsa_records, 3);
+    }
+
+    #[test]
+    fn test_patch_in_memory() {
+        let input = crate::patterns::test_helpers::create_test_executable();
+        let expected = Patcher::new("game.bin")
+            .report_mode(ReportMode::Silent)
+            .patch_bytes(&input)
+            .unwrap();
+
+        // Output and code signing options are ignored, nothing is written
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let output = temp_dir.path().join("patched.bin");
+        let patched = Patcher::from_bytes("game.bin", input.clone())
+            .output(&output)
+            .strip_codesign(true)
+            .report_mode(ReportMode::Silent)
+            .patch_in_memory()
+            .unwrap();
+        assert_eq!(patched, expected);
+        assert!(!output.exists());
+
+        let patched = Patcher::from_reader("game.bin", input.as_slice())
+            .unwrap()
+            .report_mode(ReportMode::Silent)
+            .patch_in_memory()
+            .unwrap();
+        assert_eq!(patched, expected);
+
+        // A file path input is read from disk
+        let path = temp_dir.path().join("game.bin");
+        fs::write(&path, &input).unwrap();
+        let patched = Patcher::new(&path)
+            .report_mode(ReportMode::Silent)
+            .patch_in_memory()
+            .unwrap();
+        assert_eq!(patched, expected);
+
+        let err = Patcher::from_bytes("game.bin", vec![0u8; 16])
+            .patch_in_memory()
+            .unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+    }
+
+    #[test]
+    fn test_patch_from_bytes_writes_output() {
+        let input = crate::patterns::test_helpers::create_test_executable();
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let output = temp_dir.path().join("patched.bin");
+        let patcher = Patcher::from_bytes("game.bin", input.clone())
+            .output(&output)
+            .with_backup_dir(temp_dir.path())
+            .report_mode(ReportMode::Silent);
+        assert!(format!("{:?}", patcher).contains("InputBytes("));
+
+        let report = patcher.clone().dry_run(true).patch().unwrap();
+        assert!(!output.exists());
+        assert_eq!(report.applied_count(), 0);
+        assert_eq!(
+            report.patches_applied[0].status,
+            PatchStatus::Found,
+            "{:?}",
+            report.patches_applied[0]
+        );
+
+        let report = patcher.patch().unwrap();
+        assert!(report.applied_count() > 0);
+        assert_eq!(
+            fs::read(&output).unwrap(),
+            Patcher::new("game.bin").patch_bytes(&input).unwrap()
+        );
+        // There is no input file to back up
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_add_custom_byte_patch() {
+        let input = crate::patterns::test_helpers::create_test_executable();
+        let (patcher, rx) = Patcher::new("game.bin")
+            .add_custom_byte_patch("Flag", 0x210, vec![0xEB, 0x01], true)
+            .with_progress_channel();
+        let patched = patcher.patch_bytes(&input).unwrap();
+        assert_eq!(&patched[0x210..0x212], &[0xEB, 0x01]);
+        let progress: Vec<PatchProgress> = rx.try_iter().collect();
+        assert_eq!(progress[PATCH_STEP_NAMES.len()].step_name, "Flag");
+        assert_eq!(
+            progress.last().unwrap().total_steps,
+            PATCH_STEP_NAMES.len() + 1
+        );
+
+        let err = Patcher::new("game.bin")
+            .add_custom_byte_patch("Header", 0x10, vec![0xEB], true)
+            .patch_bytes(&input)
+            .unwrap_err();
+        assert_eq!(err.category, ErrorCategory::PatchingError);
+        assert!(
+            Patcher::new("game.bin")
+                .add_custom_byte_patch("Header", 0x10, vec![0xEB], false)
+                .patch_bytes(&input)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_dry_run_to_string() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let input = temp_dir.path().join("game.bin");
+        fs::write(
+            &input,
+            crate::patterns::test_helpers::create_test_executable(),
+        )
+        .unwrap();
+        let output = temp_dir.path().join("game-patched.bin");
+
+        let report = Patcher::new(&input)
+            .output(&output)
+            .dry_run_to_string()
+            .unwrap();
+        assert!(report.starts_with("🔍 Dry Run Mode"));
+        assert!(report.contains("Format:      PE"));
+        assert!(report.contains("No changes were made. Remove --dry-run to apply patches."));
+        // The patches follow as the PatchReport table
+        let table = report
+            .split_once("Patches that would be applied:\n")
+            .unwrap()
+            .1;
+        assert!(table.starts_with('┌'));
+        assert!(
+            table
+                .lines()
+                .any(|line| line.contains("Portal") && line.contains("found"))
+        );
+        assert!(table.trim_end().ends_with('┘'));
+        assert!(!output.exists());
+
+        let err = Patcher::new(temp_dir.path().join("missing.bin"))
+            .dry_run_to_string()
+            .unwrap_err();
+        assert_eq!(err.category, ErrorCategory::FileOperationError);
+    }
+
+    #[test]
+    fn test_patch_multiple_outputs() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let input = temp_dir.path().join("game.bin");
+        fs::write(
+            &input,
+            crate::patterns::test_helpers::create_test_executable(),
+        )
+        .unwrap();
+        let first = temp_dir.path().join("first.bin");
+        let second = temp_dir.path().join("second.bin");
+        let unwritable = temp_dir.path().join("missing-dir").join("third.bin");
+
+        let results =
+            Patcher::new(&input).patch_multiple_outputs(vec![&first, &unwritable, &second]);
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert!(results[2].is_ok());
+        assert_eq!(fs::read(&first).unwrap(), fs::read(&second).unwrap());
+
+        let results = Patcher::new(temp_dir.path().join("absent.bin"))
+            .patch_multiple_outputs(vec![&first, &second]);
+        assert!(results.iter().all(|result| result.is_err()));
+
+        // Every copy of the error keeps its context
+        let results = Patcher::new(&first).patch_multiple_outputs(vec![&second, &unwritable]);
+        assert!(results.iter().all(|result| {
+            result
+                .as_ref()
+                .is_err_and(|e| e.get_context("already_patched").is_some())
+        }));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_with_output_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let input = temp_dir.path().join("game.bin");
+        fs::write(
+            &input,
+            crate::patterns::test_helpers::create_test_executable(),
+        )
+        .unwrap();
+        let output = temp_dir.path().join("patched.bin");
+
+        Patcher::new(&input)
+            .output(&output)
+            .with_output_permissions(0o644)
+            .patch()
+            .unwrap();
+        let mode = fs::metadata(&output).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o644);
+    }
+
+    #[test]
+    fn test_validate_cdn_urls() {
+        let patcher = Patcher::new("Wow.exe")
+            .version_url("http://cdn.lan/%s/%s/versions")
+            .cdns_url("http://cdn.lan/cdns");
+        assert!(patcher.validate_cdn_urls().is_ok());
+
+        let err = patcher
+            .clone()
+            .with_cdn_port(8080)
+            .cdns_url("http://a-rather-long-cdn-host.lan/cdns")
+            .cdns_url_v3("http://cdn.lan/%s/%s")
+            .validate_cdn_urls()
+            .unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+        assert!(err.message.contains("CDNs URL is 43 bytes, at most 39 fit"));
+        assert!(!err.message.contains("version URL"));
+        assert!(!err.message.contains("v3 URL"));
+
+        let err = patcher
+            .version_url("http://a-rather-long-cdn-host.lan/%s/%s/versions")
+            .cdns_url("http://a-rather-long-cdn-host.lan/wow/cdns")
+            .validate_cdn_urls()
+            .unwrap_err();
+        assert_eq!(err.message.lines().count(), 3);
+
+        // Validation runs before the input is read
+        let err = Patcher::new("missing.exe")
+            .cdns_url("http://a-rather-long-cdn-host.lan/wow/cdns")
+            .patch()
+            .unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+
+        // In-memory patching is validated too, and the check can be skipped
+        let input = crate::patterns::test_helpers::create_test_executable();
+        let patcher =
+            Patcher::new("game.bin").cdns_url("http://a-rather-long-cdn-host.lan/wow/cdns");
+        let err = patcher.patch_bytes(&input).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+        assert!(
+            patcher
+                .skip_url_validation(true)
+                .patch_bytes(&input)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_patch_script() {
+        let patcher = Patcher::new("Wow.exe")
+            .output("Wow's copy.exe")
+            .cdns_url("http://cdn.local/cdns")
+            .skip_backup(true);
+
+        let script = patcher.patch_script(false).unwrap();
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("exec wow-patcher '--warcraft-exe' 'Wow.exe'"));
+        assert!(script.contains("'--output-file' 'Wow'\\''s copy.exe'"));
+        assert!(script.contains(&format!(
+            "'--rsa-hex' '{}'",
+            hex::encode_upper(crate::trinity::RSA_MODULUS)
+        )));
+        assert!(script.contains("'--cdns-url' 'http://cdn.local/cdns'"));
+        assert!(script.contains("'--no-backup'"));
+        assert!(!script.contains("--version-url"));
+        assert!(!script.contains("--output-mode"));
+
+        let script = patcher
+            .clone()
+            .with_output_permissions(0o644)
+            .with_expected_build(58867)
+            .patch_script(false)
+            .unwrap();
+        assert!(script.contains("'--output-mode' '644'"));
+        assert!(script.contains("'--expected-build' '58867'"));
+
+        let script = patcher.patch_script(true).unwrap();
+        assert!(script.starts_with("@echo off\r\n"));
+        assert!(script.contains("wow-patcher \"--warcraft-exe\" \"Wow.exe\""));
+
+        let err = patcher.dual_rsa_mode(true).patch_script(false).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+    }
+
+    #[test]
+    fn test_patch_script_custom_cdn() {
+        let patcher = Patcher::new("Wow.exe").custom_cdn("http://my-cdn.local");
+
+        // The CLI derives the same URLs from the base, including the detected build
+        let script = patcher.patch_script(false).unwrap();
+        assert!(script.contains("'--cdn-base' 'http://my-cdn.local'"));
+        assert!(!script.contains("--version-url"));
+        assert!(!script.contains("--cdns-url"));
+
+        // URLs set after the base still need their own flags
+        let script = patcher
+            .cdns_url("http://other.local/cdns")
+            .cdns_url_v3("http://other.local/%s/%s")
+            .patch_script(false)
+            .unwrap();
+        assert!(script.contains("'--cdn-base' 'http://my-cdn.local'"));
+        assert!(script.contains("'--cdns-url' 'http://other.local/cdns'"));
+        assert!(script.contains("'--cdns-url-v3' 'http://other.local/%s/%s'"));
+    }
+
+    #[test]
+    fn test_patcher_server_preset() {
+        let patcher = Patcher::new("Wow.exe")
+            .server_preset("trinitycore")
+            .unwrap();
+        assert!(patcher.key_config.unwrap().is_trinity_core());
+        assert!(Patcher::new("Wow.exe").server_preset("nope").is_err());
+    }
+
+    #[test]
+    fn test_patcher_with_cdn_port() {
+        let patcher = Patcher::new("Wow.exe").with_cdn_port(8080);
+        assert_eq!(patcher.cdn_port, Some(8080));
     }
 
     #[test]
@@ -528,12 +2917,55 @@ mod tests {
     fn test_patcher_strip_codesign() {
         let patcher = Patcher::new("Wow.exe").strip_codesign(true);
         assert!(patcher.strip_codesign);
+        assert_eq!(patcher.codesign_options, CodesignOptions::default());
+
+        let opts = CodesignOptions {
+            action: crate::platform::CodesignAction::AdhocSign,
+            deep: true,
+            force: true,
+        };
+        let patcher = Patcher::new("Wow.exe").strip_codesign_options(opts.clone());
+        assert!(patcher.strip_codesign);
+        assert_eq!(patcher.codesign_options, opts);
+    }
+
+    #[test]
+    fn test_patch_and_sign_empty_identity() {
+        let err = Patcher::new("Wow.exe").patch_and_sign(" ").unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
     }
 
     #[test]
     fn test_patcher_verbose() {
         let patcher = Patcher::new("Wow.exe").verbose(true);
-        assert!(patcher.verbose);
+        assert_eq!(patcher.report_mode, ReportMode::Verbose);
+        let patcher = patcher.verbose(false);
+        assert_eq!(patcher.report_mode, ReportMode::Normal);
+        let patcher = patcher.report_mode(ReportMode::Json);
+        assert_eq!(patcher.report_mode, ReportMode::Json);
+    }
+
+    #[test]
+    fn test_patcher_file_size_limits() {
+        let patcher = Patcher::new("Wow.exe");
+        assert_eq!(patcher.max_file_size, DEFAULT_MAX_FILE_SIZE);
+        assert_eq!(patcher.min_file_size, DEFAULT_MIN_FILE_SIZE);
+
+        let patcher = patcher.with_max_file_size(4096).with_min_file_size(16);
+        assert_eq!(patcher.max_file_size, 4096);
+        assert_eq!(patcher.min_file_size, 16);
+    }
+
+    #[test]
+    fn test_patcher_expected_version() {
+        let patcher = Patcher::new("Wow.exe");
+        assert!(patcher.expected_version.is_none());
+
+        let patcher = patcher.with_expected_version(Version::new(11, 0, 7, 58867));
+        assert_eq!(
+            patcher.expected_version,
+            Some(Version::new(11, 0, 7, 58867))
+        );
     }
 
     #[test]
@@ -548,9 +2980,9 @@ mod tests {
 
         assert_eq!(patcher.output, Some(PathBuf::from("out.exe")));
         assert!(patcher.key_config.is_some());
-        assert!(patcher.version_url.is_some());
+        assert!(patcher.cdn_base.is_some());
         assert!(patcher.cdns_url.is_some());
-        assert!(patcher.verbose);
+        assert_eq!(patcher.report_mode, ReportMode::Verbose);
         assert!(patcher.dry_run);
         assert!(patcher.strip_codesign);
     }
@@ -558,17 +2990,179 @@ mod tests {
     #[test]
     fn test_patcher_default_output_exe() {
         let patcher = Patcher::new("Wow.exe");
-        // We can't test patch() directly without a real file, but we can test the logic
-        let input_str = patcher.input.to_string_lossy();
-        let expected = input_str.replace(".exe", "-patched.exe");
-        assert_eq!(expected, "Wow-patched.exe");
+        assert_eq!(patcher.output_path(), PathBuf::from("Wow-patched.exe"));
     }
 
     #[test]
     fn test_patcher_default_output_no_extension() {
         let patcher = Patcher::new("/path/to/WorldOfWarcraft");
-        let input_str = patcher.input.to_string_lossy();
-        let expected = format!("{}-patched", input_str);
-        assert_eq!(expected, "/path/to/WorldOfWarcraft-patched");
+        assert_eq!(
+            patcher.output_path(),
+            PathBuf::from("/path/to/WorldOfWarcraft-patched")
+        );
+    }
+
+    #[test]
+    fn test_with_output_suffix() {
+        let patcher = Patcher::new("Wow.exe").with_output_suffix("-trinity");
+        assert_eq!(patcher.output_path(), PathBuf::from("Wow-trinity.exe"));
+        let patcher = Patcher::new("/path/to/WorldOfWarcraft").with_output_suffix("-private");
+        assert_eq!(
+            patcher.output_path(),
+            PathBuf::from("/path/to/WorldOfWarcraft-private")
+        );
+
+        // Only a trailing .exe counts as the extension
+        assert_eq!(
+            suffixed_output_path(Path::new("games.exe/Wow.exe"), "-tc"),
+            PathBuf::from("games.exe/Wow-tc.exe")
+        );
+
+        // An explicit output path wins
+        let patcher = Patcher::new("Wow.exe")
+            .with_output_suffix("-trinity")
+            .output("out.exe");
+        assert_eq!(patcher.output_path(), PathBuf::from("out.exe"));
+    }
+
+    #[test]
+    fn test_patcher_progress_channel() {
+        let (patcher, receiver) = Patcher::new("Wow.exe").with_progress_channel();
+        assert!(patcher.progress_sender.is_some());
+
+        patcher
+            .progress_sender
+            .as_ref()
+            .unwrap()
+            .send(PatchProgress::complete(5));
+        assert_eq!(receiver.recv().unwrap(), PatchProgress::complete(5));
+
+        // A failed patch drops the sender without a completion message
+        assert!(patcher.patch().is_err());
+        assert!(receiver.recv().is_err());
+    }
+
+    #[test]
+    fn test_patch_progress_complete() {
+        let progress = PatchProgress::complete(5);
+        assert_eq!(progress.step_index, 5);
+        assert!(progress.is_complete());
+
+        let progress = PatchProgress {
+            step_index: 2,
+            total_steps: 5,
+            step_name: "Ed25519 public key".to_string(),
+        };
+        assert!(!progress.is_complete());
+    }
+
+    #[test]
+    fn test_estimate_patch_time() {
+        let small = tempfile::NamedTempFile::new().unwrap();
+        fs::write(small.path(), vec![0u8; 1024]).unwrap();
+        let large = tempfile::NamedTempFile::new().unwrap();
+        fs::write(large.path(), vec![0u8; 1024 * 1024]).unwrap();
+
+        let small_estimate = Patcher::new(small.path()).estimate_patch_time().unwrap();
+        let large_estimate = Patcher::new(large.path()).estimate_patch_time().unwrap();
+        assert!(large_estimate >= small_estimate);
+
+        assert!(
+            Patcher::new("/nonexistent/Wow.exe")
+                .estimate_patch_time()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_backup_timestamp() {
+        assert_eq!(backup_timestamp(UNIX_EPOCH), "19700101_000000");
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(backup_timestamp(time), "20240229_123456");
+    }
+
+    #[test]
+    fn test_backup_and_unpatch_from_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("Wow.exe");
+        fs::write(&input, b"original").unwrap();
+
+        let patcher = Patcher::new(&input).with_backup_dir(dir.path());
+        let backup_path = patcher.backup_path().unwrap();
+        assert!(
+            backup_path
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("Wow.exe.bak.")
+        );
+        assert!(patcher.dry_run(true).backup_path().is_none());
+
+        // An explicit skip wins over the backup directory, in either order
+        let patcher = Patcher::new(&input)
+            .skip_backup(true)
+            .with_backup_dir(dir.path());
+        assert!(patcher.backup_path().is_none());
+
+        // The backup is written even though patching this fake input fails
+        let patcher = Patcher::new(&input).with_backup_dir(dir.path());
+        assert!(patcher.patch().is_err());
+        let backup = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path != &input)
+            .unwrap();
+        assert_eq!(fs::read(&backup).unwrap(), b"original");
+
+        fs::write(&input, b"patched").unwrap();
+        Patcher::unpatch_from_backup(&backup, &input).unwrap();
+        assert_eq!(fs::read(&input).unwrap(), b"original");
+
+        assert!(Patcher::unpatch_from_backup(dir.path().join("missing"), &input).is_err());
+    }
+
+    #[test]
+    fn test_backup_failure_aborts_patch() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("Wow.exe");
+        fs::write(&input, b"original").unwrap();
+
+        let result = Patcher::new(&input)
+            .with_backup_dir(dir.path().join("missing"))
+            .patch();
+        let err = result.unwrap_err();
+        assert!(err.message.contains("back up"));
+    }
+
+    #[test]
+    fn test_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("Wow.exe");
+
+        fs::write(&input, b"original").unwrap();
+        assert!(!Patcher::new(&input).verify().unwrap());
+
+        let mut patched = b"header".to_vec();
+        patched.extend_from_slice(KeyConfig::trinity_core().rsa_modulus());
+        fs::write(&input, &patched).unwrap();
+        assert!(Patcher::new(&input).verify().unwrap());
+
+        // Keys alone are not enough while the portal suffix is untouched
+        patched.extend_from_slice(PORTAL_ORIGINAL);
+        fs::write(&input, patched).unwrap();
+        assert!(!Patcher::new(&input).verify().unwrap());
+
+        assert!(Patcher::new(dir.path().join("missing")).verify().is_err());
+    }
+
+    #[test]
+    fn test_inspect() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("Wow.exe");
+        fs::write(&input, b"xx.actual.battle.netxx").unwrap();
+
+        let scan = Patcher::new(&input).inspect().unwrap();
+        assert_eq!(scan.found.get("portal"), Some(&2));
+        assert!(scan.missing.contains(&"connect_to_modulus"));
     }
 }
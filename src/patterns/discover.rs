@@ -0,0 +1,343 @@
+//! Heuristic search for key material in builds where the known patterns no longer match.
+//!
+//! This is a best-effort aid for finding new search bytes after a client
+//! update. Every candidate must be verified manually before it is added to
+//! the known patterns.
+
+use crate::binary::{BinaryFormat, ParsedBinary};
+use crate::patterns::{
+    connect_to_modulus_pattern, crypto_ed_public_key_pattern, crypto_rsa_modulus_pattern,
+    signature_modulus_pattern,
+};
+use goblin::Object;
+use std::fmt;
+use std::ops::Range;
+
+/// Size of an RSA-2048 modulus in bytes
+const RSA_MODULUS_SIZE: usize = 256;
+/// Size of an encoded Ed25519 public key in bytes
+const ED25519_KEY_SIZE: usize = 32;
+/// Alignment at which RSA moduli are searched for
+const RSA_ALIGNMENT: usize = 16;
+/// Alignment at which Ed25519 keys are searched for
+const ED25519_ALIGNMENT: usize = 8;
+/// Minimum number of distinct byte values in an RSA candidate (random data averages ~162)
+const MIN_RSA_DISTINCT_BYTES: usize = 128;
+/// Minimum number of distinct byte values in an Ed25519 candidate (random data averages ~30)
+const MIN_ED25519_DISTINCT_BYTES: usize = 28;
+/// Bytes on either side of an Ed25519 candidate that must contain a zero byte
+const ED25519_GUARD_SIZE: usize = 8;
+/// Highest confidence given to a candidate that does not match a known pattern
+const MAX_HEURISTIC_CONFIDENCE: f32 = 0.9;
+
+/// Kind of key material a discovered candidate looks like.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscoveredPatternType {
+    /// 256-byte little-endian RSA modulus
+    RsaModulus,
+    /// 32-byte encoded Ed25519 public key
+    Ed25519PublicKey,
+}
+
+impl fmt::Display for DiscoveredPatternType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiscoveredPatternType::RsaModulus => write!(f, "RSA modulus"),
+            DiscoveredPatternType::Ed25519PublicKey => write!(f, "Ed25519 public key"),
+        }
+    }
+}
+
+/// A candidate location for key material found by [`auto_discover`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveredPattern {
+    pub pattern_type: DiscoveredPatternType,
+    pub file_offset: usize,
+    /// First 8 bytes of the candidate as lowercase hex, usable as a new pattern
+    pub first_8_bytes_hex: String,
+    /// Section containing the candidate, when the binary format is known
+    pub section_name: Option<String>,
+    /// Between 0.0 and 1.0, where 1.0 means the bytes match a known pattern
+    pub confidence: f32,
+}
+
+/// Search the read-only data of an executable for likely RSA moduli and Ed25519 keys.
+///
+/// RSA candidates are 256-byte runs with the low bit of the first byte and
+/// the high bit of the last byte set, as in a little-endian 2048-bit modulus,
+/// and with no long zero runs. Ed25519 candidates are isolated 32-byte runs
+/// that form a canonical point encoding. Both must look random.
+///
+/// PE binaries are searched in `.rdata` and `.data`, thin Mach-O binaries in
+/// their data segments and `__TEXT.__const`. Data in any other format is
+/// searched as a whole. Results are ordered by descending confidence.
+pub fn auto_discover(data: &[u8]) -> Vec<DiscoveredPattern> {
+    let mut discovered = Vec::new();
+    for (section_name, range) in data_regions(data) {
+        let rsa_ranges = discover_rsa(data, range.clone(), &section_name, &mut discovered);
+        discover_ed25519(data, range, &section_name, &rsa_ranges, &mut discovered);
+    }
+
+    discovered.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then(a.file_offset.cmp(&b.file_offset))
+    });
+    discovered
+}
+
+/// File ranges that may hold key material, with their section names.
+fn data_regions(data: &[u8]) -> Vec<(Option<String>, Range<usize>)> {
+    let clamp = |start: u64, size: u64| {
+        let start = (start as usize).min(data.len());
+        let end = start.saturating_add(size as usize).min(data.len());
+        start..end
+    };
+
+    let binary = ParsedBinary::new(data);
+    match binary.obj {
+        Object::PE(pe) => pe
+            .sections
+            .iter()
+            .filter_map(|section| {
+                let name = String::from_utf8_lossy(&section.name)
+                    .trim_end_matches('\0')
+                    .to_string();
+                (name == ".rdata" || name == ".data").then(|| {
+                    let range = clamp(
+                        section.pointer_to_raw_data as u64,
+                        section.size_of_raw_data as u64,
+                    );
+                    (Some(name), range)
+                })
+            })
+            .collect(),
+        Object::Mach(goblin::mach::Mach::Binary(macho)) => {
+            let mut regions = Vec::new();
+            for segment in &macho.segments {
+                let (Ok(seg_name), Ok(sections)) = (segment.name(), segment.sections()) else {
+                    continue;
+                };
+                for (sect, _) in sections.iter() {
+                    let Ok(section_name) = sect.name() else {
+                        continue;
+                    };
+                    if seg_name == "__DATA"
+                        || seg_name == "__DATA_CONST"
+                        || (seg_name == "__TEXT" && section_name == "__const")
+                    {
+                        let range = clamp(sect.offset as u64, sect.size);
+                        regions.push((Some(format!("{}.{}", seg_name, section_name)), range));
+                    }
+                }
+            }
+            regions
+        }
+        // Fat binaries need a slice to be selected first
+        Object::Mach(_) => Vec::new(),
+        _ if binary.format == BinaryFormat::Unknown => vec![(None, 0..data.len())],
+        _ => Vec::new(),
+    }
+}
+
+/// Record RSA modulus candidates in `range`, returning the ranges they cover.
+fn discover_rsa(
+    data: &[u8],
+    range: Range<usize>,
+    section_name: &Option<String>,
+    discovered: &mut Vec<DiscoveredPattern>,
+) -> Vec<Range<usize>> {
+    let known = [
+        connect_to_modulus_pattern(),
+        signature_modulus_pattern(),
+        crypto_rsa_modulus_pattern(),
+    ];
+
+    let mut covered = Vec::new();
+    let mut offset = range.start.next_multiple_of(RSA_ALIGNMENT);
+    while offset + RSA_MODULUS_SIZE <= range.end {
+        let window = &data[offset..offset + RSA_MODULUS_SIZE];
+        match rsa_confidence(window, &known) {
+            Some(confidence) => {
+                discovered.push(candidate(
+                    DiscoveredPatternType::RsaModulus,
+                    window,
+                    offset,
+                    section_name,
+                    confidence,
+                ));
+                covered.push(offset..offset + RSA_MODULUS_SIZE);
+                offset += RSA_MODULUS_SIZE;
+            }
+            None => offset += RSA_ALIGNMENT,
+        }
+    }
+    covered
+}
+
+/// Record Ed25519 key candidates in `range` that do not overlap an RSA candidate.
+fn discover_ed25519(
+    data: &[u8],
+    range: Range<usize>,
+    section_name: &Option<String>,
+    rsa_ranges: &[Range<usize>],
+    discovered: &mut Vec<DiscoveredPattern>,
+) {
+    let known = [crypto_ed_public_key_pattern()];
+
+    let mut offset = range.start.next_multiple_of(ED25519_ALIGNMENT);
+    while offset + ED25519_KEY_SIZE <= range.end {
+        let end = offset + ED25519_KEY_SIZE;
+        let overlaps_rsa = rsa_ranges
+            .iter()
+            .any(|rsa| offset < rsa.end && rsa.start < end);
+        let before = &data[offset.saturating_sub(ED25519_GUARD_SIZE).max(range.start)..offset];
+        let after = &data[end..(end + ED25519_GUARD_SIZE).min(range.end)];
+        let isolated =
+            (before.is_empty() || before.contains(&0)) && (after.is_empty() || after.contains(&0));
+
+        if !overlaps_rsa
+            && isolated
+            && let Some(confidence) = ed25519_confidence(&data[offset..end], &known)
+        {
+            discovered.push(candidate(
+                DiscoveredPatternType::Ed25519PublicKey,
+                &data[offset..end],
+                offset,
+                section_name,
+                confidence,
+            ));
+            offset += ED25519_KEY_SIZE;
+        } else {
+            offset += ED25519_ALIGNMENT;
+        }
+    }
+}
+
+/// Score a 256-byte window as an RSA modulus, or `None` if it cannot be one.
+fn rsa_confidence(window: &[u8], known: &[&Vec<i16>]) -> Option<f32> {
+    // A modulus is odd and exactly 2048 bits long
+    if window[0] & 0x01 == 0 || window[RSA_MODULUS_SIZE - 1] & 0x80 == 0 {
+        return None;
+    }
+    if window.windows(4).any(|run| run == [0; 4]) {
+        return None;
+    }
+    score(window, MIN_RSA_DISTINCT_BYTES, known)
+}
+
+/// Score a 32-byte window as an Ed25519 public key, or `None` if it cannot be one.
+fn ed25519_confidence(window: &[u8], known: &[&Vec<i16>]) -> Option<f32> {
+    // The y coordinate (sign bit cleared) must be below p = 2^255 - 19
+    let last = ED25519_KEY_SIZE - 1;
+    let non_canonical = window[0] >= 0xed
+        && window[1..last].iter().all(|&b| b == 0xff)
+        && window[last] & 0x7f == 0x7f;
+    if non_canonical {
+        return None;
+    }
+    score(window, MIN_ED25519_DISTINCT_BYTES, known)
+}
+
+/// Confidence from byte diversity, or 1.0 when the window starts with a known pattern.
+fn score(window: &[u8], min_distinct: usize, known: &[&Vec<i16>]) -> Option<f32> {
+    let mut seen = [false; 256];
+    for &b in window {
+        seen[b as usize] = true;
+    }
+    let distinct = seen.iter().filter(|&&s| s).count();
+    if distinct < min_distinct {
+        return None;
+    }
+
+    let matches_known = known.iter().any(|pattern| {
+        pattern
+            .iter()
+            .zip(window)
+            .all(|(&p, &b)| p < 0 || p as u8 == b)
+    });
+    if matches_known {
+        return Some(1.0);
+    }
+
+    // Expected number of distinct values in uniformly random data of this length
+    let expected = 256.0 * (1.0 - (255.0f32 / 256.0).powi(window.len() as i32));
+    Some((MAX_HEURISTIC_CONFIDENCE * distinct as f32 / expected).min(MAX_HEURISTIC_CONFIDENCE))
+}
+
+fn candidate(
+    pattern_type: DiscoveredPatternType,
+    window: &[u8],
+    file_offset: usize,
+    section_name: &Option<String>,
+    confidence: f32,
+) -> DiscoveredPattern {
+    DiscoveredPattern {
+        pattern_type,
+        file_offset,
+        first_8_bytes_hex: hex::encode(&window[..8]),
+        section_name: section_name.clone(),
+        confidence,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trinity::{CRYPTO_ED25519_PUBLIC_KEY, RSA_MODULUS};
+
+    #[test]
+    fn test_auto_discover_finds_keys() {
+        let mut data = vec![0u8; 64];
+        data.extend_from_slice(RSA_MODULUS);
+        data.extend_from_slice(&[0u8; 64]);
+        data.extend_from_slice(CRYPTO_ED25519_PUBLIC_KEY);
+        data.extend_from_slice(&[0u8; 64]);
+
+        let discovered = auto_discover(&data);
+        assert_eq!(discovered.len(), 2);
+
+        let rsa = &discovered
+            .iter()
+            .find(|d| d.pattern_type == DiscoveredPatternType::RsaModulus)
+            .unwrap();
+        assert_eq!(rsa.file_offset, 64);
+        assert_eq!(rsa.first_8_bytes_hex, hex::encode(&RSA_MODULUS[..8]));
+        assert_eq!(rsa.section_name, None);
+        assert!(rsa.confidence > 0.5 && rsa.confidence < 1.0);
+
+        let ed25519 = &discovered
+            .iter()
+            .find(|d| d.pattern_type == DiscoveredPatternType::Ed25519PublicKey)
+            .unwrap();
+        assert_eq!(ed25519.file_offset, 64 + 256 + 64);
+    }
+
+    #[test]
+    fn test_auto_discover_known_pattern_confidence() {
+        let mut modulus = RSA_MODULUS.to_vec();
+        for (byte, &p) in modulus.iter_mut().zip(connect_to_modulus_pattern()) {
+            *byte = p as u8;
+        }
+        let mut data = vec![0u8; 32];
+        data.extend_from_slice(&modulus);
+
+        let discovered = auto_discover(&data);
+        assert_eq!(discovered[0].file_offset, 32);
+        assert_eq!(discovered[0].first_8_bytes_hex, "91d59bb7d4e183a5");
+        assert_eq!(discovered[0].confidence, 1.0);
+    }
+
+    #[test]
+    fn test_auto_discover_rejects_low_entropy() {
+        assert!(auto_discover(&[0u8; 4096]).is_empty());
+
+        let text: Vec<u8> = b"http://%s.patch.battle.net:1119/%s/versions\0"
+            .iter()
+            .copied()
+            .cycle()
+            .take(4096)
+            .collect();
+        assert!(auto_discover(&text).is_empty());
+    }
+}
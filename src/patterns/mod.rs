@@ -1,8 +1,15 @@
 #[cfg(test)]
 use crate::binary::PatternExt;
-use crate::binary::{Pattern, string_to_pattern};
+use crate::binary::{ParsedBinary, Pattern, SectionInfo, multi_pattern_search, string_to_pattern};
+use crate::platform::ClientType;
+use std::collections::HashMap;
 use std::sync::OnceLock;
 
+pub mod discover;
+pub use discover::{DiscoveredPattern, DiscoveredPatternType, auto_discover};
+#[cfg(test)]
+pub(crate) mod test_helpers;
+
 pub static PORTAL_PATTERN: OnceLock<Pattern> = OnceLock::new();
 pub static CONNECT_TO_MODULUS_PATTERN: OnceLock<Pattern> = OnceLock::new();
 pub static SIGNATURE_MODULUS_PATTERN: OnceLock<Pattern> = OnceLock::new();
@@ -58,14 +65,216 @@ pub fn cdns_url_pattern() -> &'static Pattern {
     CDNS_URL_PATTERN.get_or_init(|| string_to_pattern("http://%s.patch.battle.net:1119/%s/cdns"))
 }
 
+/// Known beginning of the certificate bundle JSON embedded in the client
+pub const CERT_BUNDLE_ORIGINAL: &str = "{\"Created\":";
+
 pub fn cert_bundle_pattern() -> &'static Pattern {
-    CERT_BUNDLE_PATTERN.get_or_init(|| string_to_pattern("{\"Created\":"))
+    CERT_BUNDLE_PATTERN.get_or_init(|| string_to_pattern(CERT_BUNDLE_ORIGINAL))
+}
+
+/// Names of every known pattern, in the order the patcher applies them.
+pub const PATTERN_NAMES: &[&str] = &[
+    "portal",
+    "connect_to_modulus",
+    "signature_modulus",
+    "crypto_rsa_modulus",
+    "crypto_ed_public_key",
+    "version_url",
+    "version_url_v2",
+    "version_url_v3",
+    "cdns_url",
+    "cert_bundle",
+];
+
+/// Look up a known pattern by name.
+pub fn pattern_by_name(name: &str) -> Option<&'static Pattern> {
+    let pattern = match name {
+        "portal" => portal_pattern(),
+        "connect_to_modulus" => connect_to_modulus_pattern(),
+        "signature_modulus" => signature_modulus_pattern(),
+        "crypto_rsa_modulus" => crypto_rsa_modulus_pattern(),
+        "crypto_ed_public_key" => crypto_ed_public_key_pattern(),
+        "version_url" => version_url_pattern(),
+        "version_url_v2" => version_url_v2_pattern(),
+        "version_url_v3" => version_url_v3_pattern(),
+        "cdns_url" => cdns_url_pattern(),
+        "cert_bundle" => cert_bundle_pattern(),
+        _ => return None,
+    };
+    Some(pattern)
+}
+
+/// Human-readable documentation for a known pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternDescription {
+    pub name: String,
+    /// Pattern bytes as space-separated hex, with `??` for wildcards
+    pub hex_bytes: String,
+    pub purpose: String,
+    /// Client builds the pattern has been confirmed in (empty when not tracked)
+    pub known_builds: Vec<u16>,
+    /// Section the pattern is expected in
+    pub section: &'static str,
+}
+
+/// Describe a known pattern by name.
+pub fn describe(name: &str) -> Option<PatternDescription> {
+    let pattern = pattern_by_name(name)?;
+    let purpose = match name {
+        "portal" => "Battle.net portal host suffix, nulled to disable the portal connection",
+        "connect_to_modulus" => {
+            "RSA modulus used to verify the ConnectTo packet; the first 8 bytes are the search key"
+        }
+        "signature_modulus" => {
+            "RSA modulus used for signature verification in builds without the ConnectTo key"
+        }
+        "crypto_rsa_modulus" => "RSA modulus in the crypto module, the last RSA fallback",
+        "crypto_ed_public_key" => {
+            "Ed25519 public key used for signature verification in retail clients"
+        }
+        "version_url" => "Legacy patch server URL the client fetches version information from",
+        "version_url_v2" => "Version server URL the client fetches version information from",
+        "version_url_v3" => {
+            "Unified product API URL serving both versions and cdns (Classic 1.15.8+)"
+        }
+        "cdns_url" => "Legacy patch server URL the client fetches CDN configuration from",
+        "cert_bundle" => "Start of the embedded certificate bundle (not patched)",
+        _ => return None,
+    };
+
+    let hex_bytes = pattern
+        .iter()
+        .map(|&b| {
+            if b < 0 {
+                "??".to_string()
+            } else {
+                format!("{:02X}", b)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Some(PatternDescription {
+        name: name.to_string(),
+        hex_bytes,
+        purpose: purpose.to_string(),
+        known_builds: Vec::new(),
+        section: ".rdata",
+    })
+}
+
+/// Key patterns to try for a client, in the order they are tried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternSet {
+    /// RSA modulus patterns, labelled with the slot name
+    pub rsa_modulus: Vec<(&'static str, &'static Pattern)>,
+    /// Ed25519 public key pattern, `None` when the client has no Ed25519 key
+    pub ed25519_public_key: Option<&'static Pattern>,
+}
+
+impl PatternSet {
+    /// Every known key pattern.
+    pub fn all() -> Self {
+        Self {
+            rsa_modulus: vec![
+                ("ConnectTo", connect_to_modulus_pattern()),
+                ("Signature", signature_modulus_pattern()),
+                ("Crypto", crypto_rsa_modulus_pattern()),
+            ],
+            ed25519_public_key: Some(crypto_ed_public_key_pattern()),
+        }
+    }
+}
+
+/// Select the key patterns for a client type when the build number is unknown.
+///
+/// Classic clients use the ConnectTo modulus, Classic Era and Hardcore
+/// clients use only RSA keys, and Retail, Season of Discovery and unknown
+/// clients try every pattern.
+pub fn for_client_type(client: ClientType) -> PatternSet {
+    let all = PatternSet::all();
+    match client {
+        ClientType::Retail | ClientType::ClassicSeasonOfDiscovery | ClientType::Unknown => all,
+        ClientType::Classic => PatternSet {
+            rsa_modulus: vec![("ConnectTo", connect_to_modulus_pattern())],
+            ..all
+        },
+        ClientType::ClassicEra | ClientType::ClassicHardcore => PatternSet {
+            ed25519_public_key: None,
+            ..all
+        },
+    }
+}
+
+/// Result of scanning a binary for every known pattern.
+#[derive(Debug, Clone, Default)]
+pub struct PatternScanResult {
+    /// Offset of the first occurrence of each pattern that was found
+    pub found: HashMap<&'static str, usize>,
+    /// Patterns that do not occur in the binary, in [`PATTERN_NAMES`] order
+    pub missing: Vec<&'static str>,
+    /// Section containing each found pattern, when the binary format is known
+    pub sections: HashMap<&'static str, SectionInfo>,
+}
+
+/// Scan a binary for every known pattern.
+///
+/// All patterns are found in a single pass with [`multi_pattern_search`].
+/// With the `rayon` feature enabled large binaries are split into chunks
+/// that are scanned in parallel.
+pub fn find_in_binary(data: &[u8]) -> PatternScanResult {
+    let patterns: Vec<(&str, &Pattern)> = PATTERN_NAMES
+        .iter()
+        .filter_map(|&name| pattern_by_name(name).map(|pattern| (name, pattern)))
+        .collect();
+    let search = multi_pattern_search(data, &patterns);
+
+    let binary = ParsedBinary::new(data);
+    let mut result = PatternScanResult::default();
+    for &name in PATTERN_NAMES {
+        match search.found.get(name).copied() {
+            Some(offset) => {
+                result.found.insert(name, offset);
+                if let Some(section) = binary.section_at(offset) {
+                    result.sections.insert(name, section);
+                }
+            }
+            None => result.missing.push(name),
+        }
+    }
+    result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_for_client_type() {
+        assert_eq!(for_client_type(ClientType::Retail), PatternSet::all());
+        assert_eq!(for_client_type(ClientType::Unknown), PatternSet::all());
+
+        let classic = for_client_type(ClientType::Classic);
+        assert_eq!(
+            classic.rsa_modulus,
+            vec![("ConnectTo", connect_to_modulus_pattern())]
+        );
+        assert!(classic.ed25519_public_key.is_some());
+
+        assert_eq!(
+            for_client_type(ClientType::ClassicSeasonOfDiscovery),
+            PatternSet::all()
+        );
+        assert_eq!(
+            for_client_type(ClientType::ClassicHardcore),
+            for_client_type(ClientType::ClassicEra)
+        );
+
+        let classic_era = for_client_type(ClientType::ClassicEra);
+        assert_eq!(classic_era.rsa_modulus.len(), 3);
+        assert!(classic_era.ed25519_public_key.is_none());
+    }
+
     #[test]
     fn test_portal_pattern() {
         let expected = string_to_pattern(".actual.battle.net");
@@ -86,7 +295,7 @@ mod tests {
         assert_eq!(connect_to_modulus_pattern().len(), 8);
 
         for &val in connect_to_modulus_pattern().iter() {
-            assert!(val >= 0 && val <= 255);
+            assert!((0..=255).contains(&val));
         }
     }
 
@@ -97,7 +306,7 @@ mod tests {
         assert_eq!(crypto_ed_public_key_pattern().len(), 8);
 
         for &val in crypto_ed_public_key_pattern().iter() {
-            assert!(val >= 0 && val <= 255);
+            assert!((0..=255).contains(&val));
         }
     }
 
@@ -197,7 +406,7 @@ mod tests {
         assert_eq!(signature_modulus_pattern().len(), 8);
 
         for &val in signature_modulus_pattern().iter() {
-            assert!(val >= 0 && val <= 255);
+            assert!((0..=255).contains(&val));
         }
     }
 
@@ -208,7 +417,7 @@ mod tests {
         assert_eq!(crypto_rsa_modulus_pattern().len(), 8);
 
         for &val in crypto_rsa_modulus_pattern().iter() {
-            assert!(val >= 0 && val <= 255);
+            assert!((0..=255).contains(&val));
         }
     }
 
@@ -232,4 +441,45 @@ mod tests {
             assert_eq!(cert_bundle_pattern()[i], ch as i16);
         }
     }
+
+    #[test]
+    fn test_describe() {
+        let description = describe("connect_to_modulus").unwrap();
+        assert_eq!(description.name, "connect_to_modulus");
+        assert_eq!(description.hex_bytes, "91 D5 9B B7 D4 E1 83 A5");
+        assert!(description.purpose.contains("ConnectTo"));
+        assert_eq!(description.section, ".rdata");
+
+        assert!(describe("unknown").is_none());
+    }
+
+    #[test]
+    fn test_every_pattern_is_described() {
+        for name in PATTERN_NAMES {
+            let description = describe(name).unwrap();
+            assert_eq!(
+                description.hex_bytes.split(' ').count(),
+                pattern_by_name(name).unwrap().len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_in_binary() {
+        let mut data = vec![0u8; 90];
+        test_helpers::embed_patterns_in_buffer(
+            &mut data,
+            &[("portal", 64), ("connect_to_modulus", 82)],
+        )
+        .unwrap();
+
+        let result = find_in_binary(&data);
+        assert_eq!(result.found.get("portal"), Some(&64));
+        assert_eq!(result.found.get("connect_to_modulus"), Some(&82));
+        assert_eq!(result.missing.len(), PATTERN_NAMES.len() - 2);
+        assert!(result.missing.contains(&"cdns_url"));
+
+        // Raw data has no sections to report
+        assert!(result.sections.is_empty());
+    }
 }
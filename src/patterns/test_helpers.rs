@@ -0,0 +1,96 @@
+//! Helpers for building synthetic executables in tests.
+
+use crate::patterns::pattern_by_name;
+
+/// Write the bytes of each named pattern into `data` at the given offset.
+///
+/// Names are those in [`PATTERN_NAMES`](crate::patterns::PATTERN_NAMES).
+/// Wildcard positions are written as zero.
+pub fn embed_patterns_in_buffer(
+    data: &mut [u8],
+    patterns: &[(&str, usize)],
+) -> Result<(), &'static str> {
+    for &(name, offset) in patterns {
+        let pattern = pattern_by_name(name).ok_or("unknown pattern name")?;
+        let end = offset
+            .checked_add(pattern.len())
+            .filter(|&end| end <= data.len())
+            .ok_or("pattern does not fit in buffer")?;
+        for (byte, &value) in data[offset..end].iter_mut().zip(pattern.iter()) {
+            *byte = if value < 0 { 0 } else { value as u8 };
+        }
+    }
+    Ok(())
+}
+
+/// Build a minimal PE32+ image with a single `.rdata` section holding the patterns.
+pub fn create_test_executable() -> Vec<u8> {
+    const RAW_OFFSET: usize = 0x200;
+    const RAW_SIZE: usize = 0x1000;
+
+    let mut data = vec![0u8; RAW_OFFSET + RAW_SIZE];
+    data[0..2].copy_from_slice(b"MZ");
+    data[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+
+    // PE signature and COFF header
+    data[0x80..0x84].copy_from_slice(b"PE\0\0");
+    data[0x84..0x86].copy_from_slice(&0x8664u16.to_le_bytes());
+    data[0x86..0x88].copy_from_slice(&1u16.to_le_bytes());
+    data[0x94..0x96].copy_from_slice(&240u16.to_le_bytes());
+    data[0x96..0x98].copy_from_slice(&0x22u16.to_le_bytes());
+
+    // PE32+ optional header
+    let opt = 0x98;
+    data[opt..opt + 2].copy_from_slice(&0x20Bu16.to_le_bytes());
+    data[opt + 24..opt + 32].copy_from_slice(&0x1_4000_0000u64.to_le_bytes());
+    data[opt + 32..opt + 36].copy_from_slice(&0x1000u32.to_le_bytes());
+    data[opt + 36..opt + 40].copy_from_slice(&0x200u32.to_le_bytes());
+    data[opt + 56..opt + 60].copy_from_slice(&0x2000u32.to_le_bytes());
+    data[opt + 60..opt + 64].copy_from_slice(&(RAW_OFFSET as u32).to_le_bytes());
+    data[opt + 68..opt + 70].copy_from_slice(&3u16.to_le_bytes());
+    data[opt + 108..opt + 112].copy_from_slice(&16u32.to_le_bytes());
+
+    // .rdata section header
+    let sect = opt + 240;
+    data[sect..sect + 6].copy_from_slice(b".rdata");
+    data[sect + 8..sect + 12].copy_from_slice(&(RAW_SIZE as u32).to_le_bytes());
+    data[sect + 12..sect + 16].copy_from_slice(&0x1000u32.to_le_bytes());
+    data[sect + 16..sect + 20].copy_from_slice(&(RAW_SIZE as u32).to_le_bytes());
+    data[sect + 20..sect + 24].copy_from_slice(&(RAW_OFFSET as u32).to_le_bytes());
+    data[sect + 36..sect + 40].copy_from_slice(&0x4000_0040u32.to_le_bytes());
+
+    embed_patterns_in_buffer(
+        &mut data,
+        &[
+            ("portal", RAW_OFFSET + 0x100),
+            ("connect_to_modulus", RAW_OFFSET + 0x300),
+        ],
+    )
+    .unwrap();
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::find_in_binary;
+
+    #[test]
+    fn test_embed_patterns_in_buffer() {
+        let mut data = vec![0xAAu8; 256];
+        embed_patterns_in_buffer(&mut data, &[("portal", 16), ("cdns_url", 64)]).unwrap();
+
+        let result = find_in_binary(&data);
+        assert_eq!(result.found.get("portal"), Some(&16));
+        assert_eq!(result.found.get("cdns_url"), Some(&64));
+
+        assert_eq!(
+            embed_patterns_in_buffer(&mut data, &[("nope", 0)]),
+            Err("unknown pattern name")
+        );
+        assert_eq!(
+            embed_patterns_in_buffer(&mut data, &[("portal", 250)]),
+            Err("pattern does not fit in buffer")
+        );
+    }
+}
@@ -1,10 +1,61 @@
 use crate::errors::WowPatcherError;
+pub use crate::platform::{CodesignAction, CodesignOptions, MacosVersion};
+use crate::report::Reporter;
 use std::path::Path;
 use std::process::Command;
 
-pub fn remove_codesign(path: &Path) -> Result<(), WowPatcherError> {
+/// First macOS major version with the current `codesign --remove-signature` behavior
+const CODESIGN_STABLE_MAJOR: u32 = 12;
+
+/// Read the host macOS version from `sw_vers -productVersion`.
+pub fn macos_version() -> Option<MacosVersion> {
+    let output = Command::new("sw_vers")
+        .arg("-productVersion")
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    MacosVersion::parse(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Run `codesign` on a patched executable, removing or replacing its signature.
+///
+/// A new signature carries `identifier`, as `codesign` would otherwise derive
+/// it from the file name of `path`. A warning about older macOS releases is
+/// sent to `reporter`.
+pub fn remove_codesign(
+    path: &Path,
+    identifier: &str,
+    opts: CodesignOptions,
+    reporter: &mut dyn Reporter,
+) -> Result<(), WowPatcherError> {
+    if matches!(
+        opts.action,
+        CodesignAction::RemoveSignature | CodesignAction::Sign(_)
+    ) && let Some(version) = macos_version().filter(|v| v.major < CODESIGN_STABLE_MAJOR)
+    {
+        reporter.warning(&format!(
+            "⚠️  Running on macOS {}. codesign --remove-signature behaves differently before macOS 12 (Monterey), verify the patched client launches.",
+            version
+        ));
+    }
+
+    // Signing over a stale signature can fail, so drop it first
+    if matches!(opts.action, CodesignAction::Sign(_)) {
+        run_codesign(&["--remove-signature"], path)?;
+    }
+    let mut args = opts.args();
+    if opts.action != CodesignAction::RemoveSignature {
+        args.extend(["--identifier", identifier]);
+    }
+    run_codesign(&args, path)
+}
+
+/// Run `codesign` with `args` on `path`, failing with its stderr on a non-zero exit.
+fn run_codesign(args: &[&str], path: &Path) -> Result<(), WowPatcherError> {
     let output = Command::new("codesign")
-        .arg("--remove-signature")
+        .args(args)
         .arg(path)
         .output()
         .map_err(|e| {
@@ -29,6 +80,7 @@ pub fn remove_codesign(path: &Path) -> Result<(), WowPatcherError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::report::{ConsoleReporter, ReportMode};
     use std::fs;
     use tempfile::TempDir;
 
@@ -40,6 +92,11 @@ mod tests {
         fs::write(&test_file, b"test binary content").unwrap();
 
         // This might fail on CI without proper setup, so we just test it doesn't panic
-        let _ = remove_codesign(&test_file);
+        let _ = remove_codesign(
+            &test_file,
+            "test_binary",
+            CodesignOptions::default(),
+            &mut ConsoleReporter::new(ReportMode::Silent),
+        );
     }
 }
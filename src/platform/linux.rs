@@ -1,32 +1,169 @@
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
 pub fn find_wow_executable() -> Option<PathBuf> {
     let home = env::var("HOME").ok()?;
 
-    let possible_paths = vec![
-        format!(
+    home_candidate_paths(&home)
+        .into_iter()
+        .find(|path| path.exists())
+        .or_else(|| find_wow_via_steam(None))
+}
+
+/// Every location checked for a WoW executable, existing or not.
+///
+/// Covers the default WINE prefixes under `$HOME`, the prefix named by
+/// `WINEPREFIX` and the Steam Proton prefixes.
+pub fn candidate_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Ok(home) = env::var("HOME") {
+        paths.extend(home_candidate_paths(&home));
+    }
+    if let Some(prefix) = env::var_os("WINEPREFIX") {
+        let drive_c = Path::new(&prefix).join("drive_c");
+        paths.extend(DRIVE_C_PATHS.iter().map(|relative| drive_c.join(relative)));
+    }
+    paths.extend(steam_candidate_paths(None));
+    paths
+}
+
+fn home_candidate_paths(home: &str) -> Vec<PathBuf> {
+    vec![
+        PathBuf::from(format!(
             "{}/.wine/drive_c/Program Files/World of Warcraft/_retail_/Wow.exe",
             home
-        ),
-        format!(
+        )),
+        PathBuf::from(format!(
             "{}/.wine/drive_c/Program Files (x86)/World of Warcraft/_retail_/Wow.exe",
             home
-        ),
-        format!(
+        )),
+        PathBuf::from(format!(
             "{}/Games/world-of-warcraft/drive_c/Program Files/World of Warcraft/_retail_/Wow.exe",
             home
-        ),
-    ];
+        )),
+    ]
+}
+
+/// Steam App IDs for WoW installs added as non-Steam games run through Proton
+const STEAM_APP_IDS: &[&str] = &[
+    "2835570", // Retail (Dragonflight)
+    "2835580", // Classic
+];
+
+/// Executable locations relative to a WINE prefix's `drive_c`
+const DRIVE_C_PATHS: &[&str] = &[
+    "Program Files (x86)/World of Warcraft/_retail_/Wow.exe",
+    "Program Files/World of Warcraft/_retail_/Wow.exe",
+    "Program Files (x86)/World of Warcraft/_classic_/WowClassic.exe",
+    "Program Files/World of Warcraft/_classic_/WowClassic.exe",
+];
+
+/// Locate `Wow.exe` inside a Steam Proton prefix (e.g. on the Steam Deck).
+///
+/// If `steam_root` is `None`, both `~/.steam/steam` and `~/.local/share/Steam`
+/// are searched.
+pub fn find_wow_via_steam(steam_root: Option<&Path>) -> Option<PathBuf> {
+    steam_candidate_paths(steam_root)
+        .into_iter()
+        .find(|path| path.exists())
+}
 
-    for path_str in possible_paths {
-        let path = PathBuf::from(path_str);
-        if path.exists() {
-            return Some(path);
+/// Executable locations inside the Steam Proton prefixes, existing or not.
+fn steam_candidate_paths(steam_root: Option<&Path>) -> Vec<PathBuf> {
+    let steam_roots = match steam_root {
+        Some(root) => vec![root.to_path_buf()],
+        None => {
+            let Ok(home) = env::var("HOME") else {
+                return Vec::new();
+            };
+            vec![
+                PathBuf::from(format!("{}/.steam/steam", home)),
+                PathBuf::from(format!("{}/.local/share/Steam", home)),
+            ]
+        }
+    };
+
+    let mut paths = Vec::new();
+    for root in &steam_roots {
+        for app_id in STEAM_APP_IDS {
+            let drive_c = root
+                .join("steamapps/compatdata")
+                .join(app_id)
+                .join("pfx/drive_c");
+            paths.extend(DRIVE_C_PATHS.iter().map(|relative| drive_c.join(relative)));
         }
     }
+    paths
+}
+
+/// Return the WINE prefix containing `path`, if it lives under a `drive_c` directory.
+pub fn find_wine_prefix(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|ancestor| ancestor.file_name().is_some_and(|name| name == "drive_c"))
+        .and_then(Path::parent)
+        .map(Path::to_path_buf)
+}
+
+/// Query the WINE version used for the given prefix (e.g. `wine-8.0.2`).
+///
+/// Runs `wine --version` with `WINEPREFIX` pointing at `prefix`. Returns `None`
+/// if WINE is not installed or the command fails.
+pub fn check_wine_version(prefix: &Path) -> Option<String> {
+    let output = Command::new("wine")
+        .arg("--version")
+        .env("WINEPREFIX", prefix)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if version.is_empty() {
+        None
+    } else {
+        Some(version)
+    }
+}
 
-    None
+/// Parse the major version from a `wine --version` string like `wine-7.0 (Staging)`.
+pub fn parse_wine_major_version(version: &str) -> Option<u32> {
+    version
+        .trim()
+        .trim_start_matches("wine-")
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Translate a WSL mount path to the equivalent Windows path for display.
+///
+/// `/mnt/c/Program Files/...` becomes `C:\Program Files\...` and
+/// `//wsl$/Ubuntu/...` becomes `\\wsl$\Ubuntu\...`. Returns `None` if the
+/// path is not a WSL mount.
+pub fn translate_wsl_path(path: &str) -> Option<String> {
+    if let Some(rest) = path.strip_prefix("//wsl$/") {
+        return Some(format!("\\\\wsl$\\{}", rest.replace('/', "\\")));
+    }
+
+    let rest = path.strip_prefix("/mnt/")?;
+    let mut chars = rest.chars();
+    let drive = chars.next().filter(|c| c.is_ascii_alphabetic())?;
+    let remainder = chars.as_str();
+
+    if !remainder.is_empty() && !remainder.starts_with('/') {
+        return None;
+    }
+
+    let remainder = if remainder.is_empty() { "/" } else { remainder };
+    Some(format!(
+        "{}:{}",
+        drive.to_ascii_uppercase(),
+        remainder.replace('/', "\\")
+    ))
 }
 
 #[cfg(test)]
@@ -38,4 +175,55 @@ mod tests {
         // This test will likely return None unless WoW is actually installed
         let _ = find_wow_executable();
     }
+
+    #[test]
+    fn test_find_wow_via_steam() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        assert!(find_wow_via_steam(Some(temp_dir.path())).is_none());
+
+        let wow_dir = temp_dir.path().join(
+            "steamapps/compatdata/2835580/pfx/drive_c/Program Files (x86)/World of Warcraft/_classic_",
+        );
+        std::fs::create_dir_all(&wow_dir).unwrap();
+        std::fs::write(wow_dir.join("WowClassic.exe"), b"test").unwrap();
+
+        assert_eq!(
+            find_wow_via_steam(Some(temp_dir.path())),
+            Some(wow_dir.join("WowClassic.exe"))
+        );
+    }
+
+    #[test]
+    fn test_find_wine_prefix() {
+        assert_eq!(
+            find_wine_prefix(Path::new(
+                "/home/user/.wine/drive_c/Program Files/World of Warcraft/_retail_/Wow.exe"
+            )),
+            Some(PathBuf::from("/home/user/.wine"))
+        );
+        assert_eq!(find_wine_prefix(Path::new("/opt/wow/Wow.exe")), None);
+    }
+
+    #[test]
+    fn test_parse_wine_major_version() {
+        assert_eq!(parse_wine_major_version("wine-6.0.4"), Some(6));
+        assert_eq!(parse_wine_major_version("wine-9.0 (Staging)"), Some(9));
+        assert_eq!(parse_wine_major_version("wine-10.2\n"), Some(10));
+        assert_eq!(parse_wine_major_version("not wine"), None);
+    }
+
+    #[test]
+    fn test_translate_wsl_path() {
+        assert_eq!(
+            translate_wsl_path("/mnt/c/Program Files/World of Warcraft/_retail_/Wow.exe"),
+            Some("C:\\Program Files\\World of Warcraft\\_retail_\\Wow.exe".to_string())
+        );
+        assert_eq!(translate_wsl_path("/mnt/d"), Some("D:\\".to_string()));
+        assert_eq!(
+            translate_wsl_path("//wsl$/Ubuntu/home/user/Wow.exe"),
+            Some("\\\\wsl$\\Ubuntu\\home\\user\\Wow.exe".to_string())
+        );
+        assert_eq!(translate_wsl_path("/mnt/data/Wow.exe"), None);
+        assert_eq!(translate_wsl_path("/home/user/Wow.exe"), None);
+    }
 }
@@ -1,7 +1,13 @@
+use crate::binary::{DataExt, ParsedBinary, string_to_pattern};
+use crate::cmd::execute::is_already_patched;
+use crate::keys::KeyConfig;
+use crate::trinity::PORTAL_ORIGINAL;
 use goblin::Object;
-use std::path::Path;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Client version, ordered by major, minor, patch and then build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Version {
     pub major: u16,
     pub minor: u16,
@@ -18,6 +24,20 @@ impl Version {
             build,
         }
     }
+
+    /// Extract the client version from an in-memory executable.
+    ///
+    /// Tries the PE version resource and Mach-O version commands first, then
+    /// falls back to scanning for a version string. The first result wins.
+    pub fn from_binary(data: &[u8]) -> Option<Version> {
+        extract_version_from_data(data).or_else(|| extract_version_fallback_from_data(data))
+    }
+
+    /// Read an executable and extract its version, see [`Version::from_binary`].
+    pub fn from_file(path: &Path) -> Option<Version> {
+        let data = std::fs::read(path).ok()?;
+        Self::from_binary(&data)
+    }
 }
 
 impl std::fmt::Display for Version {
@@ -30,22 +50,71 @@ impl std::fmt::Display for Version {
     }
 }
 
+/// Version of the macOS host, as reported by `sw_vers -productVersion`.
+///
+/// Serialized as a `major.minor` string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "String", try_from = "String"))]
+pub struct MacosVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl MacosVersion {
+    /// Parse a product version such as `14.2.1`; a missing minor version is 0.
+    pub fn parse(product_version: &str) -> Option<Self> {
+        let mut parts = product_version.trim().split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = match parts.next() {
+            Some(minor) => minor.parse().ok()?,
+            None => 0,
+        };
+        Some(Self { major, minor })
+    }
+}
+
+impl std::fmt::Display for MacosVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl From<MacosVersion> for String {
+    fn from(version: MacosVersion) -> Self {
+        version.to_string()
+    }
+}
+
+impl TryFrom<String> for MacosVersion {
+    type Error = String;
+
+    fn try_from(product_version: String) -> Result<Self, Self::Error> {
+        Self::parse(&product_version)
+            .ok_or_else(|| format!("invalid macOS version '{}'", product_version))
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ClientType {
     Retail,
     Classic,
     ClassicEra,
+    /// Season of Discovery, installed in `_classic_sod_`
+    ClassicSeasonOfDiscovery,
+    /// Hardcore, installed in `_classic_hc_`
+    ClassicHardcore,
     Unknown,
 }
 
 impl ClientType {
     pub fn uses_ed25519(&self) -> bool {
         match self {
-            ClientType::Retail | ClientType::Unknown => true,
+            ClientType::Retail | ClientType::ClassicSeasonOfDiscovery | ClientType::Unknown => true,
             // Classic (1.13.x, 2.5.x, 3.4.x) and Classic Era do not embed
             // an Ed25519 public key. Verified via RE of Classic 1.13.2.31650:
             // the pattern (15 D6 18 BD...) is absent from the binary.
-            ClientType::Classic | ClientType::ClassicEra => false,
+            ClientType::Classic | ClientType::ClassicEra | ClientType::ClassicHardcore => false,
         }
     }
 }
@@ -56,18 +125,48 @@ impl std::fmt::Display for ClientType {
             ClientType::Retail => write!(f, "Retail"),
             ClientType::Classic => write!(f, "Classic"),
             ClientType::ClassicEra => write!(f, "Classic Era"),
+            ClientType::ClassicSeasonOfDiscovery => write!(f, "Classic Season of Discovery"),
+            ClientType::ClassicHardcore => write!(f, "Classic Hardcore"),
             ClientType::Unknown => write!(f, "Unknown"),
         }
     }
 }
 
 pub fn detect_client_type(exe_path: &str) -> ClientType {
-    let path_lower = exe_path.to_lowercase();
+    let client_type = detect_client_type_from_path(exe_path);
+    if client_type != ClientType::Unknown {
+        return client_type;
+    }
+
+    // Fall back to inspecting the binary itself for custom install locations
+    match std::fs::read(exe_path) {
+        Ok(data) => detect_client_type_from_binary(&data),
+        Err(_) => ClientType::Unknown,
+    }
+}
+
+/// Detect the client type from install directory markers and the file name only.
+pub fn detect_client_type_from_path(exe_path: &str) -> ClientType {
+    // Normalize WSL mounts (/mnt/c/...) to their Windows form first
+    #[cfg(target_os = "linux")]
+    let translated = linux::translate_wsl_path(exe_path);
+    #[cfg(target_os = "linux")]
+    let display_path = translated.as_deref().unwrap_or(exe_path);
+    #[cfg(not(target_os = "linux"))]
+    let display_path = exe_path;
+
+    let path_lower = display_path.to_lowercase();
 
     // Check directory markers
     if path_lower.contains("_retail_") {
         return ClientType::Retail;
     }
+    if path_lower.contains("_classic_sod_") {
+        return ClientType::ClassicSeasonOfDiscovery;
+    }
+    if path_lower.contains("_classic_hc_") {
+        return ClientType::ClassicHardcore;
+    }
     if path_lower.contains("_classic_era_") {
         return ClientType::ClassicEra;
     }
@@ -75,12 +174,8 @@ pub fn detect_client_type(exe_path: &str) -> ClientType {
         return ClientType::Classic;
     }
 
-    // Check filename
-    let filename = Path::new(exe_path)
-        .file_name()
-        .and_then(|s| s.to_str())
-        .unwrap_or("")
-        .to_lowercase();
+    // Check filename (split on both separators so Windows paths work everywhere)
+    let filename = path_lower.rsplit(['/', '\\']).next().unwrap_or("");
 
     if filename.contains("wowclassic") {
         return ClientType::Classic;
@@ -92,6 +187,27 @@ pub fn detect_client_type(exe_path: &str) -> ClientType {
     ClientType::Unknown
 }
 
+/// Detect the client type from product code strings embedded in the binary.
+///
+/// Each client flavor embeds its Battle.net product code together with the
+/// locale (e.g. `wow_enus`). The more specific Classic codes are checked
+/// first so they are not shadowed by the Retail code.
+pub fn detect_client_type_from_binary(data: &[u8]) -> ClientType {
+    const PRODUCT_CODES: &[(&str, ClientType)] = &[
+        ("wowclassice_enus", ClientType::ClassicEra),
+        ("wowclassic_enus", ClientType::Classic),
+        ("wow_enus", ClientType::Retail),
+    ];
+
+    for (code, client_type) in PRODUCT_CODES {
+        if data.find_pattern(&string_to_pattern(code)).is_some() {
+            return *client_type;
+        }
+    }
+
+    ClientType::Unknown
+}
+
 #[cfg(target_os = "macos")]
 pub mod darwin;
 
@@ -113,40 +229,307 @@ pub fn find_warcraft_client_executable() -> String {
     }
 }
 
+/// A WoW client executable found on this machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WowInstallation {
+    pub path: PathBuf,
+    pub client_type: ClientType,
+    /// Client version, `None` if it could not be read from the executable
+    pub version: Option<Version>,
+    /// `Some(false)` while the original portal host is intact, `Some(true)` when
+    /// the TrinityCore keys are present, `None` when neither can be determined
+    pub is_patched: Option<bool>,
+}
+
+impl WowInstallation {
+    /// Inspect the executable at `path`.
+    pub fn from_path(path: &Path) -> Self {
+        let data = std::fs::read(path).ok();
+        let is_patched = data.as_deref().and_then(|data| {
+            if data
+                .windows(PORTAL_ORIGINAL.len())
+                .any(|window| window == PORTAL_ORIGINAL)
+            {
+                Some(false)
+            } else if is_already_patched(data, &KeyConfig::trinity_core()) {
+                Some(true)
+            } else {
+                None
+            }
+        });
+
+        // Like detect_client_type, but falling back to the data already read
+        let mut client_type = detect_client_type_from_path(&path.to_string_lossy());
+        if client_type == ClientType::Unknown
+            && let Some(data) = data.as_deref()
+        {
+            client_type = detect_client_type_from_binary(data);
+        }
+
+        Self {
+            path: path.to_path_buf(),
+            client_type,
+            version: data.as_deref().and_then(Version::from_binary),
+            is_patched,
+        }
+    }
+}
+
+/// Find every WoW executable in the default install locations of this platform.
+///
+/// Checks the fixed install paths, the WINE prefixes under `$HOME` and in
+/// `WINEPREFIX`, and the Steam Proton prefixes where they apply. Paths that
+/// resolve to the same file are reported once. The result is ordered Retail,
+/// Classic, Classic Era, Season of Discovery, Hardcore, then unknown clients.
+pub fn find_all_wow_executables() -> Vec<WowInstallation> {
+    let mut candidates = Vec::new();
+
+    #[cfg(target_os = "windows")]
+    candidates.extend(windows::candidate_paths());
+
+    #[cfg(target_os = "linux")]
+    candidates.extend(linux::candidate_paths());
+
+    #[cfg(target_os = "macos")]
+    candidates.push(PathBuf::from(find_warcraft_client_executable()));
+
+    installations_from_candidates(candidates)
+}
+
+/// Inspect the existing files among `candidates`, dropping duplicates.
+fn installations_from_candidates(candidates: Vec<PathBuf>) -> Vec<WowInstallation> {
+    let mut seen = HashSet::new();
+    let mut installations: Vec<WowInstallation> = candidates
+        .into_iter()
+        .filter(|path| path.is_file())
+        .filter(|path| seen.insert(std::fs::canonicalize(path).unwrap_or_else(|_| path.clone())))
+        .map(|path| WowInstallation::from_path(&path))
+        .collect();
+
+    installations.sort_by_key(|installation| match installation.client_type {
+        ClientType::Retail => 0,
+        ClientType::Classic => 1,
+        ClientType::ClassicEra => 2,
+        ClientType::ClassicSeasonOfDiscovery => 3,
+        ClientType::ClassicHardcore => 4,
+        ClientType::Unknown => 5,
+    });
+    installations
+}
+
+/// What `codesign` does to a patched macOS executable.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum CodesignAction {
+    /// Remove the signature (`--remove-signature`)
+    #[default]
+    RemoveSignature,
+    /// Sign with an ad-hoc identity (`--sign -`)
+    AdhocSign,
+    /// Remove the signature, then sign with a keychain identity (`--sign <identity> --timestamp`)
+    ///
+    /// The identity `-` signs ad hoc, without a secure timestamp.
+    Sign(String),
+}
+
+/// Options for the `codesign` run on a patched macOS executable.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CodesignOptions {
+    pub action: CodesignAction,
+    /// Also process nested code such as frameworks and helpers (`--deep`)
+    pub deep: bool,
+    /// Replace an existing signature (`--force`)
+    pub force: bool,
+}
+
+impl CodesignOptions {
+    /// Arguments passed to `codesign` ahead of the executable path.
+    ///
+    /// For [`CodesignAction::Sign`] these are the arguments of the signing
+    /// run, which follows a separate `--remove-signature` run.
+    pub fn args(&self) -> Vec<&str> {
+        let mut args = Vec::new();
+        if self.force {
+            args.push("--force");
+        }
+        if self.deep {
+            args.push("--deep");
+        }
+        match &self.action {
+            CodesignAction::RemoveSignature => args.push("--remove-signature"),
+            CodesignAction::AdhocSign => args.extend(["--sign", "-"]),
+            CodesignAction::Sign(identity) => {
+                args.extend(["--sign", identity.as_str()]);
+                if identity != "-" {
+                    args.push("--timestamp");
+                }
+            }
+        }
+        args
+    }
+}
+
+/// Signing identifier `codesign` derives for a single executable: its file name without extension.
+///
+/// Passed as `--identifier` when the executable is signed under another
+/// name, such as a temporary file that is renamed afterwards.
+pub fn codesign_identifier(path: &Path) -> Result<String, crate::errors::WowPatcherError> {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .filter(|stem| !stem.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            crate::errors::WowPatcherError::new(
+                crate::errors::ErrorCategory::PlatformError,
+                format!("Cannot derive a code signing identifier from {:?}", path),
+            )
+            .with_suggestion("Use an output file name that is valid UTF-8")
+        })
+}
+
+/// Run `codesign` on `path`, signing it as `identifier` when the action signs.
 #[cfg(target_os = "macos")]
-pub fn remove_codesigning_signature(path: &str) -> Result<(), crate::errors::WowPatcherError> {
-    darwin::remove_codesign(Path::new(path))
+pub fn remove_codesigning_signature(
+    path: &Path,
+    identifier: &str,
+    opts: CodesignOptions,
+    reporter: &mut dyn crate::report::Reporter,
+) -> Result<(), crate::errors::WowPatcherError> {
+    darwin::remove_codesign(path, identifier, opts, reporter)
 }
 
 #[cfg(not(target_os = "macos"))]
-pub fn remove_codesigning_signature(_path: &str) -> Result<(), crate::errors::WowPatcherError> {
-    println!("ℹ️  Code signing removal is not required on this platform");
+pub fn remove_codesigning_signature(
+    _path: &Path,
+    _identifier: &str,
+    _opts: CodesignOptions,
+    reporter: &mut dyn crate::report::Reporter,
+) -> Result<(), crate::errors::WowPatcherError> {
+    reporter.detail("ℹ️  Code signing removal is not required on this platform");
     Ok(())
 }
 
+/// Version of the macOS host, or `None` on other platforms.
+#[cfg(target_os = "macos")]
+pub fn host_macos_version() -> Option<MacosVersion> {
+    darwin::macos_version()
+}
+
+/// Version of the macOS host, or `None` on other platforms.
+#[cfg(not(target_os = "macos"))]
+pub fn host_macos_version() -> Option<MacosVersion> {
+    None
+}
+
 /// Extract version information from WoW executable
 pub fn extract_version(exe_path: &Path) -> Option<Version> {
     let data = std::fs::read(exe_path).ok()?;
-    let obj = Object::parse(&data).ok()?;
+    extract_version_from_data(&data)
+}
+
+/// Extract version information from an in-memory WoW executable
+pub fn extract_version_from_data(data: &[u8]) -> Option<Version> {
+    let binary = ParsedBinary::new(data);
 
-    match obj {
-        Object::PE(pe) => extract_pe_version(&pe),
-        Object::Mach(mach) => extract_macho_version(&mach, &data),
+    match &binary.obj {
+        Object::PE(pe) => extract_pe_version(pe, data),
+        Object::Mach(mach) => extract_macho_version(mach, data),
         _ => None,
     }
 }
 
+/// Resource type ID of the `VS_VERSIONINFO` resource (`RT_VERSION`)
+const RT_VERSION: u32 = 16;
+/// `dwSignature` value at the start of `VS_FIXEDFILEINFO`
+const VS_FFI_SIGNATURE: u32 = 0xFEEF_04BD;
+/// High bit of a resource directory entry's offset, set when it points at a subdirectory
+const RESOURCE_SUBDIRECTORY: u32 = 0x8000_0000;
+
 /// Extract version from PE file (Windows executables)
-fn extract_pe_version(_pe: &goblin::pe::PE) -> Option<Version> {
-    // PE files store version info in the VS_VERSIONINFO resource
-    // For now, we'll try to find version patterns in the binary
-    // The version is usually stored as 4 16-bit values in the VS_FIXEDFILEINFO structure
+///
+/// Walks the resource directory from the data directory entry at index 2
+/// through the `RT_VERSION` type to its first name and language, and reads
+/// the file version from the `VS_FIXEDFILEINFO` at the start of the
+/// `VS_VERSIONINFO` resource.
+fn extract_pe_version(pe: &goblin::pe::PE, data: &[u8]) -> Option<Version> {
+    let table = pe
+        .header
+        .optional_header?
+        .data_directories
+        .get_resource_table()
+        .copied()?;
+    let rsrc_offset = pe_rva_to_offset(pe, table.virtual_address)?;
+    let rsrc = data.get(rsrc_offset..rsrc_offset.checked_add(table.size as usize)?)?;
+
+    // Type, then name, then language
+    let names = resource_subdirectory(find_resource_entry(rsrc, 0, Some(RT_VERSION))?)?;
+    let languages = resource_subdirectory(find_resource_entry(rsrc, names, None)?)?;
+    let data_entry = find_resource_entry(rsrc, languages, None)?;
+    if data_entry & RESOURCE_SUBDIRECTORY != 0 {
+        return None;
+    }
 
-    // This is a simplified approach - in production, we'd properly parse the resource section
-    // For WoW executables, the version is typically stored in a consistent location
-    // We'll return None for now and rely on the fallback pattern matching
+    let data_entry = data_entry as usize;
+    let offset = pe_rva_to_offset(pe, read_u32_le(rsrc, data_entry)?)?;
+    let size = read_u32_le(rsrc, data_entry + 4)? as usize;
+    let version_info = data.get(offset..offset.checked_add(size)?)?;
+
+    // VS_FIXEDFILEINFO follows the UTF-16 "VS_VERSION_INFO" key on a DWORD boundary
+    let fixed = version_info
+        .chunks_exact(4)
+        .position(|dword| dword == VS_FFI_SIGNATURE.to_le_bytes())?
+        * 4;
+    let ms = read_u32_le(version_info, fixed + 8)?;
+    let ls = read_u32_le(version_info, fixed + 12)?;
+    Some(Version::new(
+        (ms >> 16) as u16,
+        ms as u16,
+        (ls >> 16) as u16,
+        ls as u16,
+    ))
+}
 
-    None
+/// Map a relative virtual address to a file offset through the PE section table.
+fn pe_rva_to_offset(pe: &goblin::pe::PE, rva: u32) -> Option<usize> {
+    pe.sections.iter().find_map(|section| {
+        let delta = rva.checked_sub(section.virtual_address)?;
+        let size = section.virtual_size.max(section.size_of_raw_data);
+        (delta < size).then(|| section.pointer_to_raw_data as usize + delta as usize)
+    })
+}
+
+/// Offset field of the first entry of the resource directory at `directory`.
+///
+/// With `id` only entries identified by that numeric ID match, otherwise the
+/// first entry of any kind does.
+fn find_resource_entry(rsrc: &[u8], directory: usize, id: Option<u32>) -> Option<u32> {
+    let named = rsrc.get(directory + 12..directory + 14)?;
+    let ids = rsrc.get(directory + 14..directory + 16)?;
+    let count = u16::from_le_bytes([named[0], named[1]]) as usize
+        + u16::from_le_bytes([ids[0], ids[1]]) as usize;
+
+    (0..count).find_map(|index| {
+        let entry = directory + 16 + index * 8;
+        let name = read_u32_le(rsrc, entry)?;
+        let matches = match id {
+            Some(id) => name & RESOURCE_SUBDIRECTORY == 0 && name == id,
+            None => true,
+        };
+        if matches {
+            read_u32_le(rsrc, entry + 4)
+        } else {
+            None
+        }
+    })
+}
+
+/// Offset of the subdirectory a resource entry points at, `None` for a data entry.
+fn resource_subdirectory(entry: u32) -> Option<usize> {
+    (entry & RESOURCE_SUBDIRECTORY != 0).then_some((entry & !RESOURCE_SUBDIRECTORY) as usize)
+}
+
+fn read_u32_le(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
 }
 
 /// Extract version from Mach-O file (macOS executables)
@@ -180,12 +563,17 @@ pub fn extract_version_fallback(exe_path: &Path) -> Option<Version> {
     let mut buffer = Vec::new();
     reader.read_to_end(&mut buffer).ok()?;
 
+    extract_version_fallback_from_data(&buffer)
+}
+
+/// Fallback version extraction on an in-memory executable using pattern matching
+pub fn extract_version_fallback_from_data(buffer: &[u8]) -> Option<Version> {
     // Common version patterns in WoW executables
     // Look for patterns like "10.2.5.53584" or "3.4.3.51666"
     let version_regex = regex::Regex::new(r"(\d{1,2})\.(\d{1,2})\.(\d{1,2})\.(\d{5,6})").ok()?;
 
     // Convert buffer to string, ignoring non-UTF8 sequences
-    let text = String::from_utf8_lossy(&buffer);
+    let text = String::from_utf8_lossy(buffer);
 
     // Find the first matching version pattern
     if let Some(captures) = version_regex.captures(&text) {
@@ -203,6 +591,7 @@ pub fn extract_version_fallback(exe_path: &Path) -> Option<Version> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::{BTreeSet, HashMap};
 
     #[test]
     fn test_detect_client_type() {
@@ -228,6 +617,18 @@ mod tests {
             ClientType::ClassicEra
         );
 
+        assert_eq!(
+            detect_client_type(
+                "C:\\Program Files\\World of Warcraft\\_classic_sod_\\WowClassic.exe"
+            ),
+            ClientType::ClassicSeasonOfDiscovery
+        );
+
+        assert_eq!(
+            detect_client_type("/home/user/wow/_Classic_HC_/WowClassic.exe"),
+            ClientType::ClassicHardcore
+        );
+
         assert_eq!(detect_client_type("WowClassic.exe"), ClientType::Classic);
 
         assert_eq!(detect_client_type("Wow.exe"), ClientType::Retail);
@@ -241,6 +642,197 @@ mod tests {
             detect_client_type("C:\\Games\\WoW\\_RETAIL_\\WOW.EXE"),
             ClientType::Retail
         );
+
+        assert_eq!(
+            detect_client_type("D:\\Games\\WowClassic.exe"),
+            ClientType::Classic
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_detect_client_type_wsl_path() {
+        assert_eq!(
+            detect_client_type(
+                "/mnt/c/Program Files/World of Warcraft/_classic_era_/WowClassic.exe"
+            ),
+            ClientType::ClassicEra
+        );
+        assert_eq!(
+            detect_client_type("/mnt/c/Games/WoW/Wow.exe"),
+            ClientType::Retail
+        );
+    }
+
+    #[test]
+    fn test_installations_from_candidates() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let classic_dir = temp_dir.path().join("_classic_");
+        let retail_dir = temp_dir.path().join("_retail_");
+        std::fs::create_dir_all(&classic_dir).unwrap();
+        std::fs::create_dir_all(&retail_dir).unwrap();
+
+        let classic = classic_dir.join("WowClassic.exe");
+        let retail = retail_dir.join("Wow.exe");
+        std::fs::write(&classic, b"portal .actual.battle.net").unwrap();
+        std::fs::write(&retail, crate::trinity::RSA_MODULUS).unwrap();
+
+        let installations = installations_from_candidates(vec![
+            classic.clone(),
+            retail.clone(),
+            retail_dir.join("..").join("_retail_").join("Wow.exe"),
+            retail_dir.join("missing.exe"),
+        ]);
+        assert_eq!(installations.len(), 2);
+
+        assert_eq!(installations[0].path, retail);
+        assert_eq!(installations[0].client_type, ClientType::Retail);
+        assert_eq!(installations[0].is_patched, Some(true));
+
+        assert_eq!(installations[1].path, classic);
+        assert_eq!(installations[1].client_type, ClientType::Classic);
+        assert_eq!(installations[1].is_patched, Some(false));
+        assert!(installations[1].version.is_none());
+    }
+
+    #[test]
+    fn test_detect_client_type_from_binary() {
+        let mut data = vec![0u8; 64];
+        data[10..18].copy_from_slice(b"wow_enus");
+        assert_eq!(detect_client_type_from_binary(&data), ClientType::Retail);
+
+        let mut data = vec![0u8; 64];
+        data[10..25].copy_from_slice(b"wowclassic_enus");
+        assert_eq!(detect_client_type_from_binary(&data), ClientType::Classic);
+
+        let mut data = vec![0u8; 64];
+        data[10..26].copy_from_slice(b"wowclassice_enus");
+        assert_eq!(
+            detect_client_type_from_binary(&data),
+            ClientType::ClassicEra
+        );
+
+        assert_eq!(
+            detect_client_type_from_binary(b"no product code here"),
+            ClientType::Unknown
+        );
+    }
+
+    #[test]
+    fn test_detect_client_type_binary_fallback() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let exe_path = temp_dir.path().join("game.exe");
+        std::fs::write(&exe_path, b"....wowclassic_enus....").unwrap();
+
+        assert_eq!(
+            detect_client_type(exe_path.to_str().unwrap()),
+            ClientType::Classic
+        );
+    }
+
+    #[test]
+    fn test_version_from_binary() {
+        let mut data = vec![0u8; 32];
+        data.extend_from_slice(b"WoW 1.15.8.64272\0");
+        assert_eq!(
+            Version::from_binary(&data),
+            Some(Version::new(1, 15, 8, 64272))
+        );
+        assert_eq!(Version::from_binary(&[0u8; 32]), None);
+    }
+
+    #[test]
+    fn test_version_ordering() {
+        let versions: BTreeSet<Version> = [
+            Version::new(11, 1, 0, 0),
+            Version::new(3, 4, 4, 0),
+            Version::new(1, 15, 0, 0),
+            Version::new(10, 2, 5, 0),
+            Version::new(3, 4, 4, 0),
+        ]
+        .into_iter()
+        .collect();
+        let sorted: Vec<String> = versions.iter().map(Version::to_string).collect();
+        assert_eq!(sorted, ["1.15.0.0", "3.4.4.0", "10.2.5.0", "11.1.0.0"]);
+
+        // The build number only breaks ties
+        assert!(Version::new(1, 15, 8, 1) < Version::new(1, 15, 9, 0));
+
+        let mut notes = HashMap::new();
+        notes.insert(Version::new(1, 15, 8, 64272), "Classic Era");
+        assert_eq!(
+            notes.get(&Version::new(1, 15, 8, 64272)),
+            Some(&"Classic Era")
+        );
+    }
+
+    #[test]
+    fn test_codesign_options_args() {
+        assert_eq!(CodesignOptions::default().args(), ["--remove-signature"]);
+        let opts = CodesignOptions {
+            action: CodesignAction::AdhocSign,
+            deep: true,
+            force: true,
+        };
+        assert_eq!(opts.args(), ["--force", "--deep", "--sign", "-"]);
+
+        let opts = CodesignOptions {
+            action: CodesignAction::Sign("Developer ID Application: Example".to_string()),
+            deep: false,
+            force: true,
+        };
+        assert_eq!(
+            opts.args(),
+            [
+                "--force",
+                "--sign",
+                "Developer ID Application: Example",
+                "--timestamp"
+            ]
+        );
+        // Ad-hoc signatures cannot carry a secure timestamp
+        let opts = CodesignOptions {
+            action: CodesignAction::Sign("-".to_string()),
+            ..opts
+        };
+        assert_eq!(opts.args(), ["--force", "--sign", "-"]);
+    }
+
+    #[test]
+    fn test_codesign_identifier() {
+        assert_eq!(
+            codesign_identifier(Path::new("/Applications/Wow-patched")).unwrap(),
+            "Wow-patched"
+        );
+        assert_eq!(codesign_identifier(Path::new("Wow.exe")).unwrap(), "Wow");
+        assert!(codesign_identifier(Path::new("/")).is_err());
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            let path = Path::new(std::ffi::OsStr::from_bytes(b"Wow\xff"));
+            assert!(codesign_identifier(path).is_err());
+        }
+    }
+
+    #[test]
+    fn test_macos_version_parse() {
+        assert_eq!(
+            MacosVersion::parse("14.2.1\n"),
+            Some(MacosVersion {
+                major: 14,
+                minor: 2
+            })
+        );
+        assert_eq!(
+            MacosVersion::parse("11"),
+            Some(MacosVersion {
+                major: 11,
+                minor: 0
+            })
+        );
+        assert_eq!(MacosVersion::parse(""), None);
+        assert_eq!(MacosVersion::parse("abc"), None);
+        assert_eq!(MacosVersion::parse("13.4").unwrap().to_string(), "13.4");
     }
 
     #[test]
@@ -248,6 +840,8 @@ mod tests {
         assert!(ClientType::Retail.uses_ed25519());
         assert!(!ClientType::Classic.uses_ed25519());
         assert!(!ClientType::ClassicEra.uses_ed25519());
+        assert!(ClientType::ClassicSeasonOfDiscovery.uses_ed25519());
+        assert!(!ClientType::ClassicHardcore.uses_ed25519());
         assert!(ClientType::Unknown.uses_ed25519());
     }
 
@@ -256,6 +850,11 @@ mod tests {
         assert_eq!(ClientType::Retail.to_string(), "Retail");
         assert_eq!(ClientType::Classic.to_string(), "Classic");
         assert_eq!(ClientType::ClassicEra.to_string(), "Classic Era");
+        assert_eq!(
+            ClientType::ClassicSeasonOfDiscovery.to_string(),
+            "Classic Season of Discovery"
+        );
+        assert_eq!(ClientType::ClassicHardcore.to_string(), "Classic Hardcore");
         assert_eq!(ClientType::Unknown.to_string(), "Unknown");
     }
 }
@@ -1,21 +1,113 @@
-use std::path::PathBuf;
+use crate::errors::{ErrorCategory, WowPatcherError};
+use std::path::{Path, PathBuf};
 
 pub fn find_wow_executable() -> Option<PathBuf> {
-    let possible_paths = vec![
+    candidate_paths().into_iter().find(|path| path.exists())
+}
+
+/// Default install locations checked for a WoW executable, existing or not.
+pub fn candidate_paths() -> Vec<PathBuf> {
+    [
         "C:\\Program Files\\World of Warcraft\\_retail_\\Wow.exe",
         "C:\\Program Files (x86)\\World of Warcraft\\_retail_\\Wow.exe",
         "C:\\Program Files\\World of Warcraft\\_classic_\\WowClassic.exe",
         "C:\\Program Files (x86)\\World of Warcraft\\_classic_\\WowClassic.exe",
-    ];
+    ]
+    .into_iter()
+    .map(PathBuf::from)
+    .collect()
+}
+
+/// Grant the current user read and execute access to `path`.
+///
+/// Windows has no executable flag, but a file written into a directory with
+/// restrictive inheritable ACLs can end up unreadable by the user who wrote
+/// it. The grant is merged into the file's existing DACL, so other entries
+/// are kept.
+pub fn ensure_readable_executable(path: &Path) -> Result<(), WowPatcherError> {
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::null_mut;
+    use windows_sys::Win32::Foundation::{ERROR_SUCCESS, LocalFree, WIN32_ERROR};
+    use windows_sys::Win32::Security::Authorization::{
+        EXPLICIT_ACCESS_W, GRANT_ACCESS, GetNamedSecurityInfoW, NO_MULTIPLE_TRUSTEE,
+        SE_FILE_OBJECT, SetEntriesInAclW, SetNamedSecurityInfoW, TRUSTEE_IS_NAME, TRUSTEE_IS_USER,
+        TRUSTEE_W,
+    };
+    use windows_sys::Win32::Security::{
+        ACL, DACL_SECURITY_INFORMATION, NO_INHERITANCE, PSECURITY_DESCRIPTOR,
+    };
+    use windows_sys::Win32::Storage::FileSystem::{FILE_GENERIC_EXECUTE, FILE_GENERIC_READ};
 
-    for path_str in possible_paths {
-        let path = PathBuf::from(path_str);
-        if path.exists() {
-            return Some(path);
-        }
+    let acl_error = |message: &str, status: WIN32_ERROR| {
+        WowPatcherError::wrap(
+            ErrorCategory::FileOperationError,
+            message,
+            std::io::Error::from_raw_os_error(status as i32),
+        )
+        .with_context("path", path.display().to_string())
+    };
+
+    let wide_path: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    // "CURRENT_USER" is a trustee name the ACL functions resolve to the calling user
+    let mut current_user: Vec<u16> = "CURRENT_USER".encode_utf16().chain(Some(0)).collect();
+
+    let mut dacl: *mut ACL = null_mut();
+    let mut descriptor: PSECURITY_DESCRIPTOR = null_mut();
+    let status = unsafe {
+        GetNamedSecurityInfoW(
+            wide_path.as_ptr(),
+            SE_FILE_OBJECT,
+            DACL_SECURITY_INFORMATION,
+            null_mut(),
+            null_mut(),
+            &mut dacl,
+            null_mut(),
+            &mut descriptor,
+        )
+    };
+    if status != ERROR_SUCCESS {
+        return Err(acl_error("Failed to read output file permissions", status));
     }
 
-    None
+    let access = EXPLICIT_ACCESS_W {
+        grfAccessPermissions: FILE_GENERIC_READ | FILE_GENERIC_EXECUTE,
+        grfAccessMode: GRANT_ACCESS,
+        grfInheritance: NO_INHERITANCE,
+        Trustee: TRUSTEE_W {
+            pMultipleTrustee: null_mut(),
+            MultipleTrusteeOperation: NO_MULTIPLE_TRUSTEE,
+            TrusteeForm: TRUSTEE_IS_NAME,
+            TrusteeType: TRUSTEE_IS_USER,
+            ptstrName: current_user.as_mut_ptr(),
+        },
+    };
+    let mut new_dacl: *mut ACL = null_mut();
+    let status = unsafe { SetEntriesInAclW(1, &access, dacl, &mut new_dacl) };
+    // `dacl` points into the descriptor and is not used past this point
+    unsafe { LocalFree(descriptor) };
+    if status != ERROR_SUCCESS {
+        return Err(acl_error(
+            "Failed to update output file permissions",
+            status,
+        ));
+    }
+
+    let status = unsafe {
+        SetNamedSecurityInfoW(
+            wide_path.as_ptr(),
+            SE_FILE_OBJECT,
+            DACL_SECURITY_INFORMATION,
+            null_mut(),
+            null_mut(),
+            new_dacl,
+            null_mut(),
+        )
+    };
+    unsafe { LocalFree(new_dacl.cast()) };
+    if status != ERROR_SUCCESS {
+        return Err(acl_error("Failed to set output file permissions", status));
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -27,4 +119,18 @@ mod tests {
         // This test will likely return None unless WoW is actually installed
         let _ = find_wow_executable();
     }
+
+    #[test]
+    fn test_ensure_readable_executable() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("Wow-patched.exe");
+        std::fs::write(&path, b"MZ").unwrap();
+        ensure_readable_executable(&path).unwrap();
+        // Applying the grant again leaves the file readable
+        ensure_readable_executable(&path).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"MZ");
+
+        let err = ensure_readable_executable(&temp_dir.path().join("missing.exe")).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::FileOperationError);
+    }
 }
@@ -0,0 +1,502 @@
+//! Structured reports describing the patches found or applied in an executable.
+
+use crate::errors::WowPatcherError;
+use crate::platform::MacosVersion;
+use std::fmt;
+
+/// Number of leading bytes shown for the original and replacement columns.
+const PREVIEW_BYTES: usize = 8;
+
+/// Outcome of a single patch step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum PatchStatus {
+    /// The replacement was written
+    Applied,
+    /// The pattern was located but nothing was written
+    Found,
+    /// The pattern was not found
+    Missing,
+    /// The step did not run for this client
+    Skipped,
+}
+
+impl fmt::Display for PatchStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchStatus::Applied => write!(f, "applied"),
+            PatchStatus::Found => write!(f, "found"),
+            PatchStatus::Missing => write!(f, "missing"),
+            PatchStatus::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+/// A single row of a [`PatchReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PatchRecord {
+    /// Name of the patch step or pattern
+    pub step: String,
+    pub status: PatchStatus,
+    /// Whether the step that produced the record must apply its patch
+    pub required: bool,
+    /// File offset of the match, if one was found
+    pub offset: Option<usize>,
+    /// Section containing the match, when the binary format is known
+    pub section: Option<String>,
+    /// Bytes at the offset before patching
+    #[cfg_attr(feature = "serde", serde(with = "hex_bytes"))]
+    pub original: Vec<u8>,
+    /// Bytes written at the offset
+    #[cfg_attr(feature = "serde", serde(with = "hex_bytes"))]
+    pub replacement: Vec<u8>,
+}
+
+/// Environment the patcher ran in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlatformInfo {
+    /// Host macOS version, `None` on other platforms
+    pub macos_version: Option<MacosVersion>,
+}
+
+/// Report of every patch step attempted on an executable.
+///
+/// With the `serde` feature the report and its records implement
+/// `Serialize` and `Deserialize`, using the same schema as
+/// [`PatchReport::to_json`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PatchReport {
+    #[cfg_attr(feature = "serde", serde(rename = "patches"))]
+    pub patches_applied: Vec<PatchRecord>,
+    pub platform: PlatformInfo,
+}
+
+impl PatchRecord {
+    /// Number of bytes written, or that would be written for a found pattern.
+    pub fn bytes_replaced(&self) -> usize {
+        self.replacement.len()
+    }
+}
+
+impl PatchReport {
+    /// Create an empty report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a record to the report.
+    pub fn push(&mut self, record: PatchRecord) {
+        self.patches_applied.push(record);
+    }
+
+    /// Number of records whose replacement was written.
+    pub fn applied_count(&self) -> usize {
+        self.patches_applied
+            .iter()
+            .filter(|record| record.status == PatchStatus::Applied)
+            .count()
+    }
+
+    /// Serialize the report as a single-line JSON object.
+    ///
+    /// Offsets are numbers, byte columns are lowercase hex strings, and
+    /// absent values are `null`.
+    pub fn to_json(&self) -> String {
+        let records: Vec<String> = self
+            .patches_applied
+            .iter()
+            .map(|record| {
+                format!(
+                    "{{\"step\":{},\"status\":{},\"required\":{},\"offset\":{},\"section\":{},\"original\":{},\"replacement\":{}}}",
+                    json_string(&record.step),
+                    json_string(&record.status.to_string()),
+                    record.required,
+                    record
+                        .offset
+                        .map_or_else(|| "null".to_string(), |offset| offset.to_string()),
+                    record
+                        .section
+                        .as_deref()
+                        .map_or_else(|| "null".to_string(), json_string),
+                    json_string(&hex::encode(&record.original)),
+                    json_string(&hex::encode(&record.replacement)),
+                )
+            })
+            .collect();
+        let macos_version = self
+            .platform
+            .macos_version
+            .map_or_else(|| "null".to_string(), |v| json_string(&v.to_string()));
+        format!(
+            "{{\"patches\":[{}],\"platform\":{{\"macos_version\":{}}}}}",
+            records.join(","),
+            macos_version
+        )
+    }
+}
+
+/// Byte columns as lowercase hex strings, as written by [`PatchReport::to_json`].
+#[cfg(feature = "serde")]
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        hex::decode(hex).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Quote and escape a string for JSON output.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if u32::from(c) < 0x20 => quoted.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// How much the patcher prints while it runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportMode {
+    /// Print nothing
+    Silent,
+    /// Print only the error that aborted the run, to stderr
+    Quiet,
+    /// Print warnings, the dry run preview, a summary and the report table
+    #[default]
+    Normal,
+    /// Also print every patch step and the bytes it changed
+    Verbose,
+    /// Print the final report, or the error, as one JSON object
+    Json,
+}
+
+/// Receives everything the patcher reports while it runs.
+pub trait Reporter {
+    /// Step-by-step detail, only shown in verbose mode.
+    fn detail(&mut self, message: &str);
+
+    /// Status message such as the dry run preview or the final summary.
+    fn status(&mut self, message: &str);
+
+    /// Warning about the input or the host environment.
+    fn warning(&mut self, message: &str);
+
+    /// Error that aborted the run.
+    fn error(&mut self, error: &WowPatcherError);
+
+    /// Report of a completed run.
+    fn finish(&mut self, report: &PatchReport);
+
+    /// Whether `detail` messages are shown, so callers can skip building them.
+    fn is_verbose(&self) -> bool;
+}
+
+/// [`Reporter`] printing to stdout according to a [`ReportMode`].
+///
+/// In [`ReportMode::Normal`] and [`ReportMode::Verbose`] errors are left to
+/// the caller, which receives them as the returned `Err`.
+#[derive(Debug, Clone, Default)]
+pub struct ConsoleReporter {
+    mode: ReportMode,
+    /// Warnings held back until the JSON object is printed
+    warnings: Vec<String>,
+}
+
+impl ConsoleReporter {
+    pub fn new(mode: ReportMode) -> Self {
+        Self {
+            mode,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn json_warnings(&self) -> String {
+        let warnings: Vec<String> = self.warnings.iter().map(|w| json_string(w)).collect();
+        format!("[{}]", warnings.join(","))
+    }
+}
+
+impl Reporter for ConsoleReporter {
+    fn detail(&mut self, message: &str) {
+        if self.mode == ReportMode::Verbose {
+            println!("{}", message);
+        }
+    }
+
+    fn status(&mut self, message: &str) {
+        if matches!(self.mode, ReportMode::Normal | ReportMode::Verbose) {
+            println!("{}", message);
+        }
+    }
+
+    fn warning(&mut self, message: &str) {
+        match self.mode {
+            ReportMode::Normal | ReportMode::Verbose => println!("{}", message),
+            ReportMode::Json => self.warnings.push(message.to_string()),
+            ReportMode::Silent | ReportMode::Quiet => {}
+        }
+    }
+
+    fn error(&mut self, error: &WowPatcherError) {
+        match self.mode {
+            ReportMode::Quiet => eprintln!("✗ {}", error),
+            ReportMode::Json => println!(
+                "{{\"error\":{{\"category\":{},\"message\":{}}},\"warnings\":{}}}",
+                json_string(&error.category.to_string()),
+                json_string(&error.message),
+                self.json_warnings()
+            ),
+            _ => {}
+        }
+    }
+
+    fn finish(&mut self, report: &PatchReport) {
+        match self.mode {
+            ReportMode::Normal | ReportMode::Verbose => print!("{}", report),
+            ReportMode::Json => println!(
+                "{{\"report\":{},\"warnings\":{}}}",
+                report.to_json(),
+                self.json_warnings()
+            ),
+            ReportMode::Silent | ReportMode::Quiet => {}
+        }
+    }
+
+    fn is_verbose(&self) -> bool {
+        self.mode == ReportMode::Verbose
+    }
+}
+
+/// Format up to the first 8 bytes as hex, or "-" when there are none.
+fn hex_preview(bytes: &[u8]) -> String {
+    if bytes.is_empty() {
+        return "-".to_string();
+    }
+    let preview = hex::encode(&bytes[..bytes.len().min(PREVIEW_BYTES)]);
+    if bytes.len() > PREVIEW_BYTES {
+        format!("{}…", preview)
+    } else {
+        preview
+    }
+}
+
+impl fmt::Display for PatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let headers = [
+            "Step",
+            "Status",
+            "Offset",
+            "Section",
+            "Original",
+            "Replacement",
+        ];
+        let rows: Vec<[String; 6]> = self
+            .patches_applied
+            .iter()
+            .map(|record| {
+                [
+                    record.step.clone(),
+                    record.status.to_string(),
+                    record
+                        .offset
+                        .map(|offset| format!("0x{:x}", offset))
+                        .unwrap_or_else(|| "-".to_string()),
+                    record.section.clone().unwrap_or_else(|| "-".to_string()),
+                    hex_preview(&record.original),
+                    hex_preview(&record.replacement),
+                ]
+            })
+            .collect();
+
+        let mut widths = headers.map(|header| header.chars().count());
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let border = |f: &mut fmt::Formatter<'_>, left: &str, mid: &str, right: &str| {
+            let segments: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+            writeln!(f, "{}{}{}", left, segments.join(mid), right)
+        };
+        let line = |f: &mut fmt::Formatter<'_>, cells: &[&str]| {
+            write!(f, "│")?;
+            for (cell, width) in cells.iter().zip(&widths) {
+                let padding = width - cell.chars().count();
+                write!(f, " {}{} │", cell, " ".repeat(padding))?;
+            }
+            writeln!(f)
+        };
+
+        border(f, "┌", "┬", "┐")?;
+        line(f, &headers)?;
+        border(f, "├", "┼", "┤")?;
+        for row in &rows {
+            let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+            line(f, &cells)?;
+        }
+        border(f, "└", "┴", "┘")?;
+
+        if let Some(version) = self.platform.macos_version {
+            writeln!(f, "Platform: macOS {}", version)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patch_report_display() {
+        let mut report = PatchReport::new();
+        report.push(PatchRecord {
+            step: "Portal".to_string(),
+            status: PatchStatus::Applied,
+            required: true,
+            offset: Some(0x1234),
+            section: Some(".rdata".to_string()),
+            original: b".actual.battle.net".to_vec(),
+            replacement: vec![0; 18],
+        });
+        report.push(PatchRecord {
+            step: "CDNs URL".to_string(),
+            status: PatchStatus::Missing,
+            required: false,
+            offset: None,
+            section: None,
+            original: Vec::new(),
+            replacement: Vec::new(),
+        });
+
+        let table = report.to_string();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].starts_with('┌'));
+        assert!(lines[1].contains("Step"));
+        assert!(lines[3].contains("0x1234"));
+        assert!(lines[3].contains("2e61637475616c2e…"));
+        assert!(lines[4].contains("missing"));
+        assert!(lines[5].starts_with('└'));
+
+        // Every line has the same width
+        let width = lines[0].chars().count();
+        assert!(lines.iter().all(|line| line.chars().count() == width));
+
+        assert_eq!(report.applied_count(), 1);
+
+        report.platform.macos_version = Some(MacosVersion {
+            major: 11,
+            minor: 7,
+        });
+        assert!(report.to_string().ends_with("┘\nPlatform: macOS 11.7\n"));
+    }
+
+    #[test]
+    fn test_patch_report_to_json() {
+        let mut report = PatchReport::new();
+        assert_eq!(
+            report.to_json(),
+            r#"{"patches":[],"platform":{"macos_version":null}}"#
+        );
+
+        report.push(PatchRecord {
+            step: "RSA modulus (\"ConnectTo\")".to_string(),
+            status: PatchStatus::Applied,
+            required: true,
+            offset: Some(16),
+            section: Some(".rdata".to_string()),
+            original: vec![0x91, 0xd5],
+            replacement: vec![0x00, 0x01],
+        });
+        report.push(PatchRecord {
+            step: "CDNs URL".to_string(),
+            status: PatchStatus::Skipped,
+            required: false,
+            offset: None,
+            section: None,
+            original: Vec::new(),
+            replacement: Vec::new(),
+        });
+        assert_eq!(
+            report.to_json(),
+            concat!(
+                r#"{"patches":[{"step":"RSA modulus (\"ConnectTo\")","status":"applied","#,
+                r#""required":true,"offset":16,"section":".rdata","original":"91d5","#,
+                r#""replacement":"0001"},{"step":"CDNs URL","status":"skipped","#,
+                r#""required":false,"offset":null,"section":null,"#,
+                r#""original":"","replacement":""}],"platform":{"macos_version":null}}"#
+            )
+        );
+        assert_eq!(json_string("a\nb\u{1}"), r#""a\nb\u0001""#);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_patch_report_serde() {
+        let mut report = PatchReport::new();
+        report.push(PatchRecord {
+            step: "Portal".to_string(),
+            status: PatchStatus::Found,
+            required: true,
+            offset: Some(0x1234),
+            section: Some(".rdata".to_string()),
+            original: b".actual".to_vec(),
+            replacement: vec![0; 7],
+        });
+        report.platform.macos_version = Some(MacosVersion {
+            major: 14,
+            minor: 2,
+        });
+
+        // Serde and to_json produce the same document
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(json, report.to_json());
+        assert!(json.contains(r#""status":"found""#));
+        assert!(json.contains(r#""original":"2e61637475616c""#));
+        assert!(json.contains(r#""macos_version":"14.2""#));
+        assert_eq!(serde_json::from_str::<PatchReport>(&json).unwrap(), report);
+    }
+
+    #[test]
+    fn test_console_reporter_modes() {
+        assert!(ConsoleReporter::new(ReportMode::Verbose).is_verbose());
+        for mode in [
+            ReportMode::Silent,
+            ReportMode::Quiet,
+            ReportMode::Normal,
+            ReportMode::Json,
+        ] {
+            assert!(!ConsoleReporter::new(mode).is_verbose());
+        }
+        assert_eq!(ReportMode::default(), ReportMode::Normal);
+
+        // Only JSON output holds warnings back for the final object
+        let mut reporter = ConsoleReporter::new(ReportMode::Json);
+        reporter.warning("old WINE");
+        assert_eq!(reporter.json_warnings(), r#"["old WINE"]"#);
+        let mut reporter = ConsoleReporter::new(ReportMode::Silent);
+        reporter.warning("old WINE");
+        assert!(reporter.warnings.is_empty());
+    }
+}
@@ -1,3 +1,7 @@
+use crate::binary::DataExt;
+use crate::errors::{ErrorCategory, WowPatcherError, new_pattern_missing_error};
+use crate::patterns::cert_bundle_pattern;
+
 pub const RSA_MODULUS: &[u8] = &[
     0x5F, 0xD6, 0x80, 0x0B, 0xA7, 0xFF, 0x01, 0x40, 0xC7, 0xBC, 0x8E, 0xF5, 0x6B, 0x27, 0xB0, 0xBF,
     0xF0, 0x1D, 0x1B, 0xFE, 0xDD, 0x0B, 0x1F, 0x3D, 0xB6, 0x6F, 0x1A, 0x48, 0x0D, 0xFB, 0x51, 0x08,
@@ -22,24 +26,67 @@ pub const CRYPTO_ED25519_PUBLIC_KEY: &[u8] = &[
     0x29, 0xEC, 0x36, 0x7F, 0xB0, 0xF3, 0x41, 0xF2, 0x8E, 0x0F, 0x08, 0xD0, 0x37, 0xBA, 0xFC, 0x69,
 ];
 
-/// Default replacement for version URL - using the Arctium CDN endpoint
-/// The %s placeholders are kept for runtime replacement with region and product
-pub fn get_version_url(build: Option<u32>, region: Option<&str>, product: Option<&str>) -> String {
+/// Original portal host suffix embedded in unpatched clients
+pub const PORTAL_ORIGINAL: &[u8; 18] = b".actual.battle.net";
+
+/// Standard portal replacement, nulling the whole host suffix
+pub const PORTAL_REPLACEMENT: &[u8; 18] = &[0; 18];
+
+/// Location of the version service that generated version URLs point at.
+///
+/// `path_template` may contain `{region}`, `{product}` and `{build}`, which
+/// [`get_version_url`] fills in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionUrlConfig<'a> {
+    pub scheme: &'a str,
+    /// Host name, optionally with a port and a path prefix
+    pub host: &'a str,
+    pub path_template: &'a str,
+}
+
+/// The Arctium CDN, used unless a custom CDN is configured
+pub const ARCTIUM_CDN: VersionUrlConfig<'static> = VersionUrlConfig {
+    scheme: "http",
+    host: "ngdp.arctium.io",
+    path_template: "/{region}/{product}/{build}/versions",
+};
+
+impl<'a> VersionUrlConfig<'a> {
+    /// Build a config serving the Arctium path layout from a base URL such as `http://cdn.lan`.
+    ///
+    /// Anything after the host in `base` is kept as a path prefix. A base
+    /// without a scheme is treated as `http`.
+    pub fn from_base_url(base: &'a str) -> Self {
+        let (scheme, rest) = base.split_once("://").unwrap_or(("http", base));
+        Self {
+            scheme,
+            host: rest.trim_end_matches('/'),
+            path_template: ARCTIUM_CDN.path_template,
+        }
+    }
+}
+
+/// Replacement for the version URL on the configured version service.
+///
+/// Missing region and product become `%s` for the client to substitute at
+/// runtime, and a missing build becomes `latest`.
+pub fn get_version_url(
+    build: Option<u32>,
+    region: Option<&str>,
+    product: Option<&str>,
+    config: &VersionUrlConfig,
+) -> String {
     let region = region.unwrap_or("%s");
     let product = product.unwrap_or("%s");
+    // Fallback to the latest build if the build is unknown
+    let build = build.map_or_else(|| "latest".to_string(), |build| build.to_string());
 
-    if let Some(build) = build {
-        format!(
-            "http://ngdp.arctium.io/{}/{}/{}/versions",
-            region, product, build
-        )
-    } else {
-        // Fallback to default pattern if build is unknown
-        format!(
-            "http://ngdp.arctium.io/{}/{}/latest/versions",
-            region, product
-        )
-    }
+    let path = config
+        .path_template
+        .replace("{region}", region)
+        .replace("{product}", product)
+        .replace("{build}", &build);
+    format!("{}://{}{}", config.scheme, config.host, path)
 }
 
 /// Default replacement for CDNs URL - using the Arctium CDN endpoint
@@ -64,9 +111,10 @@ pub fn get_unified_api_url(build: Option<u32>) -> String {
 }
 
 /// Creates a padded byte array for URL replacement
-/// Since URLs must fit within the original space, we pad with null bytes
+/// Since URLs must fit within the original space, we pad with null bytes.
+/// Trailing `\r` and `\n` left over from a CRLF config file are stripped first.
 pub fn create_url_replacement(url: &str, original_len: usize) -> Vec<u8> {
-    let mut result = url.as_bytes().to_vec();
+    let mut result = strip_line_ending(url).as_bytes().to_vec();
 
     // Ensure we don't exceed original length
     if result.len() > original_len {
@@ -79,10 +127,156 @@ pub fn create_url_replacement(url: &str, original_len: usize) -> Vec<u8> {
     result
 }
 
+/// Like [`create_url_replacement`], but fails instead of truncating a URL
+/// longer than the original.
+pub fn create_url_replacement_checked(
+    url: &str,
+    original_len: usize,
+) -> Result<Vec<u8>, WowPatcherError> {
+    let url = strip_line_ending(url);
+    if url.len() > original_len {
+        return Err(WowPatcherError::new(
+            ErrorCategory::ValidationError,
+            format!(
+                "URL is {} bytes, but the original only has room for {}",
+                url.len(),
+                original_len
+            ),
+        )
+        .with_context("url", url.to_string())
+        .with_suggestion("Use a shorter host name or path for the CDN"));
+    }
+    Ok(create_url_replacement(url, original_len))
+}
+
+/// Remove trailing line ending characters, e.g. the `\r\n` of a line read from a Windows file.
+fn strip_line_ending(url: &str) -> &str {
+    url.trim_end_matches(['\r', '\n'])
+}
+
+/// Insert `:<port>` into the host segment of a URL, replacing any port already there
+pub fn with_port(url: &str, port: u16) -> String {
+    let host_start = url.find("://").map_or(0, |i| i + 3);
+    let host_end = url[host_start..]
+        .find('/')
+        .map_or(url.len(), |i| host_start + i);
+    let host = &url[host_start..host_end];
+    let host = host.rsplit_once(':').map_or(host, |(name, _)| name);
+    format!(
+        "{}{}:{}{}",
+        &url[..host_start],
+        host,
+        port,
+        &url[host_end..]
+    )
+}
+
+/// Size in bytes of the certificate bundle JSON embedded in `data`.
+///
+/// The bundle starts at the first match of [`cert_bundle_pattern`] and ends
+/// at the brace closing its top-level object. Braces inside strings are
+/// skipped. Returns `None` when there is no bundle or it is not closed
+/// before the end of the data or a NUL byte.
+pub fn find_cert_bundle_size(data: &[u8]) -> Option<usize> {
+    let start = data.find_pattern(cert_bundle_pattern())?;
+    json_object_len(&data[start..])
+}
+
+/// Replace the embedded certificate bundle with `replacement_json`.
+///
+/// The replacement must be a single JSON object no larger than the
+/// original bundle. The space left over is filled with spaces, which JSON
+/// parsers skip as whitespace.
+///
+/// # Errors
+///
+/// Returns a patching error if the bundle is not found or not terminated,
+/// and a validation error if the replacement is not a JSON object or does
+/// not fit in the original's space.
+pub fn patch_cert_bundle(data: &mut [u8], replacement_json: &str) -> Result<(), WowPatcherError> {
+    let start = data
+        .find_pattern(cert_bundle_pattern())
+        .ok_or_else(|| new_pattern_missing_error("Certificate bundle not found in executable"))?;
+    let size = find_cert_bundle_size(&data[start..]).ok_or_else(|| {
+        WowPatcherError::new(
+            ErrorCategory::PatchingError,
+            "End of the certificate bundle not found",
+        )
+        .with_offset(start)
+    })?;
+
+    let replacement = replacement_json.trim().as_bytes();
+    if !replacement.starts_with(b"{") || json_object_len(replacement) != Some(replacement.len()) {
+        return Err(WowPatcherError::new(
+            ErrorCategory::ValidationError,
+            "Replacement certificate bundle must be a single JSON object",
+        ));
+    }
+    if replacement.len() > size {
+        return Err(WowPatcherError::new(
+            ErrorCategory::ValidationError,
+            format!(
+                "Certificate bundle is {} bytes, but the original only has room for {}",
+                replacement.len(),
+                size
+            ),
+        )
+        .with_offset(start));
+    }
+
+    let bundle = &mut data[start..start + size];
+    bundle[..replacement.len()].copy_from_slice(replacement);
+    bundle[replacement.len()..].fill(b' ');
+    Ok(())
+}
+
+/// Length of the JSON object at the start of `bytes`, up to and including its closing brace.
+fn json_object_len(bytes: &[u8]) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, &b) in bytes.iter().enumerate() {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                0 => return None,
+                _ => {}
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'{' => depth += 1,
+            b'}' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            0 => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_portal_constants() {
+        let pattern: Vec<u8> = crate::patterns::portal_pattern()
+            .iter()
+            .map(|&b| b as u8)
+            .collect();
+        assert_eq!(PORTAL_ORIGINAL.as_slice(), pattern.as_slice());
+        assert_eq!(PORTAL_REPLACEMENT.len(), PORTAL_ORIGINAL.len());
+        assert!(PORTAL_REPLACEMENT.iter().all(|&b| b == 0));
+    }
+
     #[test]
     fn test_rsa_modulus() {
         assert_eq!(RSA_MODULUS.len(), 256);
@@ -152,22 +346,40 @@ mod tests {
     #[test]
     fn test_get_version_url() {
         // Test with all parameters
-        let url = get_version_url(Some(12345), Some("EU"), Some("wow"));
+        let url = get_version_url(Some(12345), Some("EU"), Some("wow"), &ARCTIUM_CDN);
         assert_eq!(url, "http://ngdp.arctium.io/EU/wow/12345/versions");
 
         // Test with placeholders
-        let url = get_version_url(Some(12345), None, None);
+        let url = get_version_url(Some(12345), None, None, &ARCTIUM_CDN);
         assert_eq!(url, "http://ngdp.arctium.io/%s/%s/12345/versions");
 
         // Test without build
-        let url = get_version_url(None, Some("EU"), Some("wow"));
+        let url = get_version_url(None, Some("EU"), Some("wow"), &ARCTIUM_CDN);
         assert_eq!(url, "http://ngdp.arctium.io/EU/wow/latest/versions");
 
         // Test fallback pattern
-        let url = get_version_url(None, None, None);
+        let url = get_version_url(None, None, None, &ARCTIUM_CDN);
         assert_eq!(url, "http://ngdp.arctium.io/%s/%s/latest/versions");
     }
 
+    #[test]
+    fn test_version_url_config_from_base_url() {
+        let config = VersionUrlConfig::from_base_url("https://cdn.lan:8080/wow/");
+        assert_eq!(config.scheme, "https");
+        assert_eq!(config.host, "cdn.lan:8080/wow");
+        assert_eq!(
+            get_version_url(Some(1), None, None, &config),
+            "https://cdn.lan:8080/wow/%s/%s/1/versions"
+        );
+
+        let config = VersionUrlConfig::from_base_url("cdn.lan");
+        assert_eq!(config.scheme, "http");
+        assert_eq!(
+            get_version_url(None, None, None, &config),
+            "http://cdn.lan/%s/%s/latest/versions"
+        );
+    }
+
     #[test]
     fn test_get_cdns_url() {
         let url = get_cdns_url();
@@ -206,4 +418,77 @@ mod tests {
         assert_eq!(replacement.len(), 10);
         assert_eq!(&replacement, &url.as_bytes()[..10]);
     }
+
+    #[test]
+    fn test_create_url_replacement_strips_crlf() {
+        let replacement = create_url_replacement("http://test.com\r\n", 20);
+        assert_eq!(replacement, create_url_replacement("http://test.com", 20));
+        assert!(!replacement.contains(&b'\r'));
+
+        // The line ending does not count towards the length limit
+        assert!(create_url_replacement_checked("http://test.com\r\n", 15).is_ok());
+    }
+
+    #[test]
+    fn test_create_url_replacement_checked() {
+        let replacement = create_url_replacement_checked("http://test.com", 20).unwrap();
+        assert_eq!(replacement, create_url_replacement("http://test.com", 20));
+
+        let err = create_url_replacement_checked("http://test.com", 10).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+    }
+
+    #[test]
+    fn test_with_port() {
+        assert_eq!(
+            with_port("http://cdn.local/%s/%s/versions", 8080),
+            "http://cdn.local:8080/%s/%s/versions"
+        );
+        assert_eq!(
+            with_port("http://cdn.local:1119/cdns", 8443),
+            "http://cdn.local:8443/cdns"
+        );
+        assert_eq!(with_port("http://cdn.local", 80), "http://cdn.local:80");
+    }
+
+    #[test]
+    fn test_find_cert_bundle_size() {
+        let bundle = br#"{"Created":1700000000,"Certs":[{"Name":"a}{\"b"}]}"#;
+        let mut data = vec![0xAA; 16];
+        data.extend_from_slice(bundle);
+        data.extend_from_slice(b"\0trailing}");
+        assert_eq!(find_cert_bundle_size(&data), Some(bundle.len()));
+
+        // Unterminated bundles and data without one have no size
+        assert_eq!(find_cert_bundle_size(br#"{"Created":1,"Certs":[{}"#), None);
+        assert_eq!(find_cert_bundle_size(b"{\"Created\":1\0}"), None);
+        assert_eq!(find_cert_bundle_size(b"no bundle here"), None);
+    }
+
+    #[test]
+    fn test_patch_cert_bundle() {
+        let bundle = br#"{"Created":1700000000,"Certs":["original"]}"#;
+        let mut data = b"header".to_vec();
+        data.extend_from_slice(bundle);
+        data.extend_from_slice(b"\0footer");
+
+        patch_cert_bundle(&mut data, r#" {"Created":1,"Certs":[]} "#).unwrap();
+        let patched = &data[6..6 + bundle.len()];
+        assert!(patched.starts_with(br#"{"Created":1,"Certs":[]}"#));
+        assert!(patched[24..].iter().all(|&b| b == b' '));
+        assert_eq!(&data[..6], b"header");
+        assert_eq!(&data[6 + bundle.len()..], b"\0footer");
+
+        let original = data.clone();
+        let too_long = format!(r#"{{"Created":1,"Certs":["{}"]}}"#, "x".repeat(64));
+        let err = patch_cert_bundle(&mut data, &too_long).unwrap_err();
+        assert_eq!(err.category, ErrorCategory::ValidationError);
+        assert_eq!(err.offset(), Some(6));
+        assert!(patch_cert_bundle(&mut data, r#"{"Created":1} {}"#).is_err());
+        assert!(patch_cert_bundle(&mut data, "[]").is_err());
+        assert_eq!(data, original);
+
+        let err = patch_cert_bundle(&mut b"nothing".to_vec(), "{}").unwrap_err();
+        assert_eq!(err.category, ErrorCategory::PatchingError);
+    }
 }
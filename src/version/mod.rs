@@ -25,6 +25,30 @@ pub fn built_by() -> &'static str {
     BUILT_BY.get_or_init(|| option_env!("BUILT_BY").unwrap_or("unknown").to_string())
 }
 
+/// Rust target triple the binary was built for.
+pub fn target() -> &'static str {
+    option_env!("TARGET").unwrap_or("unknown")
+}
+
+/// Cargo features enabled in this build.
+pub fn features() -> Vec<&'static str> {
+    [
+        ("cli", cfg!(feature = "cli")),
+        ("cli-interactive", cfg!(feature = "cli-interactive")),
+        ("tokio", cfg!(feature = "tokio")),
+        ("rayon", cfg!(feature = "rayon")),
+        ("tracing", cfg!(feature = "tracing")),
+        ("serde", cfg!(feature = "serde")),
+        ("dev-tools", cfg!(feature = "dev-tools")),
+        ("network", cfg!(feature = "network")),
+        ("wasm", cfg!(feature = "wasm")),
+        ("ffi", cfg!(feature = "ffi")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
+
 pub fn info() -> String {
     format!(
         "wow-patcher {} ({}) built on {} by {}",
@@ -43,17 +67,64 @@ Git Commit:   {}
 Build Date:   {}
 Built By:     {}
 Rust Version: {}
-OS/Arch:      {}/{}"#,
+OS/Arch:      {}/{}
+Target:       {}
+Features:     {}"#,
         version(),
         commit(),
         date(),
         built_by(),
         option_env!("RUSTC_VERSION").unwrap_or("unknown"),
         std::env::consts::OS,
-        std::env::consts::ARCH
+        std::env::consts::ARCH,
+        target(),
+        match features().join(", ") {
+            features if features.is_empty() => "none".to_string(),
+            features => features,
+        }
     )
 }
 
 pub fn short() -> &'static str {
     version()
 }
+
+/// One-line notes for notable client builds, sorted by build number.
+pub const PATCH_NOTES: &[(u16, &str)] = &[
+    (31650, "WoW Classic 1.13.2 launch"),
+    (51666, "Wrath of the Lich King Classic 3.4.3"),
+    (53584, "Dragonflight 10.2.5 Seeds of Renewal"),
+    (55560, "Dragonflight Season 4 pre-patch"),
+];
+
+/// Look up the note for a client build, `None` unless the build is listed exactly.
+pub fn changelog_for_build(build: u16) -> Option<&'static str> {
+    PATCH_NOTES
+        .iter()
+        .find(|(known, _)| *known == build)
+        .map(|(_, note)| *note)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detailed_info_lists_target_and_features() {
+        let info = detailed_info();
+        assert!(info.contains(&format!("Target:       {}", target())));
+        assert_ne!(target(), "unknown");
+        assert_eq!(features().contains(&"cli"), cfg!(feature = "cli"));
+        assert_eq!(features().contains(&"rayon"), cfg!(feature = "rayon"));
+    }
+
+    #[test]
+    fn test_changelog_for_build() {
+        assert_eq!(
+            changelog_for_build(55560),
+            Some("Dragonflight Season 4 pre-patch")
+        );
+        assert_eq!(changelog_for_build(55561), None);
+        assert!(PATCH_NOTES.is_sorted_by_key(|(build, _)| *build));
+    }
+}
@@ -0,0 +1,49 @@
+//! JavaScript bindings for patching executables in the browser.
+//!
+//! Build the npm package with:
+//!
+//! ```text
+//! wasm-pack build --target web --no-default-features --features wasm
+//! ```
+
+use crate::keys::KeyConfig;
+use crate::patcher::Patcher;
+use js_sys::Uint8Array;
+use wasm_bindgen::prelude::*;
+
+/// Patcher exposed to JavaScript, working entirely on in-memory buffers.
+///
+/// There is no file system under WASM, so backups, dry run and code
+/// signature removal are not available.
+#[wasm_bindgen]
+#[derive(Debug, Default)]
+pub struct WasmPatcher {}
+
+#[wasm_bindgen]
+impl WasmPatcher {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Apply all standard patches to `data` with the given hex-encoded keys.
+    ///
+    /// Returns the patched executable, or the error message on failure.
+    #[wasm_bindgen(js_name = patchBytes)]
+    pub fn patch_bytes(
+        &self,
+        data: Uint8Array,
+        rsa_hex: &str,
+        ed25519_hex: &str,
+    ) -> Result<Uint8Array, JsValue> {
+        let key_config = KeyConfig::from_hex(rsa_hex, ed25519_hex).map_err(to_js_error)?;
+        let mut patcher = Patcher::new("");
+        patcher.key_config = Some(key_config);
+        let patched = patcher.patch_bytes(&data.to_vec()).map_err(to_js_error)?;
+        Ok(Uint8Array::from(patched.as_slice()))
+    }
+}
+
+fn to_js_error(err: crate::WowPatcherError) -> JsValue {
+    JsValue::from_str(&err.to_string())
+}
@@ -2,7 +2,7 @@ use std::fs;
 use tempfile::TempDir;
 use wow_patcher::binary::{PatternExt, patch};
 use wow_patcher::patterns::{
-    connect_to_modulus_pattern, crypto_ed_public_key_pattern, portal_pattern,
+    connect_to_modulus_pattern, crypto_ed_public_key_pattern, pattern_by_name, portal_pattern,
 };
 use wow_patcher::trinity::{CRYPTO_ED25519_PUBLIC_KEY, RSA_MODULUS};
 
@@ -10,21 +10,22 @@ fn create_mock_executable() -> Vec<u8> {
     let size = 100 * 1024;
     let mut data = vec![0u8; size];
 
-    for i in 0..size {
-        data[i] = (i % 256) as u8;
+    for (i, byte) in data.iter_mut().enumerate() {
+        *byte = (i % 256) as u8;
     }
 
-    // Insert portal pattern at offset 1000
-    let portal_str = b".actual.battle.net";
-    data[1000..1000 + portal_str.len()].copy_from_slice(portal_str);
-
-    // Insert RSA pattern at offset 5000
-    let rsa_pattern = &[0x91, 0xD5, 0x9B, 0xB7, 0xD4, 0xE1, 0x83, 0xA5];
-    data[5000..5000 + rsa_pattern.len()].copy_from_slice(rsa_pattern);
-
-    // Insert Ed25519 pattern at offset 10000
-    let ed_pattern = &[0x15, 0xD6, 0x18, 0xBD, 0x7D, 0xB5, 0x77, 0xBD];
-    data[10000..10000 + ed_pattern.len()].copy_from_slice(ed_pattern);
+    // Integration tests cannot use `patterns::test_helpers`, which only exists
+    // in the library's unit test build, so embed the pattern bytes directly
+    for (name, offset) in [
+        ("portal", 1000),
+        ("connect_to_modulus", 5000),
+        ("crypto_ed_public_key", 10000),
+    ] {
+        let pattern = pattern_by_name(name).unwrap();
+        for (i, &value) in pattern.iter().enumerate() {
+            data[offset + i] = value as u8;
+        }
+    }
 
     data
 }
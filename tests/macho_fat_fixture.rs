@@ -0,0 +1,233 @@
+use wow_patcher::Patcher;
+use wow_patcher::binary::{
+    Arch, PatternExt, check_offset_section, fat_slices, patch_macho_fat, select_slices,
+};
+use wow_patcher::errors::ErrorCategory;
+use wow_patcher::patterns::pattern_by_name;
+use wow_patcher::report::{PatchStatus, ReportMode};
+use wow_patcher::trinity::RSA_MODULUS;
+
+const CPU_TYPE_X86_64: u32 = 0x0100_0007;
+const CPU_TYPE_ARM64: u32 = 0x0100_000C;
+
+const SLICE_SIZE: usize = 0x1000;
+const TEXT_SECTION_OFFSET: usize = 0x400;
+const DATA_SEGMENT_OFFSET: usize = 0x800;
+
+/// Offsets of the embedded patterns within each slice
+const PORTAL_OFFSET: usize = DATA_SEGMENT_OFFSET + 0x100;
+const CONNECT_TO_OFFSET: usize = DATA_SEGMENT_OFFSET + 0x300;
+/// A copy of the portal pattern in code, which must not be patched
+const TEXT_PORTAL_OFFSET: usize = TEXT_SECTION_OFFSET + 0x10;
+
+fn put_u32(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(data: &mut [u8], offset: usize, value: u64) {
+    data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
+
+fn put_name(data: &mut [u8], offset: usize, name: &[u8]) {
+    data[offset..offset + name.len()].copy_from_slice(name);
+}
+
+fn embed(data: &mut [u8], name: &str, offset: usize) {
+    let pattern = pattern_by_name(name).unwrap();
+    for (i, &value) in pattern.iter().enumerate() {
+        data[offset + i] = value as u8;
+    }
+}
+
+/// Write an `LC_SEGMENT_64` command with a single section covering the whole segment.
+fn put_segment(
+    data: &mut [u8],
+    offset: usize,
+    segment: &[u8],
+    section: &[u8],
+    file_offset: usize,
+    size: usize,
+) {
+    put_u32(data, offset, 0x19);
+    put_u32(data, offset + 4, 72 + 80);
+    put_name(data, offset + 8, segment);
+    put_u64(data, offset + 24, 0x1_0000_0000 + file_offset as u64);
+    put_u64(data, offset + 32, size as u64);
+    put_u64(data, offset + 40, file_offset as u64);
+    put_u64(data, offset + 48, size as u64);
+    put_u32(data, offset + 64, 1);
+
+    let sect = offset + 72;
+    put_name(data, sect, section);
+    put_name(data, sect + 16, segment);
+    put_u64(data, sect + 32, 0x1_0000_0000 + file_offset as u64);
+    put_u64(data, sect + 40, size as u64);
+    put_u32(data, sect + 48, file_offset as u32);
+}
+
+/// Build a thin 64-bit Mach-O with `__TEXT.__text` and `__DATA.__data` sections.
+///
+/// The portal and ConnectTo RSA patterns are embedded in `__DATA.__data`,
+/// and a stray copy of the portal pattern in `__TEXT.__text`.
+fn create_thin_macho(cputype: u32) -> Vec<u8> {
+    let mut data = vec![0u8; SLICE_SIZE];
+
+    // mach_header_64: MH_MAGIC_64, MH_EXECUTE, two load commands
+    put_u32(&mut data, 0, 0xFEED_FACF);
+    put_u32(&mut data, 4, cputype);
+    put_u32(&mut data, 8, if cputype == CPU_TYPE_X86_64 { 3 } else { 0 });
+    put_u32(&mut data, 12, 2);
+    put_u32(&mut data, 16, 2);
+    put_u32(&mut data, 20, 2 * (72 + 80));
+
+    put_segment(
+        &mut data,
+        32,
+        b"__TEXT",
+        b"__text",
+        TEXT_SECTION_OFFSET,
+        DATA_SEGMENT_OFFSET - TEXT_SECTION_OFFSET,
+    );
+    put_segment(
+        &mut data,
+        32 + 72 + 80,
+        b"__DATA",
+        b"__data",
+        DATA_SEGMENT_OFFSET,
+        SLICE_SIZE - DATA_SEGMENT_OFFSET,
+    );
+
+    embed(&mut data, "portal", TEXT_PORTAL_OFFSET);
+    embed(&mut data, "portal", PORTAL_OFFSET);
+    embed(&mut data, "connect_to_modulus", CONNECT_TO_OFFSET);
+    data
+}
+
+/// Build a universal binary with an arm64 slice at 0x1000 and an x86_64 slice at 0x2000.
+fn create_fat_macho() -> Vec<u8> {
+    let mut data = vec![0u8; 3 * SLICE_SIZE];
+    data[0..4].copy_from_slice(&0xCAFE_BABEu32.to_be_bytes());
+    data[4..8].copy_from_slice(&2u32.to_be_bytes());
+    for (index, cputype, subtype) in [(0, CPU_TYPE_ARM64, 0u32), (1, CPU_TYPE_X86_64, 3)] {
+        let arch = 8 + index * 20;
+        let offset = (index + 1) * SLICE_SIZE;
+        data[arch..arch + 4].copy_from_slice(&cputype.to_be_bytes());
+        data[arch + 4..arch + 8].copy_from_slice(&subtype.to_be_bytes());
+        data[arch + 8..arch + 12].copy_from_slice(&(offset as u32).to_be_bytes());
+        data[arch + 12..arch + 16].copy_from_slice(&(SLICE_SIZE as u32).to_be_bytes());
+        data[arch + 16..arch + 20].copy_from_slice(&12u32.to_be_bytes());
+        data[offset..offset + SLICE_SIZE].copy_from_slice(&create_thin_macho(cputype));
+    }
+    data
+}
+
+#[test]
+fn test_fat_slices() {
+    let data = create_fat_macho();
+    let slices = fat_slices(&data).unwrap();
+    assert_eq!(slices.len(), 2);
+    assert_eq!(slices[0].name, "arm64");
+    assert_eq!(slices[0].range, SLICE_SIZE..2 * SLICE_SIZE);
+    assert_eq!(slices[1].name, "x86_64");
+    assert_eq!(slices[1].range, 2 * SLICE_SIZE..3 * SLICE_SIZE);
+
+    let selected = select_slices(&data, Arch::X86_64).unwrap();
+    assert_eq!(selected, vec![slices[1].clone()]);
+
+    // Thin binaries have no slices
+    assert!(fat_slices(&create_thin_macho(CPU_TYPE_ARM64)).is_err());
+}
+
+#[test]
+fn test_fat_section_lookup_uses_slice_header() {
+    let data = create_fat_macho();
+
+    // Offsets are resolved against the header of the slice that holds them
+    let x86_64 = 2 * SLICE_SIZE;
+    let section = check_offset_section(&data, x86_64 + CONNECT_TO_OFFSET).unwrap();
+    assert_eq!(section.name, "__DATA.__data");
+    assert!(section.is_patchable);
+    assert_eq!(section.file_offset, (x86_64 + DATA_SEGMENT_OFFSET) as u64);
+
+    let section = check_offset_section(&data, SLICE_SIZE + TEXT_PORTAL_OFFSET).unwrap();
+    assert_eq!(section.name, "__TEXT.__text");
+    assert!(!section.is_patchable);
+
+    // The fat header itself is not part of any slice
+    assert!(check_offset_section(&data, 0x10).is_none());
+}
+
+#[test]
+fn test_patch_macho_fat() {
+    let original = create_fat_macho();
+    let pattern = pattern_by_name("connect_to_modulus").unwrap();
+    let replacement = &RSA_MODULUS[..pattern.len()];
+
+    let mut data = original.clone();
+    let offsets = patch_macho_fat(&mut data, Arch::X86_64, pattern, replacement).unwrap();
+    assert_eq!(offsets, vec![2 * SLICE_SIZE + CONNECT_TO_OFFSET]);
+    // The arm64 slice is untouched
+    assert_eq!(&data[..2 * SLICE_SIZE], &original[..2 * SLICE_SIZE]);
+
+    let mut data = original.clone();
+    let offsets = patch_macho_fat(&mut data, Arch::All, pattern, replacement).unwrap();
+    assert_eq!(
+        offsets,
+        vec![
+            SLICE_SIZE + CONNECT_TO_OFFSET,
+            2 * SLICE_SIZE + CONNECT_TO_OFFSET
+        ]
+    );
+    assert!(pattern.search_in(&data).first().is_none());
+
+    // The first portal match in each slice is in code, so nothing is written
+    let portal = pattern_by_name("portal").unwrap();
+    let mut data = original.clone();
+    let err = patch_macho_fat(&mut data, Arch::All, portal, &portal.empty()).unwrap_err();
+    assert_eq!(err.category, ErrorCategory::PatchingError);
+    assert_eq!(err.offset(), Some(SLICE_SIZE + TEXT_PORTAL_OFFSET));
+    assert_eq!(data, original);
+}
+
+#[test]
+fn test_patcher_architecture() {
+    // Without the stray copy in code the portal is patched from __DATA
+    let mut input = create_fat_macho();
+    for slice in [SLICE_SIZE, 2 * SLICE_SIZE] {
+        input[slice + TEXT_PORTAL_OFFSET] = 0;
+    }
+    let pattern = pattern_by_name("connect_to_modulus").unwrap();
+    let patcher = Patcher::new("game.bin").report_mode(ReportMode::Silent);
+
+    let mut data = input.clone();
+    let report = patcher
+        .clone()
+        .architecture(Arch::Arm64)
+        .patch_bytes_in_place(&mut data)
+        .unwrap();
+    assert_eq!(
+        pattern.search_in(&data).all(),
+        vec![2 * SLICE_SIZE + CONNECT_TO_OFFSET]
+    );
+    let record = report
+        .patches_applied
+        .iter()
+        .find(|record| record.step == "RSA modulus (ConnectTo) [arm64]")
+        .unwrap();
+    assert_eq!(record.status, PatchStatus::Applied);
+    assert_eq!(record.offset, Some(SLICE_SIZE + CONNECT_TO_OFFSET));
+    assert_eq!(record.section.as_deref(), Some("__DATA.__data"));
+
+    // Every slice is patched by default
+    let patched = patcher.clone().patch_bytes(&input).unwrap();
+    assert!(pattern.search_in(&patched).first().is_none());
+
+    // A universal binary without the requested slice is rejected
+    let mut arm64_only = input[..2 * SLICE_SIZE].to_vec();
+    arm64_only[4..8].copy_from_slice(&1u32.to_be_bytes());
+    let err = patcher
+        .architecture(Arch::X86_64)
+        .patch_bytes(&arm64_only)
+        .unwrap_err();
+    assert_eq!(err.category, ErrorCategory::ValidationError);
+}
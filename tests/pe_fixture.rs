@@ -0,0 +1,406 @@
+use std::fs;
+use tempfile::TempDir;
+use wow_patcher::binary::{PatternExt, check_offset_section};
+use wow_patcher::cmd::execute::{PatchOptions, execute_patch};
+use wow_patcher::errors::ErrorCategory;
+use wow_patcher::patterns::pattern_by_name;
+use wow_patcher::platform::{Version, extract_version_from_data};
+use wow_patcher::report::{PatchStatus, ReportMode};
+use wow_patcher::trinity::RSA_MODULUS;
+
+const FILE_ALIGNMENT: u32 = 0x200;
+const SECTION_ALIGNMENT: u32 = 0x1000;
+const HEADERS_SIZE: usize = 0x400;
+
+const TEXT_OFFSET: usize = HEADERS_SIZE;
+const TEXT_SIZE: usize = 0x200;
+const RDATA_OFFSET: usize = TEXT_OFFSET + TEXT_SIZE;
+const RDATA_SIZE: usize = 0x1000;
+const RSRC_OFFSET: usize = RDATA_OFFSET + RDATA_SIZE;
+const RSRC_SIZE: usize = 0x200;
+const RSRC_RVA: u32 = 3 * SECTION_ALIGNMENT;
+
+const PORTAL_OFFSET: usize = RDATA_OFFSET + 0x100;
+const CONNECT_TO_OFFSET: usize = RDATA_OFFSET + 0x400;
+
+fn put_u16(data: &mut [u8], offset: usize, value: u16) {
+    data[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Write a 40 byte section header (name, virtual size and address, raw size and offset, flags).
+fn put_section_header(
+    data: &mut [u8],
+    offset: usize,
+    name: &[u8],
+    virtual_address: u32,
+    raw_offset: usize,
+    raw_size: usize,
+    characteristics: u32,
+) {
+    data[offset..offset + name.len()].copy_from_slice(name);
+    put_u32(data, offset + 8, raw_size as u32);
+    put_u32(data, offset + 12, virtual_address);
+    put_u32(data, offset + 16, raw_size as u32);
+    put_u32(data, offset + 20, raw_offset as u32);
+    put_u32(data, offset + 36, characteristics);
+}
+
+/// Write a resource directory with a single ID entry pointing at `target`.
+fn put_resource_directory(data: &mut [u8], offset: usize, id: u32, target: u32) {
+    put_u16(data, offset + 14, 1);
+    put_u32(data, offset + 16, id);
+    put_u32(data, offset + 20, target);
+}
+
+/// Write the `.rsrc` contents: an `RT_VERSION` resource for file version 11.0.2.56382.
+fn put_version_resource(data: &mut [u8]) {
+    let rsrc = RSRC_OFFSET;
+    const SUBDIRECTORY: u32 = 0x8000_0000;
+
+    // RT_VERSION -> name 1 -> US English -> data entry
+    put_resource_directory(data, rsrc, 16, SUBDIRECTORY | 0x18);
+    put_resource_directory(data, rsrc + 0x18, 1, SUBDIRECTORY | 0x30);
+    put_resource_directory(data, rsrc + 0x30, 0x409, 0x48);
+    put_u32(data, rsrc + 0x48, RSRC_RVA + 0x60);
+    put_u32(data, rsrc + 0x4C, 92);
+
+    // VS_VERSIONINFO header and key, then VS_FIXEDFILEINFO on a DWORD boundary
+    let info = rsrc + 0x60;
+    put_u16(data, info, 92);
+    put_u16(data, info + 2, 52);
+    for (i, unit) in "VS_VERSION_INFO".encode_utf16().enumerate() {
+        put_u16(data, info + 6 + 2 * i, unit);
+    }
+    let fixed = info + 40;
+    put_u32(data, fixed, 0xFEEF_04BD);
+    put_u32(data, fixed + 4, 0x0001_0000);
+    put_u32(data, fixed + 8, 11 << 16);
+    put_u32(data, fixed + 12, (2 << 16) | 56382);
+}
+
+/// Build a minimal PE32+ image with `.text`, `.rdata` and `.rsrc` sections.
+///
+/// The portal and ConnectTo RSA patterns are embedded in `.rdata`, where
+/// the patcher expects to find them in a real client, and `.rsrc` holds a
+/// version resource.
+fn create_pe_fixture() -> Vec<u8> {
+    let mut data = vec![0u8; RSRC_OFFSET + RSRC_SIZE];
+
+    // DOS header pointing at the PE header
+    data[0..2].copy_from_slice(b"MZ");
+    put_u32(&mut data, 0x3C, 0x80);
+
+    // PE signature and COFF header: AMD64, two sections, executable large-address-aware image
+    data[0x80..0x84].copy_from_slice(b"PE\0\0");
+    put_u16(&mut data, 0x84, 0x8664);
+    put_u16(&mut data, 0x86, 3);
+    put_u16(&mut data, 0x94, 240);
+    put_u16(&mut data, 0x96, 0x22);
+
+    // PE32+ optional header
+    let opt = 0x98;
+    put_u16(&mut data, opt, 0x20B);
+    put_u32(&mut data, opt + 16, SECTION_ALIGNMENT);
+    data[opt + 24..opt + 32].copy_from_slice(&0x1_4000_0000u64.to_le_bytes());
+    put_u32(&mut data, opt + 32, SECTION_ALIGNMENT);
+    put_u32(&mut data, opt + 36, FILE_ALIGNMENT);
+    put_u32(&mut data, opt + 56, 4 * SECTION_ALIGNMENT);
+    put_u32(&mut data, opt + 60, HEADERS_SIZE as u32);
+    put_u16(&mut data, opt + 68, 3);
+    put_u32(&mut data, opt + 108, 16);
+    // Resource table, data directory entry 2
+    put_u32(&mut data, opt + 128, RSRC_RVA);
+    put_u32(&mut data, opt + 132, RSRC_SIZE as u32);
+
+    // Section table: code first, then read-only data and resources
+    let sections = opt + 240;
+    put_section_header(
+        &mut data,
+        sections,
+        b".text",
+        SECTION_ALIGNMENT,
+        TEXT_OFFSET,
+        TEXT_SIZE,
+        0x6000_0020,
+    );
+    put_section_header(
+        &mut data,
+        sections + 40,
+        b".rdata",
+        2 * SECTION_ALIGNMENT,
+        RDATA_OFFSET,
+        RDATA_SIZE,
+        0x4000_0040,
+    );
+    put_section_header(
+        &mut data,
+        sections + 80,
+        b".rsrc",
+        RSRC_RVA,
+        RSRC_OFFSET,
+        RSRC_SIZE,
+        0x4000_0040,
+    );
+
+    // A `ret` so .text is not empty
+    data[TEXT_OFFSET] = 0xC3;
+
+    for (name, offset) in [
+        ("portal", PORTAL_OFFSET),
+        ("connect_to_modulus", CONNECT_TO_OFFSET),
+    ] {
+        let pattern = pattern_by_name(name).unwrap();
+        for (i, &value) in pattern.iter().enumerate() {
+            data[offset + i] = value as u8;
+        }
+    }
+    put_version_resource(&mut data);
+
+    data
+}
+
+#[test]
+fn test_pe_fixture_sections() {
+    let data = create_pe_fixture();
+
+    let section = check_offset_section(&data, CONNECT_TO_OFFSET).unwrap();
+    assert_eq!(section.name, ".rdata");
+    assert!(section.is_patchable);
+    assert_eq!(section.file_offset, RDATA_OFFSET as u64);
+    assert_eq!(section.raw_size, RDATA_SIZE as u64);
+
+    let text = check_offset_section(&data, TEXT_OFFSET).unwrap();
+    assert_eq!(text.name, ".text");
+    assert!(!text.is_patchable);
+
+    // The headers are not part of any section
+    assert!(check_offset_section(&data, 0x10).is_none());
+}
+
+#[test]
+fn test_pattern_search_in_pe_sections() {
+    let data = create_pe_fixture();
+    let pattern = pattern_by_name("connect_to_modulus").unwrap();
+
+    let search = pattern.search_in(&data);
+    assert_eq!(search.first(), Some(CONNECT_TO_OFFSET));
+    assert_eq!(search.in_section(&data, ".rdata"), Some(CONNECT_TO_OFFSET));
+    assert_eq!(search.in_section(&data, ".text"), None);
+    assert_eq!(search.in_section(&data, ".missing"), None);
+}
+
+#[test]
+fn test_execute_patch_on_pe_fixture() {
+    let temp_dir = TempDir::new().unwrap();
+    let client_dir = temp_dir.path().join("_retail_");
+    fs::create_dir(&client_dir).unwrap();
+    let input_file = client_dir.join("Wow.exe");
+    let output_file = client_dir.join("Wow-patched.exe");
+    let original = create_pe_fixture();
+    fs::write(&input_file, &original).unwrap();
+
+    let opts = PatchOptions {
+        report_mode: ReportMode::Silent,
+        ..Default::default()
+    };
+    let report = execute_patch(&input_file, &output_file, opts).unwrap();
+
+    for step in ["Portal", "RSA modulus (ConnectTo)"] {
+        let record = report
+            .patches_applied
+            .iter()
+            .find(|record| record.step == step)
+            .unwrap_or_else(|| panic!("no record for {}", step));
+        assert_eq!(record.status, PatchStatus::Applied);
+        assert!(record.required);
+        assert_eq!(record.section.as_deref(), Some(".rdata"));
+    }
+
+    let patched = fs::read(&output_file).unwrap();
+    assert_eq!(patched.len(), original.len());
+    // Only the matched pattern bytes are overwritten with the start of the modulus
+    let pattern_len = pattern_by_name("connect_to_modulus").unwrap().len();
+    assert_eq!(
+        &patched[CONNECT_TO_OFFSET..CONNECT_TO_OFFSET + pattern_len],
+        &RSA_MODULUS[..pattern_len]
+    );
+    // Everything outside .rdata is left untouched
+    assert_eq!(&patched[..RDATA_OFFSET], &original[..RDATA_OFFSET]);
+
+    // The patched output still parses as a PE with the same sections
+    let section = check_offset_section(&patched, CONNECT_TO_OFFSET).unwrap();
+    assert_eq!(section.name, ".rdata");
+}
+
+#[test]
+fn test_failed_write_leaves_no_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let client_dir = temp_dir.path().join("_retail_");
+    fs::create_dir(&client_dir).unwrap();
+    let input_file = client_dir.join("Wow.exe");
+    fs::write(&input_file, create_pe_fixture()).unwrap();
+    let silent = || PatchOptions {
+        report_mode: ReportMode::Silent,
+        ..Default::default()
+    };
+
+    // The output directory is a file, so the temporary file cannot be created
+    let blocker = client_dir.join("not_a_directory");
+    fs::write(&blocker, b"").unwrap();
+    let err = execute_patch(&input_file, &blocker.join("Wow-patched.exe"), silent()).unwrap_err();
+    assert_eq!(err.category, ErrorCategory::FileOperationError);
+
+    // The output is a directory, so the complete temporary file cannot be renamed over it
+    let output_dir = client_dir.join("Wow-patched.exe");
+    fs::create_dir(&output_dir).unwrap();
+    fs::write(output_dir.join("keep"), b"").unwrap();
+    let err = execute_patch(&input_file, &output_dir, silent()).unwrap_err();
+    assert_eq!(err.category, ErrorCategory::FileOperationError);
+    assert!(output_dir.join("keep").exists());
+
+    // Neither failure left a temporary file next to the output
+    let mut names: Vec<_> = fs::read_dir(&client_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["Wow-patched.exe", "Wow.exe", "not_a_directory"]);
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn test_patch_command_on_pe_fixture() {
+    use wow_patcher::cli::{PatchArgs, patch_command};
+
+    let temp_dir = TempDir::new().unwrap();
+    let input_file = temp_dir.path().join("Wow.exe");
+    let backup_dir = temp_dir.path().join("backups");
+    fs::create_dir(&backup_dir).unwrap();
+    let original = create_pe_fixture();
+    fs::write(&input_file, &original).unwrap();
+
+    patch_command(PatchArgs {
+        location: Some(input_file.display().to_string()),
+        output_suffix: Some("-tc".to_string()),
+        backup_dir: Some(backup_dir.display().to_string()),
+        ..PatchArgs::default()
+    })
+    .unwrap();
+
+    let patched = fs::read(temp_dir.path().join("Wow-tc.exe")).unwrap();
+    let pattern_len = pattern_by_name("connect_to_modulus").unwrap().len();
+    assert_eq!(
+        &patched[CONNECT_TO_OFFSET..CONNECT_TO_OFFSET + pattern_len],
+        &RSA_MODULUS[..pattern_len]
+    );
+    let backups: Vec<_> = fs::read_dir(&backup_dir).unwrap().collect();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(
+        fs::read(backups[0].as_ref().unwrap().path()).unwrap(),
+        original
+    );
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn test_patch_command_rejects_url_longer_than_pattern() {
+    use wow_patcher::cli::{ExitReason, PatchArgs, patch_command};
+    use wow_patcher::patterns::cdns_url_pattern;
+
+    let temp_dir = TempDir::new().unwrap();
+    let input_file = temp_dir.path().join("Wow.exe");
+    let output_file = temp_dir.path().join("Wow-patched.exe");
+    fs::write(&input_file, create_pe_fixture()).unwrap();
+
+    // Well under the old 512 character limit, but one byte longer than the pattern
+    let cdns_url = format!(
+        "http://{}",
+        "a".repeat(cdns_url_pattern().len() + 1 - "http://".len())
+    );
+    let err = patch_command(PatchArgs {
+        location: Some(input_file.display().to_string()),
+        output: Some(output_file.display().to_string()),
+        cdns_url: Some(cdns_url),
+        ..PatchArgs::default()
+    })
+    .unwrap_err();
+
+    assert!(err.to_string().contains("URLs too long for their patterns"));
+    assert_eq!(
+        ExitReason::from_error(err.as_ref()),
+        ExitReason::InvalidInput
+    );
+    assert!(!output_file.exists());
+}
+
+#[test]
+fn test_dry_run_report_on_pe_fixture() {
+    let temp_dir = TempDir::new().unwrap();
+    let client_dir = temp_dir.path().join("_retail_");
+    fs::create_dir(&client_dir).unwrap();
+    let input_file = client_dir.join("Wow.exe");
+    let output_file = client_dir.join("Wow-patched.exe");
+    fs::write(&input_file, create_pe_fixture()).unwrap();
+
+    let opts = PatchOptions {
+        dry_run: true,
+        report_mode: ReportMode::Silent,
+        ..Default::default()
+    };
+    let report = execute_patch(&input_file, &output_file, opts).unwrap();
+    assert!(!output_file.exists());
+    assert_eq!(report.applied_count(), 0);
+
+    // Each patch that would be applied is reported as found, with its replacement
+    let pattern_len = pattern_by_name("connect_to_modulus").unwrap().len();
+    let record = report
+        .patches_applied
+        .iter()
+        .find(|record| record.step == "RSA modulus (ConnectTo)")
+        .unwrap();
+    assert_eq!(record.status, PatchStatus::Found);
+    assert!(record.required);
+    assert_eq!(record.offset, Some(CONNECT_TO_OFFSET));
+    assert_eq!(record.section.as_deref(), Some(".rdata"));
+    assert_eq!(record.bytes_replaced(), pattern_len);
+    assert_eq!(record.replacement, RSA_MODULUS[..pattern_len]);
+
+    let record = report
+        .patches_applied
+        .iter()
+        .find(|record| record.step == "Version URL")
+        .unwrap();
+    assert_eq!(record.status, PatchStatus::Missing);
+    assert!(!record.required);
+
+    // A missing required pattern fails the dry run as it would fail patching
+    let mut data = create_pe_fixture();
+    data[CONNECT_TO_OFFSET] ^= 0xFF;
+    fs::write(&input_file, data).unwrap();
+    let opts = PatchOptions {
+        dry_run: true,
+        report_mode: ReportMode::Silent,
+        ..Default::default()
+    };
+    let err = execute_patch(&input_file, &output_file, opts).unwrap_err();
+    assert_eq!(err.category, ErrorCategory::PatchingError);
+    assert!(!output_file.exists());
+}
+
+#[test]
+fn test_pe_version_resource() {
+    let mut data = create_pe_fixture();
+    assert_eq!(
+        extract_version_from_data(&data),
+        Some(Version::new(11, 0, 2, 56382))
+    );
+    assert_eq!(Version::from_binary(&data).unwrap().build, 56382);
+
+    // Without an RT_VERSION resource there is nothing to read
+    put_u32(&mut data, RSRC_OFFSET + 16, 24);
+    assert_eq!(extract_version_from_data(&data), None);
+}